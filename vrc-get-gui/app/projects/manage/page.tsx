@@ -43,10 +43,13 @@ import {
 	projectResolve,
 	projectUpgradeMultiplePackage,
 	TauriBasePackageInfo,
+	TauriInstallProgress,
 	TauriPackage,
 	TauriPackageChange,
 	TauriPendingProjectChanges,
 	TauriProjectDetails,
+	TauriRejectReason,
+	TauriResolutionExplanation,
 	TauriUnityVersions,
 	TauriUserRepository,
 	TauriVersion,
@@ -56,6 +59,7 @@ import {compareUnityVersion, compareVersion, toVersionString} from "@/lib/versio
 import {VGOption, VGSelect} from "@/components/select";
 import {useOpenUnity} from "@/lib/use-open-unity";
 import {nop} from "@/lib/nop";
+import {callAsyncCommand} from "@/lib/call-async-command";
 import {shellOpen} from "@/lib/shellOpen";
 import {toastError, toastSuccess, toastThrownError} from "@/lib/toast";
 import {useRemoveProjectModal} from "@/lib/remove-project";
@@ -90,8 +94,45 @@ type InstallStatus = {
 	status: "promptingChanges";
 	changes: TauriPendingProjectChanges;
 	requested: RequestedOperation;
+} | {
+	status: "showingDependencyNotFound";
+	error: DependencyNotFoundError;
+} | {
+	status: "promptingLocalModifications";
+	changes: TauriPendingProjectChanges;
+	requested: RequestedOperation;
+	packages: LocallyModifiedPackage[];
 } | {
 	status: "applyingChanges";
+	progress: TauriInstallProgress | null;
+}
+
+// the error thrown by projectApplyPendingChanges if the changes would overwrite local modifications
+type LocallyModifiedPackage = {
+	name: string;
+	modified_files: string[];
+}
+
+// the error thrown by the commands creating changes if the dependency cannot be resolved
+type DependencyNotFoundError = {
+	type: "DependencyNotFound";
+	message: string;
+	dependency_name: string;
+	explanation: TauriResolutionExplanation;
+}
+
+// shows the error of creating changes and returns the next status
+function statusForCreateChangesError(error: any): InstallStatus {
+	if (typeof error === 'object' && error != null && 'type' in error && error.type === "DependencyNotFound")
+		return {status: "showingDependencyNotFound", error};
+	toastThrownError(error);
+	return {status: "normal"};
+}
+
+function localModificationsOfError(error: any): LocallyModifiedPackage[] | null {
+	if (typeof error === 'object' && error != null && 'type' in error && error.type === "LocalModifications")
+		return error.packages;
+	return null;
 }
 
 type BulkUpdateMode = 'install' | 'upgradeOrRemove' | 'remove' | 'upgrade' | 'any';
@@ -239,8 +280,7 @@ function PageBody() {
 			setInstallStatus({status: "promptingChanges", changes, requested: {type: "install", pkg}});
 		} catch (e) {
 			console.error(e);
-			setInstallStatus({status: "normal"});
-			toastThrownError(e);
+			setInstallStatus(statusForCreateChangesError(e));
 		}
 	}, [projectPath]);
 
@@ -264,8 +304,7 @@ function PageBody() {
 			setInstallStatus({status: "promptingChanges", changes, requested: {type: "upgradeAll"}});
 		} catch (e) {
 			console.error(e);
-			setInstallStatus({status: "normal"});
-			toastThrownError(e);
+			setInstallStatus(statusForCreateChangesError(e));
 		}
 	}
 
@@ -276,8 +315,7 @@ function PageBody() {
 			setInstallStatus({status: "promptingChanges", changes, requested: {type: "upgradeAll"}});
 		} catch (e) {
 			console.error(e);
-			setInstallStatus({status: "normal"});
-			toastThrownError(e);
+			setInstallStatus(statusForCreateChangesError(e));
 		}
 	};
 
@@ -319,8 +357,7 @@ function PageBody() {
 			setInstallStatus({status: "promptingChanges", changes, requested: {type: "upgradeAll"}});
 		} catch (e) {
 			console.error(e);
-			setInstallStatus({status: "normal"});
-			toastThrownError(e);
+			setInstallStatus(statusForCreateChangesError(e));
 		}
 	};
 
@@ -349,8 +386,7 @@ function PageBody() {
 			setInstallStatus({status: "promptingChanges", changes, requested: {type: "bulkInstalled"}});
 		} catch (e) {
 			console.error(e);
-			setInstallStatus({status: "normal"});
-			toastThrownError(e);
+			setInstallStatus(statusForCreateChangesError(e));
 		}
 	};
 
@@ -387,10 +423,14 @@ function PageBody() {
 		}: {
 			changes: TauriPendingProjectChanges,
 			requested: RequestedOperation,
-		}) => {
+		},
+		overwriteLocalModifications: boolean = false,
+	) => {
 		try {
-			setInstallStatus({status: "applyingChanges"});
-			await projectApplyPendingChanges(projectPath, changes.changes_version);
+			setInstallStatus({status: "applyingChanges", progress: null});
+			const [_, promise] = callAsyncCommand(projectApplyPendingChanges, [projectPath, changes.changes_version, overwriteLocalModifications],
+				progress => setInstallStatus({status: "applyingChanges", progress}));
+			await promise;
 			setInstallStatus({status: "normal"});
 			onRefreshProject();
 
@@ -415,6 +455,11 @@ function PageBody() {
 					let _: never = requested;
 			}
 		} catch (e) {
+			const modifiedPackages = localModificationsOfError(e);
+			if (modifiedPackages != null) {
+				setInstallStatus({status: "promptingLocalModifications", changes, requested, packages: modifiedPackages});
+				return;
+			}
 			console.error(e);
 			setInstallStatus({status: "normal"});
 			toastThrownError(e);
@@ -467,6 +512,23 @@ function PageBody() {
 				apply={() => applyChanges(installStatus)}
 			/>;
 			break;
+		case "showingDependencyNotFound":
+			dialogForState = <DependencyNotFoundDialog
+				packages={packageRowsData}
+				error={installStatus.error}
+				close={() => setInstallStatus({status: "normal"})}
+			/>;
+			break;
+		case "promptingLocalModifications":
+			dialogForState = <LocalModificationsDialog
+				packages={installStatus.packages}
+				cancel={() => setInstallStatus({status: "normal"})}
+				overwrite={() => applyChanges(installStatus, true)}
+			/>;
+			break;
+		case "applyingChanges":
+			dialogForState = <ApplyingChangesDialog progress={installStatus.progress}/>;
+			break;
 	}
 
 	return (
@@ -758,6 +820,7 @@ function ProjectChangesDialog(
 	}) {
 	const versionConflicts = changes.conflicts.filter(([_, c]) => c.packages.length > 0);
 	const unityConflicts = changes.conflicts.filter(([_, c]) => c.unity_conflict);
+	const holdConflicts = changes.conflicts.filter(([_, c]) => c.hold_conflict);
 
 	const getPackageDisplayName = useMemo(() => {
 		const packagesById = new Map(packages.map(p => [p.id, p]));
@@ -808,6 +871,10 @@ function ProjectChangesDialog(
 									return <TypographyItem key={pkgId}>
 										{tc("projects:manage:dialog:uninstall package as unused", {name})}
 									</TypographyItem>
+								case "Dependent":
+									return <TypographyItem key={pkgId}>
+										{tc("projects:manage:dialog:uninstall package as dependent", {name})}
+									</TypographyItem>
 							}
 						}
 					})}
@@ -821,12 +888,17 @@ function ProjectChangesDialog(
 							<List>
 								{versionConflicts.map(([pkgId, conflict]) => {
 									return (
-										<TypographyItem key={pkgId}>
-											{tc("projects:manage:dialog:conflicts with", {
-												pkg: getPackageDisplayName(pkgId),
-												other: conflict.packages.map(p => getPackageDisplayName(p)).join(", ")
-											})}
-										</TypographyItem>
+										<ListItem key={pkgId} className={"flex-col items-start"}>
+											<Typography className={"font-normal"}>
+												{tc("projects:manage:dialog:conflicts with", {
+													pkg: getPackageDisplayName(pkgId),
+													other: conflict.packages.map(p => getPackageDisplayName(p)).join(", ")
+												})}
+											</Typography>
+											{conflict.explanation &&
+												<ConflictExplanation explanation={conflict.explanation}
+																						 getPackageDisplayName={getPackageDisplayName}/>}
+										</ListItem>
 									);
 								})}
 							</List>
@@ -849,6 +921,22 @@ function ProjectChangesDialog(
 						</>
 					) : null
 				}
+				{
+					holdConflicts.length > 0 ? (
+						<>
+							<Typography className={"text-red-700"}>
+								{tc("projects:manage:dialog:held package conflicts")}
+							</Typography>
+							<List>
+								{holdConflicts.map(([pkgId, _]) => (
+									<TypographyItem key={pkgId}>
+										{tc("projects:manage:dialog:package is held", {pkg: getPackageDisplayName(pkgId)})}
+									</TypographyItem>
+								))}
+							</List>
+						</>
+					) : null
+				}
 				{
 					changes.remove_legacy_files.length > 0 || changes.remove_legacy_folders.length > 0 ? (
 						<>
@@ -879,6 +967,187 @@ function ProjectChangesDialog(
 	);
 }
 
+function DependencyNotFoundDialog(
+	{
+		packages,
+		error,
+		close,
+	}: {
+		packages: PackageRowInfo[],
+		error: DependencyNotFoundError,
+		close: () => void,
+	}) {
+	const getPackageDisplayName = useMemo(() => {
+		const packagesById = new Map(packages.map(p => [p.id, p]));
+		return (pkgId: string) => packagesById.get(pkgId)?.displayName ?? pkgId;
+	}, [packages]);
+
+	return (
+		<Dialog open handler={nop} className={"whitespace-normal"}>
+			<DialogHeader>{tc("projects:manage:dialog:dependency not found")}</DialogHeader>
+			<DialogBody className={"overflow-y-auto max-h-[50vh]"}>
+				<Typography className={"text-red-700"}>
+					{tc("projects:manage:dialog:dependency not found description", {pkg: getPackageDisplayName(error.dependency_name)})}
+				</Typography>
+				<ConflictExplanation explanation={error.explanation}
+														 getPackageDisplayName={getPackageDisplayName}/>
+			</DialogBody>
+			<DialogFooter>
+				<Button onClick={close}>{tc("general:button:close")}</Button>
+			</DialogFooter>
+		</Dialog>
+	);
+}
+
+function LocalModificationsDialog(
+	{
+		packages,
+		cancel,
+		overwrite,
+	}: {
+		packages: LocallyModifiedPackage[],
+		cancel: () => void,
+		overwrite: () => void,
+	}) {
+	return (
+		<Dialog open handler={nop} className={"whitespace-normal"}>
+			<DialogHeader>{tc("projects:manage:button:apply changes")}</DialogHeader>
+			<DialogBody className={"overflow-y-auto max-h-[50vh]"}>
+				<Typography className={"text-red-700"}>
+					{tc("projects:manage:dialog:local modifications description")}
+				</Typography>
+				<List>
+					{packages.flatMap(pkg => pkg.modified_files.map(file => (
+						<ListItem key={file}><Typography className={"font-normal"}>{file}</Typography></ListItem>
+					)))}
+				</List>
+			</DialogBody>
+			<DialogFooter>
+				<Button onClick={cancel} className="mr-1">{tc("general:button:cancel")}</Button>
+				<Button onClick={overwrite} color={"red"}>{tc("projects:manage:button:overwrite")}</Button>
+			</DialogFooter>
+		</Dialog>
+	);
+}
+
+function ApplyingChangesDialog(
+	{
+		progress,
+	}: {
+		progress: TauriInstallProgress | null,
+	}) {
+	let message: React.ReactNode;
+	let ratio: number | null = null;
+
+	if (progress == null) {
+		message = tc("projects:manage:dialog:applying changes...");
+	} else {
+		switch (progress.type) {
+			case "Downloading":
+				message = progress.total != null
+					? tc("projects:manage:dialog:downloading package with size", {
+						name: progress.package,
+						downloaded: formatBytes(progress.downloaded),
+						total: formatBytes(progress.total),
+					})
+					: tc("projects:manage:dialog:downloading package", {
+						name: progress.package,
+						downloaded: formatBytes(progress.downloaded),
+					});
+				if (progress.total != null) ratio = progress.downloaded / Math.max(progress.total, 1);
+				break;
+			case "Extracting":
+				message = tc("projects:manage:dialog:extracting package", {
+					name: progress.package,
+					extracted: progress.extracted,
+					total: progress.total,
+				});
+				ratio = progress.extracted / Math.max(progress.total, 1);
+				break;
+			case "Installed":
+				message = tc("projects:manage:dialog:package installed", {name: progress.package});
+				break;
+			default:
+				let _: never = progress;
+		}
+	}
+
+	return (
+		<Dialog open handler={nop} className={"whitespace-normal"}>
+			<DialogHeader>{tc("projects:manage:button:apply changes")}</DialogHeader>
+			<DialogBody>
+				<Typography className={"text-gray-900"}>{message}</Typography>
+				{ratio != null && (
+					<div className={"w-full h-2 mt-2 bg-gray-200 rounded"}>
+						<div className={"h-2 bg-blue-500 rounded"} style={{width: `${Math.min(ratio, 1) * 100}%`}}/>
+					</div>
+				)}
+			</DialogBody>
+		</Dialog>
+	);
+}
+
+function formatBytes(bytes: number): string {
+	const units = ["B", "KiB", "MiB", "GiB"];
+	let value = bytes;
+	let unit = 0;
+	while (value >= 1024 && unit < units.length - 1) {
+		value /= 1024;
+		unit++;
+	}
+	return unit == 0 ? `${bytes} ${units[0]}` : `${value.toFixed(1)} ${units[unit]}`;
+}
+
+function ConflictExplanation(
+	{
+		explanation,
+		getPackageDisplayName,
+	}: {
+		explanation: TauriResolutionExplanation,
+		getPackageDisplayName: (pkgId: string) => string,
+	}) {
+	const rejectReasonName = (reason: TauriRejectReason) => {
+		switch (reason) {
+			case "OutOfRange":
+				return tc("projects:manage:dialog:reject reason:out of range");
+			case "Yanked":
+				return tc("projects:manage:dialog:reject reason:yanked");
+			case "IncompatibleUnity":
+				return tc("projects:manage:dialog:reject reason:incompatible unity");
+			case "Prerelease":
+				return tc("projects:manage:dialog:reject reason:prerelease");
+		}
+	};
+
+	return (
+		<ul className={"list-disc pl-5 text-sm"}>
+			{explanation.version &&
+				<li>{tc("projects:manage:dialog:conflict selected version", {version: toVersionString(explanation.version)})}</li>}
+			{explanation.requirement_chain.length > 1 &&
+				<li>{tc("projects:manage:dialog:conflict required through", {
+					chain: explanation.requirement_chain.map(p => getPackageDisplayName(p)).join(" → ")
+				})}</li>}
+			{explanation.requirements.map((requirement, i) => (
+				<li key={`requirement-${i}`} className={requirement.satisfied ? "" : "text-red-700"}>
+					{tc("projects:manage:dialog:conflict requirement", {
+						dependent: requirement.dependent != null
+							? getPackageDisplayName(requirement.dependent)
+							: tt("projects:manage:dialog:conflict requirement by project"),
+						range: requirement.range,
+					})}
+				</li>
+			))}
+			{explanation.rejected_versions.map(rejected => (
+				<li key={`rejected-${toVersionString(rejected.version)}`}>
+					{tc("projects:manage:dialog:conflict rejected version", {
+						version: toVersionString(rejected.version),
+					})} ({rejected.reasons.map(rejectReasonName).map((x, i) => <Fragment key={i}>{i > 0 && ", "}{x}</Fragment>)})
+				</li>
+			))}
+		</ul>
+	);
+}
+
 function comparePackageChange([aName, aChange]: [string, TauriPackageChange], [bName, bChange]: [string, TauriPackageChange]): number {
 	const aType = packageChangesType(aChange);
 	const bType = packageChangesType(bChange);
@@ -5,7 +5,9 @@ import Link from "next/link";
 import {useQuery} from "@tanstack/react-query";
 import {
 	deepLinkInstallVcc,
+	environmentClearPackageCache,
 	environmentGetSettings,
+	environmentPackageCacheSize,
 	environmentPickProjectBackupPath,
 	environmentPickProjectDefaultPath,
 	environmentPickUnity,
@@ -298,6 +300,7 @@ function Settings(
 					{tc("settings:show prerelease")}
 				</label>
 			</Card>
+			<PackageCacheCard/>
 			<Card className={"flex-shrink-0 p-4"}>
 				<label className={"flex items-center"}>
 					<h2>{tc("settings:language")}: </h2>
@@ -347,6 +350,53 @@ function Settings(
 	)
 }
 
+function PackageCacheCard() {
+	const result = useQuery({
+		queryKey: ["environmentPackageCacheSize"],
+		queryFn: environmentPackageCacheSize,
+	})
+
+	const clearCache = async (keepUsedByProjects: boolean) => {
+		try {
+			const freed = await environmentClearPackageCache(keepUsedByProjects);
+			toastSuccess(tt("settings:toast:package cache cleared", {size: formatSize(freed)}));
+			result.refetch();
+		} catch (e) {
+			console.error(e);
+			toastThrownError(e)
+		}
+	}
+
+	return (
+		<Card className={"flex-shrink-0 p-4"}>
+			<h2>{tc("settings:package cache")}</h2>
+			<Typography className={"whitespace-normal"}>
+				{tc("settings:package cache description")}
+			</Typography>
+			<Typography>
+				{result.status == "success"
+					? tc("settings:package cache size", {size: formatSize(result.data)})
+					: tc("general:loading...")}
+			</Typography>
+			<div className={"flex gap-1"}>
+				<Button onClick={() => clearCache(true)}>{tc("settings:button:clear unused package cache")}</Button>
+				<Button onClick={() => clearCache(false)}>{tc("settings:button:clear package cache")}</Button>
+			</div>
+		</Card>
+	)
+}
+
+function formatSize(bytes: number): string {
+	const units = ["B", "KiB", "MiB", "GiB"];
+	let value = bytes;
+	let unit = 0;
+	while (value >= 1024 && unit < units.length - 1) {
+		value /= 1024;
+		unit++;
+	}
+	return unit == 0 ? `${bytes} ${units[0]}` : `${value.toFixed(1)} ${units[unit]}`;
+}
+
 function UnityTable(
 	{
 		unityPaths,
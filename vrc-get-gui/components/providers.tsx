@@ -7,12 +7,18 @@ import {deepLinkHasAddRepository, environmentLanguage, LogEntry} from "@/lib/bin
 import i18next from "@/lib/i18n";
 import {I18nextProvider} from "react-i18next";
 import {toastError, toastNormal} from "@/lib/toast";
+import {tc} from "@/lib/i18n";
 import {ThemeProvider} from "@material-tailwind/react";
 import {useTauriListen} from "@/lib/use-tauri-listen";
 import {usePathname, useRouter} from "next/navigation";
 
 const queryClient = new QueryClient();
 
+type RecoveredProject = {
+	project_path: string;
+	result: "RolledBack" | "RolledForward";
+}
+
 export function Providers({children}: { children: React.ReactNode }) {
 	const router = useRouter();
 	const pathname = usePathname();
@@ -24,6 +30,18 @@ export function Providers({children}: { children: React.ReactNode }) {
 		}
 	}, []))
 
+	useTauriListen<RecoveredProject>("project-recovered", useCallback((event) => {
+		const {project_path: path, result} = event.payload;
+		switch (result) {
+			case "RolledBack":
+				toastNormal(tc("projects:toast:interrupted changes rolled back", {path}));
+				break;
+			case "RolledForward":
+				toastNormal(tc("projects:toast:interrupted changes rolled forward", {path}));
+				break;
+		}
+	}, []))
+
 	const moveToRepositories = useCallback(() => {
 		if (location.pathname != "/repositories") {
 			router.push("/repositories");
@@ -106,6 +106,14 @@ export function environmentSetBackupFormat(backupFormat: string) {
     return invoke()<null>("environment_set_backup_format", { backupFormat })
 }
 
+export function environmentPackageCacheSize() {
+    return invoke()<number>("environment_package_cache_size")
+}
+
+export function environmentClearPackageCache(keepUsedByProjects: boolean) {
+    return invoke()<number>("environment_clear_package_cache", { keepUsedByProjects })
+}
+
 export function environmentDownloadRepository(url: string, headers: { [key: string]: string }) {
     return invoke()<TauriDownloadRepository>("environment_download_repository", { url,headers })
 }
@@ -154,8 +162,8 @@ export function projectRemovePackages(projectPath: string, names: string[]) {
     return invoke()<TauriPendingProjectChanges>("project_remove_packages", { projectPath,names })
 }
 
-export function projectApplyPendingChanges(projectPath: string, changesVersion: number) {
-    return invoke()<null>("project_apply_pending_changes", { projectPath,changesVersion })
+export function projectApplyPendingChanges(channel: string, projectPath: string, changesVersion: number, overwriteLocalModifications: boolean) {
+    return invoke()<AsyncCallResult<TauriInstallProgress, null>>("project_apply_pending_changes", { channel,projectPath,changesVersion,overwriteLocalModifications })
 }
 
 export function projectMigrateProjectTo2022(projectPath: string) {
@@ -214,9 +222,14 @@ export type TauriPickUnityResult = "NoFolderSelected" | "InvalidSelection" | "Al
 export type TauriProjectCreationInformation = { templates: TauriProjectTemplate[]; default_path: string }
 export type TauriRepositoriesInfo = { user_repositories: TauriUserRepository[]; hidden_user_repositories: string[]; hide_local_user_packages: boolean; show_prerelease_packages: boolean }
 export type TauriAddProjectWithPickerResult = "NoFolderSelected" | "InvalidSelection" | "AlreadyAdded" | "Successful"
-export type TauriRemoveReason = "Requested" | "Legacy" | "Unused"
+export type TauriRemoveReason = "Requested" | "Legacy" | "Unused" | "Dependent"
 export type LogEntry = { time: string; level: LogLevel; target: string; message: string }
-export type TauriConflictInfo = { packages: string[]; unity_conflict: boolean }
+export type TauriConflictInfo = { packages: string[]; unity_conflict: boolean; hold_conflict: boolean; explanation: TauriResolutionExplanation | null }
+export type TauriResolutionExplanation = { version: TauriVersion | null; requirement_chain: string[]; requirements: TauriDependencyRequirement[]; rejected_versions: TauriRejectedVersion[] }
+export type TauriDependencyRequirement = { dependent: string | null; range: string; satisfied: boolean }
+export type TauriRejectedVersion = { version: TauriVersion; reasons: TauriRejectReason[] }
+export type TauriRejectReason = "OutOfRange" | "Yanked" | "IncompatibleUnity" | "Prerelease"
+export type TauriInstallProgress = { type: "Downloading"; package: string; downloaded: number; total: number | null } | { type: "Extracting"; package: string; extracted: number; total: number } | { type: "Installed"; package: string }
 export type TauriUnityVersions = { unity_paths: ([string, string, boolean])[]; recommended_version: string; install_recommended_version_link: string }
 export type LogLevel = "Error" | "Warn" | "Info" | "Debug" | "Trace"
 export type TauriCreateProjectResult = "AlreadyExists" | "TemplateNotFound" | "Successful"
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fmt::Display;
 use std::io;
@@ -7,7 +8,9 @@ use std::pin::pin;
 use std::process::Stdio;
 use std::ptr::NonNull;
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
 
+use arc_swap::ArcSwapOption;
 use futures::prelude::*;
 use log::{error, info, warn};
 use reqwest::Url;
@@ -23,17 +26,24 @@ use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufR
 use tokio::process::Command;
 
 use async_command::{async_command, AsyncCallResult, AsyncCommandContext, With};
-use vrc_get_vpm::environment::UserProject;
+use vrc_get_vpm::environment::{
+    CacheCleanOptions, CachedPackage, DownloadPackageErr, HttpClientSettings, UpdateRepositoryMode,
+    UserProject,
+};
 use vrc_get_vpm::io::{DefaultEnvironmentIo, DefaultProjectIo, DirEntry, EnvironmentIo, IoTrait};
-use vrc_get_vpm::repository::RemoteRepository;
+use vrc_get_vpm::repository::{resolve_headers, RemoteRepository};
 use vrc_get_vpm::unity_project::pending_project_changes::{
-    ConflictInfo, PackageChange, RemoveReason,
+    ConflictInfo, PackageChange, RejectReason, RemoveReason, ResolutionExplanation,
+};
+use vrc_get_vpm::unity_project::{
+    AddPackageErr, AddPackageOperation, LocallyModifiedPackage, PendingProjectChanges,
+    RecoverChangesResult,
 };
-use vrc_get_vpm::unity_project::{AddPackageOperation, PendingProjectChanges};
 use vrc_get_vpm::version::Version;
 use vrc_get_vpm::{
-    unity_hub, EnvironmentIoHolder, PackageCollection, PackageInfo, PackageManifest, ProjectType,
-    VersionSelector, VRCHAT_RECOMMENDED_2022_UNITY, VRCHAT_RECOMMENDED_2022_UNITY_HUB_LINK,
+    unity_hub, EnvironmentIoHolder, InstallProgress, PackageCollection, PackageInfo,
+    PackageManifest, ProjectType, VersionSelector, VRCHAT_RECOMMENDED_2022_UNITY,
+    VRCHAT_RECOMMENDED_2022_UNITY_HUB_LINK,
 };
 
 use crate::config::GuiConfigHolder;
@@ -68,6 +78,8 @@ pub(crate) fn handlers() -> impl Fn(Invoke) + Send + Sync + 'static {
         environment_pick_project_backup_path,
         environment_set_show_prerelease_packages,
         environment_set_backup_format,
+        environment_package_cache_size,
+        environment_clear_package_cache,
         environment_download_repository,
         environment_add_repository,
         environment_remove_repository,
@@ -123,6 +135,8 @@ pub(crate) fn export_ts() {
             environment_pick_project_backup_path,
             environment_set_show_prerelease_packages,
             environment_set_backup_format,
+            environment_package_cache_size,
+            environment_clear_package_cache,
             environment_download_repository,
             environment_add_repository,
             environment_remove_repository,
@@ -181,7 +195,11 @@ macro_rules! with_config {
     }};
 }
 
+static APP_HANDLE: ArcSwapOption<AppHandle> = ArcSwapOption::const_empty();
+
 pub(crate) fn startup(app: &mut App) {
+    APP_HANDLE.store(Some(Arc::new(app.handle())));
+
     let handle = app.handle();
     tauri::async_runtime::spawn(async move {
         let state = handle.state();
@@ -323,10 +341,27 @@ type Environment = vrc_get_vpm::Environment<reqwest::Client, DefaultEnvironmentI
 type UnityProject = vrc_get_vpm::UnityProject<DefaultProjectIo>;
 
 async fn new_environment(io: &DefaultEnvironmentIo) -> io::Result<Environment> {
-    let client = reqwest::Client::builder()
-        .user_agent(concat!("vrc-get-litedb/", env!("CARGO_PKG_VERSION")))
-        .build()
-        .expect("building client");
+    let builder = || {
+        reqwest::Client::builder().user_agent(concat!("vrc-get-litedb/", env!("CARGO_PKG_VERSION")))
+    };
+
+    // the error is shown with the log and the environment is loaded without the settings
+    // so that the settings can be fixed
+    let client = match HttpClientSettings::load(io).await {
+        Ok(settings) => match settings.build_client(builder()) {
+            Ok(client) => Some(client),
+            Err(e) => {
+                error!("error configuring http client, using the default settings: {e}");
+                None
+            }
+        },
+        Err(e) => {
+            error!("error loading http client settings, using the default settings: {e}");
+            None
+        }
+    };
+    let client = client.unwrap_or_else(|| builder().build().expect("building client"));
+
     Environment::load(Some(client), io.clone()).await
 }
 
@@ -346,7 +381,37 @@ async fn update_project_last_modified(env: &mut Environment, project_dir: &Path)
 #[specta(export)]
 #[serde(tag = "type")]
 enum RustError {
-    Unrecoverable { message: String },
+    Unrecoverable {
+        message: String,
+    },
+    /// The changes would overwrite the locally modified packages.
+    /// The changes are kept so they can be applied with overwriting after confirmation.
+    LocalModifications {
+        packages: Vec<TauriLocallyModifiedPackage>,
+    },
+    /// The dependency cannot be resolved. The explanation is shown to the user.
+    DependencyNotFound {
+        message: String,
+        dependency_name: String,
+        explanation: TauriResolutionExplanation,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+struct TauriLocallyModifiedPackage {
+    name: String,
+    modified_files: Vec<String>,
+}
+
+impl From<&LocallyModifiedPackage> for TauriLocallyModifiedPackage {
+    fn from(value: &LocallyModifiedPackage) -> Self {
+        Self {
+            name: value.name().to_string(),
+            modified_files: (value.modified_files().iter())
+                .map(|x| x.to_string_lossy().into_owned())
+                .collect(),
+        }
+    }
 }
 
 impl RustError {
@@ -358,6 +423,20 @@ impl RustError {
     }
 }
 
+fn resolve_error(error: AddPackageErr) -> RustError {
+    match &error {
+        AddPackageErr::DependencyNotFound {
+            dependency_name,
+            explanation,
+        } => RustError::DependencyNotFound {
+            message: error.to_string(),
+            dependency_name: dependency_name.to_string(),
+            explanation: explanation.as_ref().into(),
+        },
+        _ => RustError::unrecoverable(error),
+    }
+}
+
 impl<E: Display> From<E> for RustError {
     fn from(value: E) -> Self {
         RustError::unrecoverable(format!("io error: {value}"))
@@ -428,9 +507,9 @@ impl EnvironmentHolder {
                     self.last_repository_update = Some(tokio::time::Instant::now());
                     self.environment_version += Wrapping(1);
                     info!("loading package infos");
-                    environment.load_package_infos(true).await?;
+                    environment.load_package_infos(update_repository).await?;
                 }
-                UpdateRepositoryMode::IfOutdatedOrNecessary => {
+                UpdateRepositoryMode::IfOutdated => {
                     if self
                         .last_repository_update
                         .map(|x| x.elapsed() > OUTDATED)
@@ -439,7 +518,7 @@ impl EnvironmentHolder {
                         self.last_repository_update = Some(tokio::time::Instant::now());
                         self.environment_version += Wrapping(1);
                         info!("loading package infos");
-                        environment.load_package_infos(true).await?;
+                        environment.load_package_infos(update_repository).await?;
                     }
                 }
             }
@@ -452,11 +531,11 @@ impl EnvironmentHolder {
 
             match update_repository {
                 UpdateRepositoryMode::None => {}
-                UpdateRepositoryMode::Force | UpdateRepositoryMode::IfOutdatedOrNecessary => {
+                UpdateRepositoryMode::Force | UpdateRepositoryMode::IfOutdated => {
                     self.last_repository_update = Some(tokio::time::Instant::now());
                     self.environment_version += Wrapping(1);
                     info!("loading package infos");
-                    environment.load_package_infos(true).await?;
+                    environment.load_package_infos(update_repository).await?;
                 }
             }
 
@@ -465,12 +544,6 @@ impl EnvironmentHolder {
     }
 }
 
-enum UpdateRepositoryMode {
-    None,
-    Force,
-    IfOutdatedOrNecessary,
-}
-
 struct ChangesInfoHolder {
     changes_info: Option<NonNull<PendingProjectChangesInfo<'static>>>,
 }
@@ -504,6 +577,10 @@ impl ChangesInfoHolder {
         result
     }
 
+    fn get(&self) -> Option<&PendingProjectChangesInfo> {
+        Some(unsafe { self.changes_info?.as_ref() })
+    }
+
     fn take(&mut self) -> Option<PendingProjectChangesInfo> {
         Some(*unsafe { Box::from_raw(self.changes_info.take()?.as_mut()) })
     }
@@ -902,7 +979,7 @@ async fn environment_set_project_sorting(
     })
 }
 
-#[derive(Serialize, specta::Type)]
+#[derive(Debug, Clone, Serialize, specta::Type)]
 struct TauriVersion {
     major: u64,
     minor: u64,
@@ -1022,7 +1099,7 @@ async fn environment_packages(
     let env_state = &mut *env_state;
     let environment = env_state
         .environment
-        .get_environment_mut(UpdateRepositoryMode::IfOutdatedOrNecessary, &env_state.io)
+        .get_environment_mut(UpdateRepositoryMode::IfOutdated, &env_state.io)
         .await?;
 
     let packages = environment
@@ -1457,6 +1534,40 @@ async fn environment_set_backup_format(
     })
 }
 
+#[tauri::command]
+#[specta::specta]
+async fn environment_package_cache_size(
+    state: State<'_, Mutex<EnvironmentState>>,
+) -> Result<u64, RustError> {
+    with_environment!(&state, |environment| {
+        let cached = environment.get_cached_packages().await?;
+        Ok(cached.iter().map(CachedPackage::size).sum())
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn environment_clear_package_cache(
+    state: State<'_, Mutex<EnvironmentState>>,
+    keep_used_by_projects: bool,
+) -> Result<u64, RustError> {
+    with_environment!(&state, |environment| {
+        let locked = if keep_used_by_projects {
+            environment.get_locked_packages_of_projects().await?
+        } else {
+            vec![]
+        };
+        let options = CacheCleanOptions::new().keep_versions(
+            locked
+                .iter()
+                .map(|(name, version)| (name.as_ref(), version)),
+        );
+        let removed = environment.clean_package_cache(&options).await?;
+        info!("removed {} versions from the package cache", removed.len());
+        Ok(removed.iter().map(CachedPackage::size).sum())
+    })
+}
+
 #[derive(Serialize, specta::Type)]
 struct TauriRemoteRepositoryInfo {
     display_name: String,
@@ -1497,9 +1608,19 @@ async fn environment_download_repository(
             }
         }
 
-        let client = environment.http().unwrap();
-        let repo = match RemoteRepository::download(client, &url, &headers.0).await {
-            Ok((repo, _)) => repo,
+        let repo = if url.scheme() == "file" {
+            RemoteRepository::load_file(environment.io(), &url).await
+        } else {
+            let client = environment.http().unwrap();
+            match resolve_headers(&headers.0) {
+                Ok(headers) => RemoteRepository::download(client, &url, &headers)
+                    .await
+                    .map(|(repo, _)| repo),
+                Err(e) => Err(e.into()),
+            }
+        };
+        let repo = match repo {
+            Ok(repo) => repo,
             Err(e) => {
                 return Ok(TauriDownloadRepository::DownloadError {
                     message: e.to_string(),
@@ -1553,7 +1674,9 @@ async fn environment_add_repository(
     };
 
     with_environment!(&state, |environment| {
-        environment.add_remote_repo(url, None, headers.0).await?;
+        environment
+            .add_remote_repo(url, None, headers.0, Vec::new())
+            .await?;
         environment.save().await?;
     });
 
@@ -1861,7 +1984,7 @@ async fn environment_create_project(
         let env_state = &mut *env_state;
         let environment = env_state
             .environment
-            .get_environment_mut(UpdateRepositoryMode::IfOutdatedOrNecessary, &env_state.io)
+            .get_environment_mut(UpdateRepositoryMode::IfOutdated, &env_state.io)
             .await?;
 
         let mut unity_project = load_project(path_str.into()).await?;
@@ -1889,11 +2012,47 @@ struct TauriProjectDetails {
     should_resolve: bool,
 }
 
+#[derive(Serialize, Clone)]
+struct TauriRecoveredProject {
+    project_path: String,
+    result: TauriRecoverChangesResult,
+}
+
+#[derive(Serialize, Clone)]
+enum TauriRecoverChangesResult {
+    RolledBack,
+    RolledForward,
+}
+
 async fn load_project(project_path: String) -> Result<UnityProject, RustError> {
-    Ok(UnityProject::load(vrc_get_vpm::io::DefaultProjectIo::new(
-        PathBuf::from(project_path).into(),
+    let mut project = UnityProject::load(vrc_get_vpm::io::DefaultProjectIo::new(
+        PathBuf::from(&project_path).into(),
     ))
-    .await?)
+    .await?;
+
+    if project.has_interrupted_changes() {
+        // the previous changes were interrupted (e.g. the app was killed), so recover the project
+        let result = project.recover_interrupted_changes().await?;
+        warn!("recovered interrupted changes to the project: {result:?}");
+
+        let result = match result {
+            RecoverChangesResult::RolledBack => Some(TauriRecoverChangesResult::RolledBack),
+            RecoverChangesResult::RolledForward => Some(TauriRecoverChangesResult::RolledForward),
+            _ => None,
+        };
+        if let (Some(result), Some(app_handle)) = (result, APP_HANDLE.load().as_ref()) {
+            // tell the user that the project was changed on load
+            let recovered = TauriRecoveredProject {
+                project_path,
+                result,
+            };
+            if let Err(e) = app_handle.emit_all("project-recovered", recovered) {
+                error!("failed to notify the recovered project: {e}");
+            }
+        }
+    }
+
+    Ok(project)
 }
 
 #[tauri::command]
@@ -1979,6 +2138,7 @@ enum TauriRemoveReason {
     Requested,
     Legacy,
     Unused,
+    Dependent,
 }
 
 impl From<RemoveReason> for TauriRemoveReason {
@@ -1987,6 +2147,7 @@ impl From<RemoveReason> for TauriRemoveReason {
             RemoveReason::Requested => Self::Requested,
             RemoveReason::Legacy => Self::Legacy,
             RemoveReason::Unused => Self::Unused,
+            RemoveReason::Dependent => Self::Dependent,
         }
     }
 }
@@ -1995,6 +2156,8 @@ impl From<RemoveReason> for TauriRemoveReason {
 struct TauriConflictInfo {
     packages: Vec<String>,
     unity_conflict: bool,
+    hold_conflict: bool,
+    explanation: Option<TauriResolutionExplanation>,
 }
 
 impl From<&ConflictInfo> for TauriConflictInfo {
@@ -2006,10 +2169,89 @@ impl From<&ConflictInfo> for TauriConflictInfo {
                 .map(|x| x.to_string())
                 .collect(),
             unity_conflict: value.conflicts_with_unity(),
+            hold_conflict: value.conflicts_with_hold(),
+            explanation: value.explanation().map(Into::into),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+struct TauriResolutionExplanation {
+    version: Option<TauriVersion>,
+    requirement_chain: Vec<String>,
+    requirements: Vec<TauriDependencyRequirement>,
+    rejected_versions: Vec<TauriRejectedVersion>,
+}
+
+impl From<&ResolutionExplanation> for TauriResolutionExplanation {
+    fn from(value: &ResolutionExplanation) -> Self {
+        Self {
+            version: value.version().map(Into::into),
+            requirement_chain: value
+                .requirement_chain()
+                .iter()
+                .map(|x| x.to_string())
+                .collect(),
+            requirements: value
+                .requirements()
+                .iter()
+                .map(|x| TauriDependencyRequirement {
+                    dependent: x.dependent().map(|x| x.to_string()),
+                    range: x.range().to_string(),
+                    satisfied: x.is_satisfied(),
+                })
+                .collect(),
+            rejected_versions: value
+                .rejected_versions()
+                .iter()
+                .map(|x| TauriRejectedVersion {
+                    version: x.version().into(),
+                    reasons: x
+                        .reasons()
+                        .iter()
+                        .filter_map(|&x| x.try_into().ok())
+                        .collect(),
+                })
+                .collect(),
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, specta::Type)]
+struct TauriDependencyRequirement {
+    dependent: Option<String>,
+    range: String,
+    satisfied: bool,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+struct TauriRejectedVersion {
+    version: TauriVersion,
+    reasons: Vec<TauriRejectReason>,
+}
+
+#[derive(Debug, Clone, Serialize, specta::Type)]
+enum TauriRejectReason {
+    OutOfRange,
+    Yanked,
+    IncompatibleUnity,
+    Prerelease,
+}
+
+impl TryFrom<RejectReason> for TauriRejectReason {
+    type Error = ();
+
+    fn try_from(value: RejectReason) -> Result<Self, ()> {
+        Ok(match value {
+            RejectReason::OutOfRange => Self::OutOfRange,
+            RejectReason::Yanked => Self::Yanked,
+            RejectReason::IncompatibleUnity => Self::IncompatibleUnity,
+            RejectReason::Prerelease => Self::Prerelease,
+            _ => return Err(()),
+        })
+    }
+}
+
 macro_rules! changes {
     ($state: ident, $($env_version: ident, )? |$environment: pat_param, $packages: pat_param| $body: expr) => {{
         let mut state = $state.lock().await;
@@ -2064,7 +2306,7 @@ async fn project_install_package(
             .await
         {
             Ok(request) => request,
-            Err(e) => return Err(RustError::unrecoverable(e)),
+            Err(e) => return Err(resolve_error(e)),
         }
     })
 }
@@ -2099,7 +2341,7 @@ async fn project_install_multiple_package(
             .await
         {
             Ok(request) => request,
-            Err(e) => return Err(RustError::unrecoverable(e)),
+            Err(e) => return Err(resolve_error(e)),
         }
     })
 }
@@ -2113,13 +2355,15 @@ async fn project_upgrade_multiple_package(
     package_indices: Vec<usize>,
 ) -> Result<TauriPendingProjectChanges, RustError> {
     changes!(state, env_version, |environment, packages| {
+        let unity_project = load_project(project_path).await?;
+
+        // held packages are kept at the locked version
         let installing_packages = package_indices
             .iter()
             .map(|index| packages[*index])
+            .filter(|package| !unity_project.is_held(package.name()))
             .collect::<Vec<_>>();
 
-        let unity_project = load_project(project_path).await?;
-
         let operation = AddPackageOperation::UpgradeLocked;
 
         let allow_prerelease = environment.show_prerelease_packages();
@@ -2134,7 +2378,7 @@ async fn project_upgrade_multiple_package(
             .await
         {
             Ok(request) => request,
-            Err(e) => return Err(RustError::unrecoverable(e)),
+            Err(e) => return Err(resolve_error(e)),
         }
     })
 }
@@ -2150,7 +2394,7 @@ async fn project_resolve(
 
         match unity_project.resolve_request(environment).await {
             Ok(request) => request,
-            Err(e) => return Err(RustError::unrecoverable(e)),
+            Err(e) => return Err(resolve_error(e)),
         }
     })
 }
@@ -2174,37 +2418,189 @@ async fn project_remove_packages(
     })
 }
 
+#[derive(Serialize, specta::Type, Clone)]
+#[serde(tag = "type")]
+enum TauriInstallProgress {
+    Downloading {
+        package: String,
+        downloaded: u64,
+        total: Option<u64>,
+    },
+    Extracting {
+        package: String,
+        extracted: usize,
+        total: usize,
+    },
+    Installed {
+        package: String,
+    },
+}
+
+/// Sends the progress of installing packages to the frontend
+struct EmitInstallProgress {
+    context: AsyncCommandContext<TauriInstallProgress>,
+    state: std::sync::Mutex<EmitInstallProgressState>,
+}
+
+#[derive(Default)]
+struct EmitInstallProgressState {
+    last_emitted: Option<std::time::Instant>,
+    download_totals: HashMap<Box<str>, Option<u64>>,
+    extract_totals: HashMap<Box<str>, usize>,
+}
+
+impl EmitInstallProgress {
+    fn new(context: AsyncCommandContext<TauriInstallProgress>) -> Self {
+        Self {
+            context,
+            state: std::sync::Mutex::new(EmitInstallProgressState::default()),
+        }
+    }
+
+    fn emit(
+        &self,
+        force: bool,
+        progress: impl FnOnce(&mut EmitInstallProgressState) -> TauriInstallProgress,
+    ) {
+        let progress = {
+            let mut state = self.state.lock().unwrap();
+            // progress events are frequent so we throttle them
+            let now = std::time::Instant::now();
+            if !force {
+                if let Some(last) = state.last_emitted {
+                    if now - last < std::time::Duration::from_millis(100) {
+                        return;
+                    }
+                }
+            }
+            state.last_emitted = Some(now);
+            progress(&mut state)
+        };
+
+        if let Err(e) = self.context.emit(progress) {
+            error!("error sending install progress: {e}");
+        }
+    }
+}
+
+impl InstallProgress for EmitInstallProgress {
+    fn download_started(&self, package: &PackageManifest, total: Option<u64>) {
+        self.emit(true, |state| {
+            state.download_totals.insert(package.name().into(), total);
+            TauriInstallProgress::Downloading {
+                package: package.name().to_string(),
+                downloaded: 0,
+                total,
+            }
+        });
+    }
+
+    fn download_progress(&self, package: &PackageManifest, downloaded: u64) {
+        self.emit(false, |state| TauriInstallProgress::Downloading {
+            package: package.name().to_string(),
+            downloaded,
+            total: state.download_totals.get(package.name()).copied().flatten(),
+        });
+    }
+
+    fn extract_started(&self, package: &PackageManifest, total: usize) {
+        self.emit(true, |state| {
+            state.extract_totals.insert(package.name().into(), total);
+            TauriInstallProgress::Extracting {
+                package: package.name().to_string(),
+                extracted: 0,
+                total,
+            }
+        });
+    }
+
+    fn extract_progress(&self, package: &PackageManifest, extracted: usize) {
+        self.emit(false, |state| TauriInstallProgress::Extracting {
+            package: package.name().to_string(),
+            extracted,
+            total: (state.extract_totals.get(package.name()).copied()).unwrap_or(0),
+        });
+    }
+
+    fn package_installed(&self, package: &PackageManifest) {
+        self.emit(true, |state| {
+            state.download_totals.remove(package.name());
+            state.extract_totals.remove(package.name());
+            TauriInstallProgress::Installed {
+                package: package.name().to_string(),
+            }
+        });
+    }
+}
+
 #[tauri::command]
 #[specta::specta]
 async fn project_apply_pending_changes(
-    state: State<'_, Mutex<EnvironmentState>>,
+    window: Window,
+    channel: String,
     project_path: String,
     changes_version: u32,
-) -> Result<(), RustError> {
-    let mut env_state = state.lock().await;
-    let env_state = &mut *env_state;
-    let changes = env_state.changes_info.take().unwrap();
-    if changes.changes_version != changes_version {
-        return Err(RustError::unrecoverable("changes version mismatch"));
-    }
-    if changes.environment_version != env_state.environment.environment_version.0 {
-        return Err(RustError::unrecoverable("environment version mismatch"));
-    }
+    overwrite_local_modifications: bool,
+) -> Result<AsyncCallResult<TauriInstallProgress, ()>, RustError> {
+    let app_handle = window.app_handle();
+    async_command(channel, window, async {
+        With::<TauriInstallProgress>::continue_async(move |context| async move {
+            // the state is borrowed from the app handle since the task outlives the command
+            let state = app_handle.state::<Mutex<EnvironmentState>>();
+            let mut env_state = state.lock().await;
+            let env_state = &mut *env_state;
+            let changes = env_state.changes_info.get().unwrap();
+            if changes.changes_version != changes_version {
+                return Err(RustError::unrecoverable("changes version mismatch"));
+            }
+            if changes.environment_version != env_state.environment.environment_version.0 {
+                return Err(RustError::unrecoverable("environment version mismatch"));
+            }
 
-    let environment = env_state
-        .environment
-        .get_environment_mut(UpdateRepositoryMode::None, &env_state.io)
-        .await?;
+            let environment = env_state
+                .environment
+                .get_environment_mut(UpdateRepositoryMode::None, &env_state.io)
+                .await?;
 
-    let mut unity_project = load_project(project_path).await?;
+            let mut unity_project = load_project(project_path).await?;
 
-    unity_project
-        .apply_pending_changes(environment, changes.changes)
-        .await?;
+            if !overwrite_local_modifications {
+                let modified = unity_project
+                    .find_local_modifications(environment, &changes.changes)
+                    .await?;
+                if !modified.is_empty() {
+                    return Err(RustError::LocalModifications {
+                        packages: modified.iter().map(Into::into).collect(),
+                    });
+                }
+            }
 
-    unity_project.save().await?;
-    update_project_last_modified(environment, unity_project.project_dir()).await;
-    Ok(())
+            let mut changes = env_state.changes_info.take().unwrap();
+            // the local modifications are checked above
+            changes.changes.set_overwrite_local_modifications(true);
+
+            let progress = EmitInstallProgress::new(context);
+
+            unity_project
+                .apply_pending_changes_with_progress(environment, changes.changes, &progress)
+                .await
+                .map_err(|e| {
+                    // report which repository served the broken package
+                    match e
+                        .get_ref()
+                        .and_then(|e| e.downcast_ref::<DownloadPackageErr>())
+                    {
+                        Some(download_err) => RustError::unrecoverable(download_err),
+                        None => RustError::from(e),
+                    }
+                })?;
+
+            unity_project.save().await?;
+            update_project_last_modified(environment, unity_project.project_dir()).await;
+            Ok(())
+        })
+    })
+    .await
 }
 
 #[tauri::command]
@@ -2318,7 +2714,7 @@ async fn project_migrate_project_to_vpm(
     let env_state = &mut *env_state;
     let environment = env_state
         .environment
-        .get_environment_mut(UpdateRepositoryMode::IfOutdatedOrNecessary, &env_state.io)
+        .get_environment_mut(UpdateRepositoryMode::IfOutdated, &env_state.io)
         .await?;
 
     let mut unity_project = load_project(project_path).await?;
@@ -5,6 +5,7 @@ use arc_swap::ArcSwapOption;
 use indexmap::IndexMap;
 use tauri::{AppHandle, Manager};
 use url::{Host, Url};
+use vrc_get_vpm::repository::uses_credential_reference;
 
 static APP_HANDLE: ArcSwapOption<AppHandle> = ArcSwapOption::const_empty();
 
@@ -44,6 +45,15 @@ fn parse_deep_link(deep_link: Url) -> Option<DeepLink> {
                     }
                     "headers[]" => {
                         let (key, value) = value.split_once(':')?;
+                        if uses_credential_reference(value) {
+                            // deep links come from anywhere so they must not read
+                            // environment variables or run commands
+                            log::error!(
+                                "Credential reference is not allowed in deep link: {}",
+                                key
+                            );
+                            return None;
+                        }
                         headers.insert(key.to_string(), value.to_string());
                     }
                     _ => {
@@ -236,4 +246,26 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn parse_add_repo_with_credential_helper() {
+        let deep_link = parse_deep_link(
+            Url::parse(
+                "vcc://vpm/addRepo?url=https://example.com&headers[]=Authorization:${command:id}",
+            )
+            .unwrap(),
+        );
+        assert_eq!(deep_link, None);
+    }
+
+    #[test]
+    fn parse_add_repo_with_environment_variable() {
+        let deep_link = parse_deep_link(
+            Url::parse(
+                "vcc://vpm/addRepo?url=https://example.com&headers[]=Authorization:${env:GITHUB_TOKEN}",
+            )
+            .unwrap(),
+        );
+        assert_eq!(deep_link, None);
+    }
 }
@@ -1,3 +1,7 @@
+mod http_client_settings;
+mod mirror;
+mod package_cache;
+mod package_store;
 mod repo_holder;
 mod repo_source;
 mod settings;
@@ -14,18 +18,25 @@ mod unity_management;
 use crate::io;
 use crate::io::SeekFrom;
 use crate::repository::local::LocalCachedRepository;
-use crate::repository::RemoteRepository;
+use crate::repository::remote::file_url_to_path;
+use crate::repository::signature::keys_fingerprint;
+use crate::repository::{resolve_headers, RemoteRepository};
 use crate::structs::setting::UserRepoSetting;
-use crate::traits::{EnvironmentIoHolder, HttpClient, PackageCollection, RemotePackageDownloader};
+use crate::traits::{
+    EnvironmentIoHolder, HttpClient, InstallProgress, PackageCollection, RemotePackageDownloader,
+};
 use crate::utils::{to_vec_pretty_os_eol, Sha256AsyncWrite};
+use crate::version::Version;
 use crate::{PackageInfo, PackageManifest, VersionSelector};
-use futures::future::{join_all, try_join};
+use futures::future::{join_all, try_join, Either};
 use futures::prelude::*;
 use hex::FromHex;
 use indexmap::IndexMap;
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use log::{error, warn};
+use sha2::digest::Output;
+use sha2::{Digest, Sha256};
 use std::cmp::Reverse;
 use std::collections::HashSet;
 use std::ffi::{OsStr, OsString};
@@ -33,10 +44,14 @@ use std::fmt;
 use std::fs::remove_file;
 use std::path::{Path, PathBuf};
 use std::pin::pin;
+use std::time::{Duration, SystemTime};
 use url::Url;
 
 use crate::environment::vrc_get_settings::VrcGetSettings;
 use crate::io::{DirEntry, EnvironmentIo};
+pub use http_client_settings::{HttpClientSettings, HttpClientSettingsErr};
+pub use package_cache::{CacheCleanOptions, CachedPackage};
+pub(crate) use package_store::store_package;
 #[cfg(feature = "experimental-project-management")]
 pub use project_management::*;
 pub(crate) use repo_holder::RepoHolder;
@@ -49,6 +64,19 @@ const LOCAL_OFFICIAL_PATH: &str = "Repos/vrc-official.json";
 const CURATED_URL_STR: &str = "https://packages.vrchat.com/curated?download";
 const LOCAL_CURATED_PATH: &str = "Repos/vrc-curated.json";
 const REPO_CACHE_FOLDER: &str = "Repos";
+/// The default duration not to fetch the repositories again after fetched
+const DEFAULT_REPOSITORY_TTL: Duration = Duration::from_secs(60 * 5);
+
+/// When to fetch the remote repositories in [`Environment::load_package_infos`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum UpdateRepositoryMode {
+    /// Use the cached repositories without fetching
+    None,
+    /// Fetch the repositories not fetched within the TTL
+    IfOutdated,
+    /// Fetch all repositories
+    Force,
+}
 
 /// This struct holds global state (will be saved on %LOCALAPPDATA% of VPM.
 #[derive(Debug)]
@@ -98,6 +126,10 @@ impl<T: HttpClient, IO: EnvironmentIo> Environment<T, IO> {
 }
 
 impl<T: HttpClient, IO: EnvironmentIo> Environment<T, IO> {
+    fn repository_ttl(&self) -> Duration {
+        (self.vrc_get_settings.repository_ttl()).unwrap_or(DEFAULT_REPOSITORY_TTL)
+    }
+
     fn get_predefined_repos(&self) -> Vec<RepoSource<'static>> {
         lazy_static! {
             static ref EMPTY_HEADERS: IndexMap<Box<str>, Box<str>> = IndexMap::new();
@@ -108,21 +140,27 @@ impl<T: HttpClient, IO: EnvironmentIo> Environment<T, IO> {
         let mut repositories = Vec::with_capacity(2);
 
         if !self.vrc_get_settings.ignore_official_repository() {
-            repositories.push(RepoSource::new(
-                LOCAL_OFFICIAL_PATH.as_ref(),
-                &EMPTY_HEADERS,
-                Some(&OFFICIAL_URL),
-            ));
+            repositories.push(
+                RepoSource::new(
+                    LOCAL_OFFICIAL_PATH.as_ref(),
+                    &EMPTY_HEADERS,
+                    Some(&OFFICIAL_URL),
+                )
+                .with_ttl(self.repository_ttl()),
+            );
         } else {
             warn!("ignoring official repository is experimental feature!");
         }
 
         if !self.vrc_get_settings.ignore_curated_repository() {
-            repositories.push(RepoSource::new(
-                LOCAL_CURATED_PATH.as_ref(),
-                &EMPTY_HEADERS,
-                Some(&CURATED_URL),
-            ));
+            repositories.push(
+                RepoSource::new(
+                    LOCAL_CURATED_PATH.as_ref(),
+                    &EMPTY_HEADERS,
+                    Some(&CURATED_URL),
+                )
+                .with_ttl(self.repository_ttl()),
+            );
         } else {
             warn!("ignoring curated repository is experimental feature!");
         }
@@ -130,17 +168,20 @@ impl<T: HttpClient, IO: EnvironmentIo> Environment<T, IO> {
         repositories
     }
 
-    pub async fn load_package_infos(&mut self, update: bool) -> io::Result<()> {
-        let http = if update { self.http.as_ref() } else { None };
+    pub async fn load_package_infos(&mut self, update: UpdateRepositoryMode) -> io::Result<()> {
+        let http = match update {
+            UpdateRepositoryMode::None => None,
+            UpdateRepositoryMode::IfOutdated | UpdateRepositoryMode::Force => self.http.as_ref(),
+        };
         let predefined_repos = self.get_predefined_repos().into_iter();
-        let user_repos = self
-            .settings
-            .user_repos()
-            .iter()
-            .map(UserRepoSetting::to_source);
+        let repository_ttl = self.repository_ttl();
+        let user_repos = (self.settings.user_repos().iter()).map(|repo| {
+            repo.to_source()
+                .with_ttl(repo.ttl().unwrap_or(repository_ttl))
+        });
         self.io.create_dir_all("Repos".as_ref()).await?;
         self.repo_cache
-            .load_repos(http, &self.io, predefined_repos.chain(user_repos))
+            .load_repos(http, &self.io, update, predefined_repos.chain(user_repos))
             .await?;
         self.update_user_repo_id();
         self.load_user_package_infos().await?;
@@ -296,19 +337,32 @@ impl<T: HttpClient, IO: EnvironmentIo> Environment<T, IO> {
         self.settings.user_repos()
     }
 
+    /// Adds the repository at `url` to the user repositories.
+    ///
+    /// If `public_keys` is not empty, the repository must be signed with one of them
+    /// and will be verified on every update.
     pub async fn add_remote_repo(
         &mut self,
         url: Url,
         name: Option<&str>,
         headers: IndexMap<Box<str>, Box<str>>,
+        public_keys: Vec<Box<str>>,
     ) -> Result<(), AddRepositoryErr> {
         let user_repos = self.get_user_repos();
         if user_repos.iter().any(|x| x.url() == Some(&url)) {
             return Err(AddRepositoryErr::AlreadyAdded);
         }
-        let http = self.http.as_ref().ok_or(AddRepositoryErr::OfflineMode)?;
-
-        let (remote_repo, etag) = RemoteRepository::download(http, &url, &headers).await?;
+        let (remote_repo, etag) = if url.scheme() == "file" {
+            RemoteRepository::load_file_with_etag(&self.io, &url, None, &public_keys)
+                .await?
+                .expect("loading without etag always returns Some")
+        } else {
+            let http = self.http.as_ref().ok_or(AddRepositoryErr::OfflineMode)?;
+            let resolved_headers = resolve_headers(&headers).map_err(io::Error::from)?;
+            RemoteRepository::download_with_etag(http, &url, &resolved_headers, None, &public_keys)
+                .await?
+                .expect("downloading without etag always returns Some")
+        };
         let repo_name = name.or(remote_repo.name()).map(Into::into);
 
         let repo_id = remote_repo.id().map(Into::into);
@@ -329,19 +383,23 @@ impl<T: HttpClient, IO: EnvironmentIo> Environment<T, IO> {
                 .get_or_insert_with(Default::default)
                 .etag = etag;
         }
+        local_cache.set_fetched_at(SystemTime::now());
+        local_cache.set_verified_keys(keys_fingerprint(&public_keys));
 
         self.io.create_dir_all(REPO_CACHE_FOLDER.as_ref()).await?;
 
         let file_name = self.write_new_repo(&local_cache).await?;
 
-        self.settings.add_user_repo(UserRepoSetting::new(
+        let mut setting = UserRepoSetting::new(
             self.io
                 .resolve(format!("{}/{}", REPO_CACHE_FOLDER, file_name).as_ref())
                 .into_boxed_path(),
             repo_name,
             Some(url),
             repo_id,
-        ));
+        );
+        setting.public_keys = public_keys;
+        self.settings.add_user_repo(setting);
         Ok(())
     }
 
@@ -523,18 +581,17 @@ impl<T: HttpClient, IO: EnvironmentIo> Environment<T, IO> {
 impl<T: HttpClient, IO: EnvironmentIo> RemotePackageDownloader for Environment<T, IO> {
     type FileStream = IO::FileStream;
 
+    fn use_package_store(&self) -> bool {
+        Environment::use_package_store(self)
+    }
+
     async fn get_package(
         &self,
         repository: &LocalCachedRepository,
         package: &PackageManifest,
+        progress: &impl InstallProgress,
     ) -> io::Result<Self::FileStream> {
-        let zip_file_name = format!("vrc-get-{}-{}.zip", &package.name(), package.version());
-        let zip_path = PathBuf::from(format!(
-            "{}/{}/{}",
-            REPO_CACHE_FOLDER,
-            package.name(),
-            &zip_file_name
-        ));
+        let zip_path = package_cache_path(package.name(), package.version());
         let sha_path = zip_path.with_extension("zip.sha256");
 
         if let Some(cache_file) =
@@ -544,9 +601,10 @@ impl<T: HttpClient, IO: EnvironmentIo> RemotePackageDownloader for Environment<T
         } else {
             self.io.create_dir_all(zip_path.parent().unwrap()).await?;
 
+            // credential references are only resolved for headers configured by the user
+            let repository_headers = resolve_headers(repository.headers())?;
             let new_headers = IndexMap::from_iter(
-                (repository
-                    .headers()
+                (repository_headers
                     .iter()
                     .map(|(k, v)| (k.as_ref(), v.as_ref())))
                 .chain(
@@ -561,21 +619,22 @@ impl<T: HttpClient, IO: EnvironmentIo> RemotePackageDownloader for Environment<T
                 self.http.as_ref(),
                 &self.io,
                 &new_headers,
+                repository,
+                package,
                 &zip_path,
-                &sha_path,
-                &zip_file_name,
-                package.url().ok_or_else(|| {
-                    io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        "URL field of the package.json in the repository empty",
-                    )
-                })?,
+                progress,
             )
             .await?)
         }
     }
 }
 
+/// Returns the path to the cached zip file of the remote package in the environment folder.
+pub(crate) fn package_cache_path(name: &str, version: &Version) -> PathBuf {
+    let zip_file_name = format!("vrc-get-{}-{}.zip", name, version);
+    PathBuf::from(format!("{}/{}/{}", REPO_CACHE_FOLDER, name, &zip_file_name))
+}
+
 /// Try to load from the zip file
 ///
 /// # Arguments
@@ -626,49 +685,286 @@ async fn try_load_package_cache<IO: EnvironmentIo>(
 
 /// downloads the zip file from the url to the specified path
 ///
+/// The data is downloaded to `<zip_path>.part` first, which is kept if the download is interrupted
+/// and resumed next time if the server supports range requests.
+///
 /// # Arguments
 ///
 /// * `http`: http client. returns error if none
-/// * `zip_path`: the path to zip file
-/// * `sha_path`: the path to sha256 file
-/// * `zip_file_name`: the name of zip file. will be used in the sha file
-/// * `url`: url to zip file
+/// * `repository`: the repository serving the package. used for error reporting
+/// * `package`: the package to download. the downloaded file is verified with `zipSHA256` of this
+/// * `zip_path`: the path to zip file. the sha256 file will be written next to this
+/// * `progress`: the observer of the download progress
 ///
 /// returns: Result<File, Error> the readable zip file.
 async fn download_package_zip<IO: EnvironmentIo>(
     http: Option<&impl HttpClient>,
     io: &IO,
     headers: &IndexMap<&str, &str>,
+    repository: &LocalCachedRepository,
+    package: &PackageManifest,
     zip_path: &Path,
-    sha_path: &Path,
-    zip_file_name: &str,
-    url: &Url,
+    progress: &impl InstallProgress,
 ) -> io::Result<IO::FileStream> {
-    let Some(http) = http else {
-        return Err(io::Error::new(io::ErrorKind::NotFound, "Offline mode"));
-    };
-
-    // file not found: err
-    let cache_file = io.create(zip_path).await?;
-
-    let mut response = pin!(http.get(url, headers).await?);
+    let sha_path = zip_path.with_extension("zip.sha256");
 
-    let mut writer = Sha256AsyncWrite::new(cache_file);
-    io::copy(&mut response, &mut writer).await?;
+    let url = package.url().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "URL field of the package.json in the repository empty",
+        )
+    })?;
 
-    let (mut cache_file, hash) = writer.finalize();
+    let download =
+        |resume| download_to_part_file(http, io, headers, package, zip_path, resume, progress);
 
-    cache_file.flush().await?;
-    cache_file.seek(SeekFrom::Start(0)).await?;
+    let (mut hash, resumed) = download(true).await?;
+    if resumed && !hash_matches(package.zip_sha_256(), &hash[..]) {
+        // the partial file may be broken or the file on the server may be changed
+        warn!(
+            "resumed download of {} version {} is broken. downloading again",
+            package.name(),
+            package.version()
+        );
+        (hash, _) = download(false).await?;
+    }
+
+    // rename may not replace the stale zip file
+    match io.remove_file(zip_path).await {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    io.rename(&zip_path.with_extension("zip.part"), zip_path)
+        .await?;
+
+    if let Some(expected) = package.zip_sha_256() {
+        if !hash_matches(Some(expected), &hash[..]) {
+            // keep the file for investigation but never use it as a cache
+            let moved_to = zip_path.with_extension("zip.mismatched");
+            let moved_to = match io.rename(zip_path, &moved_to).await {
+                Ok(()) => Some(moved_to),
+                Err(e) => {
+                    error!("failed to move mismatched zip file aside: {e}");
+                    io.remove_file(zip_path).await.ok();
+                    None
+                }
+            };
+            io.remove_file(&sha_path).await.ok();
+
+            let repository = repository
+                .name()
+                .or(repository.id())
+                .or(repository.url().map(Url::as_str))
+                .unwrap_or("(unknown repository)");
+
+            return Err(DownloadPackageErr::HashMismatch {
+                package_name: package.name().into(),
+                version: package.version().clone(),
+                repository: repository.into(),
+                url: url.as_str().into(),
+                expected: expected.into(),
+                actual: hex::encode(&hash[..]).into(),
+                moved_to,
+            }
+            .into());
+        }
+    }
 
     // write sha file
+    let zip_file_name = zip_path.file_name().unwrap().to_string_lossy();
     io.write(
-        sha_path,
+        &sha_path,
         format!("{} {}\n", hex::encode(&hash[..]), zip_file_name).as_bytes(),
     )
     .await?;
 
-    Ok(cache_file)
+    io.open(zip_path).await
+}
+
+/// downloads the whole zip file to `<zip_path>.part`
+///
+/// If `resume` is true and the previous download is interrupted, this tries to download the rest.
+///
+/// returns: the sha256 of the whole zip file and whether the download is resumed
+async fn download_to_part_file(
+    http: Option<&impl HttpClient>,
+    io: &impl EnvironmentIo,
+    headers: &IndexMap<&str, &str>,
+    package: &PackageManifest,
+    zip_path: &Path,
+    resume: bool,
+    progress: &impl InstallProgress,
+) -> io::Result<(Output<Sha256>, bool)> {
+    let part_path = zip_path.with_extension("zip.part");
+    let validator_path = zip_path.with_extension("zip.part.validator");
+
+    let url = package.url().unwrap();
+    let partial = if resume && url.scheme() != "file" {
+        read_partial_download(io, &part_path, &validator_path).await
+    } else {
+        None
+    };
+    let (response, offset, total) = if url.scheme() == "file" {
+        // packages in file repositories are copied like downloading
+        let path = file_url_to_path(url)?;
+        (Either::Left(io.open(&path).await?), 0, None)
+    } else {
+        let Some(http) = http else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "Offline mode"));
+        };
+        let resume = partial
+            .as_ref()
+            .map(|(size, validator)| (*size, validator.as_ref()));
+        let response = http.get_resumable(url, headers, resume).await?;
+
+        // remember the validator to resume the download if interrupted
+        match &response.validator {
+            Some(validator) => io.write(&validator_path, validator.as_bytes()).await?,
+            None => {
+                io.remove_file(&validator_path).await.ok();
+            }
+        }
+
+        (
+            Either::Right(response.stream),
+            response.offset,
+            response.total,
+        )
+    };
+    let mut response = pin!(response);
+
+    if offset == 0 {
+        // io.create does not truncate the file
+        io.remove_file(&part_path).await.ok();
+    }
+    let mut part_file = io.create(&part_path).await?;
+
+    // hash the partially downloaded data
+    let mut hasher = Sha256::default();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut downloaded = 0u64;
+    while downloaded < offset {
+        let len = buffer.len().min((offset - downloaded) as usize);
+        part_file.read_exact(&mut buffer[..len]).await?;
+        hasher.update(&buffer[..len]);
+        downloaded += len as u64;
+    }
+
+    progress.download_started(package, total);
+    progress.download_progress(package, downloaded);
+
+    let mut writer = Sha256AsyncWrite::with_hasher(part_file, hasher);
+    loop {
+        let read = response.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..read]).await?;
+        downloaded += read as u64;
+        progress.download_progress(package, downloaded);
+    }
+
+    let (mut part_file, hash) = writer.finalize();
+    part_file.flush().await?;
+    drop(part_file);
+
+    io.remove_file(&validator_path).await.ok();
+
+    Ok((hash, offset != 0))
+}
+
+/// Returns the size of the partially downloaded file and the validator to resume with
+async fn read_partial_download(
+    io: &impl EnvironmentIo,
+    part_path: &Path,
+    validator_path: &Path,
+) -> Option<(u64, Box<str>)> {
+    let mut validator = String::new();
+    io.open(validator_path)
+        .await
+        .ok()?
+        .read_to_string(&mut validator)
+        .await
+        .ok()?;
+
+    let size = io
+        .open(part_path)
+        .await
+        .ok()?
+        .seek(SeekFrom::End(0))
+        .await
+        .ok()?;
+
+    if size == 0 || validator.is_empty() {
+        return None;
+    }
+
+    Some((size, validator.into()))
+}
+
+fn hash_matches(expected: Option<&str>, hash: &[u8]) -> bool {
+    match expected {
+        None => true,
+        Some(expected) => <[u8; 256 / 8] as FromHex>::from_hex(expected)
+            .map(|expected| expected[..] == hash[..])
+            .unwrap_or(false),
+    }
+}
+
+/// The error while downloading package.
+///
+/// This will be returned wrapped in [`io::Error`] from [`RemotePackageDownloader::get_package`]
+/// so you can get this with [`io::Error::get_ref`] and downcast.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DownloadPackageErr {
+    /// The downloaded zip file does not match `zipSHA256` in the repository.
+    HashMismatch {
+        package_name: Box<str>,
+        version: Version,
+        /// The name, id, or url of the repository served the package
+        repository: Box<str>,
+        url: Box<str>,
+        expected: Box<str>,
+        actual: Box<str>,
+        /// The path in the environment folder the mismatched file is moved to
+        moved_to: Option<PathBuf>,
+    },
+}
+
+impl fmt::Display for DownloadPackageErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DownloadPackageErr::HashMismatch {
+                package_name,
+                version,
+                repository,
+                url,
+                expected,
+                actual,
+                moved_to,
+            } => {
+                write!(
+                    f,
+                    "zip file of {package_name} version {version} downloaded from {url} (repository {repository}) \
+                    does not match zipSHA256 in the repository: expected {expected} but was {actual}"
+                )?;
+                if let Some(moved_to) = moved_to {
+                    write!(f, ". the file is moved to {}", moved_to.display())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for DownloadPackageErr {}
+
+impl From<DownloadPackageErr> for io::Error {
+    fn from(value: DownloadPackageErr) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, value)
+    }
 }
 
 #[derive(Debug)]
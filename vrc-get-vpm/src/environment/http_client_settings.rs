@@ -0,0 +1,176 @@
+use crate::environment::vrc_get_settings::VrcGetSettings;
+use crate::io;
+use crate::io::EnvironmentIo;
+use crate::utils::write_with_causes;
+use reqwest::{Certificate, Client, ClientBuilder, NoProxy, Proxy};
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+/// The settings of the http client in the vrc-get specific settings file.
+///
+/// Since the http client is required to load [`Environment`], load this with
+/// [`HttpClientSettings::load`] and [`build_client`] before loading the environment.
+///
+/// [`Environment`]: crate::Environment
+/// [`build_client`]: HttpClientSettings::build_client
+#[derive(Debug, Clone, Default)]
+pub struct HttpClientSettings {
+    pub(super) proxy: Option<Box<str>>,
+    pub(super) no_proxy: Option<Box<str>>,
+    pub(super) ca_bundle: Option<Box<Path>>,
+    pub(super) request_timeout: Option<Duration>,
+}
+
+impl HttpClientSettings {
+    pub async fn load(io: &impl EnvironmentIo) -> io::Result<Self> {
+        VrcGetSettings::load_http_client_settings(io).await
+    }
+
+    /// The URL of the proxy for all requests.
+    ///
+    /// If this is not set, the proxy is configured with environment variables like `HTTPS_PROXY`.
+    pub fn proxy(&self) -> Option<&str> {
+        self.proxy.as_deref()
+    }
+
+    /// The comma-separated hosts not to use [`proxy`](Self::proxy) for.
+    ///
+    /// If this is not set, `NO_PROXY` environment variable is used.
+    pub fn no_proxy(&self) -> Option<&str> {
+        self.no_proxy.as_deref()
+    }
+
+    /// The path to the PEM file with the root certificates trusted in addition to the system ones.
+    pub fn ca_bundle(&self) -> Option<&Path> {
+        self.ca_bundle.as_deref()
+    }
+
+    pub fn request_timeout(&self) -> Option<Duration> {
+        self.request_timeout
+    }
+
+    /// Builds the http client with the settings applied to `builder`.
+    pub fn build_client(
+        &self,
+        mut builder: ClientBuilder,
+    ) -> Result<Client, HttpClientSettingsErr> {
+        if let Some(proxy) = self.proxy() {
+            let no_proxy = match self.no_proxy() {
+                Some(no_proxy) => NoProxy::from_string(no_proxy),
+                None => NoProxy::from_env(),
+            };
+            let proxy = Proxy::all(proxy)
+                .map_err(|error| HttpClientSettingsErr::InvalidProxy {
+                    proxy: proxy.into(),
+                    error,
+                })?
+                .no_proxy(no_proxy);
+            builder = builder.proxy(proxy);
+        }
+
+        if let Some(ca_bundle) = self.ca_bundle() {
+            for certificate in read_ca_bundle(ca_bundle)? {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if let Some(timeout) = self.request_timeout() {
+            builder = builder.timeout(timeout);
+        }
+
+        builder
+            .build()
+            .map_err(|error| HttpClientSettingsErr::BuildClient { error })
+    }
+}
+
+fn read_ca_bundle(path: &Path) -> Result<Vec<Certificate>, HttpClientSettingsErr> {
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    const END: &str = "-----END CERTIFICATE-----";
+
+    let pem =
+        std::fs::read_to_string(path).map_err(|error| HttpClientSettingsErr::ReadCaBundle {
+            path: path.into(),
+            error,
+        })?;
+
+    // parse one by one since the backend may read only the first certificate
+    let mut certificates = vec![];
+    for (start, _) in pem.match_indices(BEGIN) {
+        let end = (pem[start..].find(END))
+            .map(|end| start + end + END.len())
+            .unwrap_or(pem.len());
+        let certificate = Certificate::from_pem(&pem.as_bytes()[start..end]).map_err(|error| {
+            HttpClientSettingsErr::InvalidCaBundle {
+                path: path.into(),
+                error,
+            }
+        })?;
+        certificates.push(certificate);
+    }
+
+    if certificates.is_empty() {
+        return Err(HttpClientSettingsErr::ReadCaBundle {
+            path: path.into(),
+            error: io::Error::new(io::ErrorKind::InvalidData, "no certificates found"),
+        });
+    }
+
+    Ok(certificates)
+}
+
+/// The error applying [`HttpClientSettings`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum HttpClientSettingsErr {
+    InvalidProxy {
+        proxy: Box<str>,
+        error: reqwest::Error,
+    },
+    ReadCaBundle {
+        path: Box<Path>,
+        error: io::Error,
+    },
+    InvalidCaBundle {
+        path: Box<Path>,
+        error: reqwest::Error,
+    },
+    /// The http client cannot be built, e.g. the TLS backend rejected the certificates.
+    BuildClient {
+        error: reqwest::Error,
+    },
+}
+
+impl fmt::Display for HttpClientSettingsErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpClientSettingsErr::InvalidProxy { proxy, error } => {
+                write!(f, "invalid proxy '{proxy}': ")?;
+                write_with_causes(f, error)
+            }
+            HttpClientSettingsErr::ReadCaBundle { path, error } => {
+                write!(f, "reading CA bundle '{}': {error}", path.display())
+            }
+            HttpClientSettingsErr::InvalidCaBundle { path, error } => {
+                write!(f, "invalid certificate in '{}': ", path.display())?;
+                write_with_causes(f, error)
+            }
+            HttpClientSettingsErr::BuildClient { error } => {
+                f.write_str("building http client: ")?;
+                write_with_causes(f, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HttpClientSettingsErr {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HttpClientSettingsErr::InvalidProxy { error, .. } => Some(error),
+            HttpClientSettingsErr::ReadCaBundle { error, .. } => Some(error),
+            HttpClientSettingsErr::InvalidCaBundle { error, .. } => Some(error),
+            HttpClientSettingsErr::BuildClient { error } => Some(error),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Mirroring packages into a directory to use them without the internet.
+
+use crate::io;
+use crate::io::{EnvironmentIo, IoTrait};
+use crate::traits::{HttpClient, InstallProgress, RemotePackageDownloader};
+use crate::utils::{to_vec_pretty_os_eol, Sha256AsyncWrite};
+use crate::{Environment, PackageInfo};
+use futures::prelude::*;
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+use std::path::Path;
+use url::Url;
+
+impl<T: HttpClient, IO: EnvironmentIo> Environment<T, IO> {
+    /// Copies the zip files of the packages to `dest` and writes `index.json` of the mirror.
+    ///
+    /// The zip files are written to `packages/<name>/<name>-<version>.zip` and `url` of
+    /// the packages in `index.json` are resolved from `base_url`, which should be the URL of `dest`.
+    /// The zip files are downloaded if they are not cached in the environment.
+    /// Local user packages and the duplicated versions are ignored.
+    ///
+    /// Returns the number of the mirrored packages.
+    pub async fn mirror_packages(
+        &self,
+        packages: &[PackageInfo<'_>],
+        name: &str,
+        dest: &impl IoTrait,
+        base_url: &Url,
+        progress: &impl InstallProgress,
+    ) -> io::Result<usize> {
+        let mut base_url = base_url.clone();
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+        let base_url = &base_url;
+        let index_url = join_url(base_url, "index.json")?;
+
+        let mut mirrored = HashSet::new();
+        let mut index_packages = Map::new();
+
+        for package in packages {
+            let Some(repo) = package.repo() else {
+                log::debug!("{} is not a remote package", package.name());
+                continue;
+            };
+            if !mirrored.insert((package.name(), package.version())) {
+                continue;
+            }
+            let manifest = package.package_json();
+
+            let relative = format!(
+                "packages/{name}/{name}-{version}.zip",
+                name = package.name(),
+                version = package.version()
+            );
+            let relative = Path::new(&relative);
+            dest.create_dir_all(relative.parent().unwrap()).await?;
+
+            let mut zip_file = self.get_package(repo, manifest, progress).await?;
+            let mut writer = Sha256AsyncWrite::new(dest.create(relative).await?);
+            io::copy(&mut zip_file, &mut writer).await?;
+            let (mut file, hash) = writer.finalize();
+            file.flush().await?;
+
+            let mut json = (repo.repo())
+                .get_package_version_json(package.name(), package.version())
+                .cloned()
+                .unwrap_or_else(|| {
+                    let mut json = Map::new();
+                    json.insert("name".into(), package.name().into());
+                    json.insert("version".into(), package.version().to_string().into());
+                    json
+                });
+            let url = join_url(base_url, relative.to_str().unwrap())?;
+            json.insert("url".into(), url.to_string().into());
+            json.insert("zipSHA256".into(), hex::encode(&hash[..]).into());
+            // the mirror does not require authentication
+            json.remove("headers");
+
+            let versions = index_packages
+                .entry(package.name())
+                .or_insert_with(|| json!({ "versions": {} }));
+            versions["versions"]
+                .as_object_mut()
+                .unwrap()
+                .insert(package.version().to_string(), Value::Object(json));
+        }
+
+        let index = json!({
+            "name": name,
+            "id": index_url.as_str(),
+            "url": index_url.as_str(),
+            "packages": index_packages,
+        });
+        dest.write("index.json".as_ref(), &to_vec_pretty_os_eol(&index)?)
+            .await?;
+
+        Ok(mirrored.len())
+    }
+}
+
+fn join_url(base_url: &Url, relative: &str) -> io::Result<Url> {
+    base_url
+        .join(relative)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
@@ -0,0 +1,283 @@
+//! Management of the zip files of the remote packages cached in the environment folder.
+
+use crate::environment::REPO_CACHE_FOLDER;
+use crate::io;
+use crate::io::{DirEntry, EnvironmentIo};
+use crate::traits::HttpClient;
+use crate::version::Version;
+use crate::Environment;
+use futures::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// The suffixes of the files cached for each version of the packages.
+///
+/// In addition to the zip file and its hash, the leftovers of the interrupted downloads and
+/// the zip files mismatched with `zipSHA256` are also managed as a part of the package.
+const CACHE_FILE_SUFFIXES: &[&str] = &[
+    ".zip",
+    ".zip.sha256",
+    ".zip.part",
+    ".zip.part.validator",
+    ".zip.mismatched",
+];
+
+/// A version of the package cached in the environment folder.
+#[derive(Debug, Clone)]
+pub struct CachedPackage {
+    name: Box<str>,
+    version: Version,
+    files: Vec<Box<Path>>,
+    size: u64,
+    modified: Option<SystemTime>,
+    downloaded: bool,
+}
+
+impl CachedPackage {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    /// The cached files relative to the environment folder.
+    pub fn files(&self) -> &[Box<Path>] {
+        &self.files
+    }
+
+    /// The total size of the cached files in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The last time any of the cached files is modified, which is usually when the package is
+    /// downloaded since the cached files are not modified by installing the package.
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// Returns false if only the leftovers of interrupted or mismatched downloads are cached.
+    pub fn is_downloaded(&self) -> bool {
+        self.downloaded
+    }
+}
+
+/// The options to select the cached packages to remove.
+///
+/// Without any options, all cached packages are selected.
+#[derive(Debug, Clone, Default)]
+pub struct CacheCleanOptions {
+    keep_versions: HashSet<(Box<str>, Version)>,
+    keep_latest: usize,
+    older_than: Option<Duration>,
+    max_size: Option<u64>,
+}
+
+impl CacheCleanOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps the versions of the packages, like the versions locked in the projects.
+    pub fn keep_versions<'a>(
+        mut self,
+        versions: impl IntoIterator<Item = (&'a str, &'a Version)>,
+    ) -> Self {
+        (self.keep_versions)
+            .extend((versions.into_iter()).map(|(name, version)| (name.into(), version.clone())));
+        self
+    }
+
+    /// Keeps the latest `count` versions of each package.
+    pub fn keep_latest(self, count: usize) -> Self {
+        Self {
+            keep_latest: count,
+            ..self
+        }
+    }
+
+    /// Selects only the packages not downloaded within `duration`.
+    ///
+    /// Installing the package from the cache does not count since it doesn't modify the files.
+    pub fn older_than(self, duration: Duration) -> Self {
+        Self {
+            older_than: Some(duration),
+            ..self
+        }
+    }
+
+    /// Selects the leftovers and the least recently downloaded packages only until the total size
+    /// of the cache becomes `max_size` bytes or less.
+    pub fn max_size(self, max_size: u64) -> Self {
+        Self {
+            max_size: Some(max_size),
+            ..self
+        }
+    }
+
+    /// Selects the packages to remove from `cached`.
+    ///
+    /// The leftovers of interrupted or mismatched downloads are not kept
+    /// by [`keep_versions`](Self::keep_versions) or [`keep_latest`](Self::keep_latest).
+    pub fn select<'a>(&self, cached: &'a [CachedPackage]) -> Vec<&'a CachedPackage> {
+        let mut kept = HashSet::new();
+
+        let mut versions = HashMap::<&str, Vec<usize>>::new();
+        for (index, package) in cached.iter().enumerate() {
+            if !package.is_downloaded() {
+                continue;
+            }
+            let key = (package.name.clone(), package.version.clone());
+            if self.keep_versions.contains(&key) {
+                kept.insert(index);
+            }
+            versions.entry(package.name()).or_default().push(index);
+        }
+        for mut indices in versions.into_values() {
+            indices.sort_by(|&a, &b| cached[b].version.cmp(&cached[a].version));
+            kept.extend(indices.into_iter().take(self.keep_latest));
+        }
+
+        let now = SystemTime::now();
+        let mut candidates = (0..cached.len())
+            .filter(|index| !kept.contains(index))
+            .filter(|&index| match (self.older_than, cached[index].modified) {
+                (Some(older_than), Some(modified)) => now
+                    .duration_since(modified)
+                    .map(|elapsed| elapsed >= older_than)
+                    .unwrap_or(false),
+                _ => true,
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(max_size) = self.max_size {
+            // the leftovers are removed first since they are useless as a cache
+            candidates.sort_by_key(|&index| (cached[index].downloaded, cached[index].modified));
+            let mut total_size = cached.iter().map(|x| x.size).sum::<u64>();
+            candidates.retain(|&index| {
+                if total_size <= max_size {
+                    return false;
+                }
+                total_size -= cached[index].size;
+                true
+            });
+            candidates.sort();
+        }
+
+        candidates.into_iter().map(|index| &cached[index]).collect()
+    }
+}
+
+impl<T: HttpClient, IO: EnvironmentIo> Environment<T, IO> {
+    /// Returns the versions of the packages cached in the environment folder,
+    /// sorted by the name and the version.
+    pub async fn get_cached_packages(&self) -> io::Result<Vec<CachedPackage>> {
+        let mut packages = HashMap::<(Box<str>, Version), CachedPackage>::new();
+
+        let mut repos = match self.io.read_dir(REPO_CACHE_FOLDER.as_ref()).await {
+            Ok(repos) => repos,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
+
+        while let Some(entry) = repos.try_next().await? {
+            if !entry.file_type().await?.is_dir() {
+                continue;
+            }
+            let Some(package_name) = entry.file_name().into_string().ok() else {
+                continue;
+            };
+            let folder = PathBuf::from(REPO_CACHE_FOLDER).join(&package_name);
+
+            let mut files = self.io.read_dir(&folder).await?;
+            while let Some(file) = files.try_next().await? {
+                let Some(file_name) = file.file_name().into_string().ok() else {
+                    continue;
+                };
+                let Some((version, suffix)) = parse_cache_file_name(&package_name, &file_name)
+                else {
+                    continue;
+                };
+                let metadata = file.metadata().await?;
+                if !metadata.is_file() {
+                    continue;
+                }
+
+                let package = (packages.entry((package_name.as_str().into(), version.clone())))
+                    .or_insert_with(|| CachedPackage {
+                        name: package_name.as_str().into(),
+                        version,
+                        files: vec![],
+                        size: 0,
+                        modified: None,
+                        downloaded: false,
+                    });
+                package
+                    .files
+                    .push(folder.join(&file_name).into_boxed_path());
+                package.size += metadata.len();
+                package.modified = package.modified.max(metadata.modified());
+                package.downloaded |= suffix == ".zip";
+            }
+        }
+
+        let mut packages = packages.into_values().collect::<Vec<_>>();
+        packages.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+        for package in &mut packages {
+            package.files.sort();
+        }
+        Ok(packages)
+    }
+
+    /// Removes the cached packages.
+    ///
+    /// Returns the total size of the removed files in bytes.
+    pub async fn remove_cached_packages(&self, packages: &[&CachedPackage]) -> io::Result<u64> {
+        let mut removed = 0;
+        for package in packages {
+            for file in package.files() {
+                match self.io.remove_file(file).await {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            removed += package.size();
+
+            // remove the package folder if it's empty
+            if let Some(folder) = package.files().first().and_then(|x| x.parent()) {
+                self.io.remove_dir(folder).await.ok();
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Removes the cached packages selected with `options` and returns the removed ones.
+    pub async fn clean_package_cache(
+        &self,
+        options: &CacheCleanOptions,
+    ) -> io::Result<Vec<CachedPackage>> {
+        let cached = self.get_cached_packages().await?;
+        let selected = options.select(&cached);
+        self.remove_cached_packages(&selected).await?;
+        Ok(selected.into_iter().cloned().collect())
+    }
+}
+
+/// Parses the file name like `vrc-get-<name>-<version>.zip` in the folder of the package.
+fn parse_cache_file_name(package_name: &str, file_name: &str) -> Option<(Version, &'static str)> {
+    let rest = file_name
+        .strip_prefix("vrc-get-")?
+        .strip_prefix(package_name)?
+        .strip_prefix('-')?;
+
+    let suffix = (CACHE_FILE_SUFFIXES.iter())
+        .find(|suffix| rest.ends_with(*suffix))
+        .copied()?;
+    let version = rest[..rest.len() - suffix.len()].parse().ok()?;
+
+    Some((version, suffix))
+}
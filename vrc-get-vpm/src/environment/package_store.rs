@@ -0,0 +1,195 @@
+//! The shared store of the extracted remote packages.
+//!
+//! When the store is enabled with `packageStore` in `vrc-get/settings.json`, each version of
+//! the remote packages is extracted once into `vrc-get/package-store/<name>/<version>/<sha256>`,
+//! where `<sha256>` is the hash of the zip file, and the files are hard linked into the projects.
+//! If hard links cannot be created, the files are copied instead.
+//!
+//! Since the files are shared with the store and other projects, modifying the files in place
+//! will affect all of them. Removing or replacing the files in the project is safe.
+//! The stored files are compared with the zip file every time before linking, and the package is
+//! extracted again if they are modified.
+
+use crate::io;
+use crate::io::{DirEntry, EnvironmentIo, SeekFrom};
+use crate::traits::{HttpClient, InstallProgress};
+use crate::utils::{compare_with_zip, extract_zip, Sha256AsyncWrite};
+use crate::version::Version;
+use crate::{Environment, PackageManifest};
+use futures::prelude::*;
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+const PACKAGE_STORE_FOLDER: &str = "vrc-get/package-store";
+
+impl<T: HttpClient, IO: EnvironmentIo> Environment<T, IO> {
+    /// Returns true if the remote packages are installed through the package store.
+    pub fn use_package_store(&self) -> bool {
+        self.vrc_get_settings.package_store()
+    }
+
+    pub fn set_use_package_store(&mut self, value: bool) {
+        self.vrc_get_settings.set_package_store(value);
+    }
+
+    /// Returns the versions of the packages extracted in the package store,
+    /// sorted by the name and the version.
+    pub async fn get_stored_packages(&self) -> io::Result<Vec<(Box<str>, Version)>> {
+        let mut stored = vec![];
+
+        let mut packages = match self.io.read_dir(PACKAGE_STORE_FOLDER.as_ref()).await {
+            Ok(packages) => packages,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
+
+        while let Some(package) = packages.try_next().await? {
+            if !package.file_type().await?.is_dir() {
+                continue;
+            }
+            let Some(name) = package.file_name().into_string().ok() else {
+                continue;
+            };
+
+            let folder = Path::new(PACKAGE_STORE_FOLDER).join(&name);
+            let mut versions = self.io.read_dir(&folder).await?;
+            while let Some(version) = versions.try_next().await? {
+                if !version.file_type().await?.is_dir() {
+                    continue;
+                }
+                let Some(version) = (version.file_name().to_str()).and_then(|x| x.parse().ok())
+                else {
+                    continue;
+                };
+                stored.push((name.as_str().into(), version));
+            }
+        }
+
+        stored.sort();
+        Ok(stored)
+    }
+
+    /// Removes the versions of the packages in the package store except for `keep`,
+    /// like the versions locked in the projects, and returns the removed versions.
+    ///
+    /// The projects the removed versions are installed to are not affected since the files are
+    /// hard linked or copied, but the versions will be extracted again when installed next time.
+    pub async fn prune_package_store<'a>(
+        &self,
+        keep: impl IntoIterator<Item = (&'a str, &'a Version)>,
+    ) -> io::Result<Vec<(Box<str>, Version)>> {
+        let keep = keep.into_iter().collect::<HashSet<_>>();
+
+        let mut removed = vec![];
+        for (name, version) in self.get_stored_packages().await? {
+            if keep.contains(&(name.as_ref(), &version)) {
+                continue;
+            }
+            let name_folder = Path::new(PACKAGE_STORE_FOLDER).join(name.as_ref());
+            self.io
+                .remove_dir_all(&name_folder.join(version.to_string()))
+                .await?;
+            // remove the package folder if no other versions are stored
+            let mut versions = self.io.read_dir(&name_folder).await?;
+            let empty = versions.try_next().await?.is_none();
+            drop(versions);
+            if empty {
+                self.io.remove_dir(&name_folder).await?;
+            }
+            removed.push((name, version));
+        }
+
+        Ok(removed)
+    }
+}
+
+/// Extracts the zip file of the package into the package store if not extracted yet.
+///
+/// returns: the path to the extracted package folder relative to the environment folder.
+pub(crate) async fn store_package(
+    io: &impl EnvironmentIo,
+    mut zip_file: impl AsyncBufRead + AsyncSeek + Unpin,
+    package: &PackageManifest,
+    progress: &impl InstallProgress,
+) -> io::Result<PathBuf> {
+    zip_file.seek(SeekFrom::Start(0)).await?;
+    let mut hasher = Sha256AsyncWrite::new(io::sink());
+    io::copy(&mut zip_file, &mut hasher).await?;
+    let sha256 = hex::encode(hasher.finalize().1);
+
+    // the name comes from the repository so it must not escape the store
+    if !is_valid_folder_name(package.name()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "package name {} cannot be used as a folder name",
+                package.name()
+            ),
+        ));
+    }
+
+    let version_folder = PathBuf::from(format!(
+        "{}/{}/{}",
+        PACKAGE_STORE_FOLDER,
+        package.name(),
+        package.version()
+    ));
+    let stored = version_folder.join(&sha256);
+    let modified = if io.is_dir(&stored).await {
+        // the stored files may be modified in place through the hard links in the projects
+        if compare_with_zip(io, &mut zip_file, &stored)
+            .await?
+            .is_empty()
+        {
+            return Ok(stored);
+        }
+        log::warn!(
+            "files of {}@{} in the package store are modified. extracting again",
+            package.name(),
+            package.version()
+        );
+        true
+    } else {
+        false
+    };
+
+    // extract to the temporary folder first so that the store never has partially extracted
+    // packages even if the process is killed or multiple processes are installing the package
+    let extracting = version_folder.join(format!(".{}.{}", sha256, uuid::Uuid::new_v4()));
+    let result = extract_zip(zip_file, io, &extracting, |extracted, total| {
+        if extracted == 0 {
+            progress.extract_started(package, total);
+        } else {
+            progress.extract_progress(package, extracted);
+        }
+    })
+    .await;
+    if let Err(err) = result {
+        io.remove_dir_all(&extracting).await.ok();
+        return Err(err);
+    }
+
+    if modified {
+        // the projects linked to the modified files keep them
+        io.remove_dir_all(&stored).await.ok();
+    }
+
+    if let Err(err) = io.rename(&extracting, &stored).await {
+        io.remove_dir_all(&extracting).await.ok();
+        // another process may have extracted the same package
+        if !io.is_dir(&stored).await {
+            return Err(err);
+        }
+    }
+
+    Ok(stored)
+}
+
+/// Returns true if `name` is a single normal component of the path,
+/// not `..`, an absolute path, nor a path with separators.
+fn is_valid_folder_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    !name.contains(['/', '\\'])
+        && matches!(components.next(), Some(Component::Normal(_)))
+        && components.next().is_none()
+}
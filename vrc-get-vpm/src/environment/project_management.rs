@@ -1,6 +1,6 @@
 use crate::io::{EnvironmentIo, FileSystemProjectIo, ProjectIo};
 use crate::utils::PathBufExt;
-use crate::version::UnityVersion;
+use crate::version::{UnityVersion, Version};
 use crate::{io, Environment, HttpClient, ProjectType, UnityProject};
 use bson::oid::ObjectId;
 use bson::DateTime;
@@ -157,6 +157,23 @@ impl<T: HttpClient, IO: EnvironmentIo> Environment<T, IO> {
         Ok(self.get_db()?.get_values(COLLECTION)?)
     }
 
+    /// Returns the versions of the packages locked in the registered projects.
+    ///
+    /// The projects which cannot be loaded are ignored.
+    pub async fn get_locked_packages_of_projects(&self) -> io::Result<Vec<(Box<str>, Version)>> {
+        let mut locked = vec![];
+        for project in self.get_projects()? {
+            let path = Path::new(project.path());
+            match UnityProject::load(self.io.new_project_io(path)).await {
+                Ok(unity) => locked.extend(
+                    (unity.locked_packages()).map(|x| (x.name().into(), x.version().clone())),
+                ),
+                Err(e) => error!("loading project {}: {}", path.display(), e),
+            }
+        }
+        Ok(locked)
+    }
+
     pub fn update_project_last_modified(&mut self, project_path: &Path) -> io::Result<()> {
         let db = self.get_db()?;
         let project_path = if project_path.is_absolute() {
@@ -1,16 +1,18 @@
 use crate::environment::repo_source::RepoSource;
+use crate::environment::UpdateRepositoryMode;
 use crate::io;
 use crate::io::EnvironmentIo;
 use crate::repository::local::LocalCachedRepository;
-use crate::repository::RemoteRepository;
+use crate::repository::signature::{is_signature_error, keys_fingerprint};
+use crate::repository::{resolve_headers, RemoteRepository};
 use crate::traits::HttpClient;
 use crate::utils::{read_json_file, to_vec_pretty_os_eol, try_load_json};
 use crate::{PackageCollection, PackageInfo, VersionSelector};
 use futures::future::join_all;
-use indexmap::IndexMap;
 use log::error;
 use std::collections::HashMap;
 use std::path::Path;
+use std::time::SystemTime;
 use url::Url;
 
 #[derive(Debug)]
@@ -32,22 +34,31 @@ impl RepoHolder {
         &mut self,
         http: Option<&impl HttpClient>,
         io: &IO,
+        update: UpdateRepositoryMode,
         sources: impl Iterator<Item = RepoSource<'a>>,
     ) -> io::Result<()> {
         let repos = join_all(sources.map(|src| async move {
-            match Self::load_repo_from_source(http, io, &src).await {
-                Ok(Some(v)) => Some((v, src.cache_path().into())),
+            match Self::load_repo_from_source(http, io, update, &src).await {
+                Ok(Some(v)) => Some((Some(v), src.cache_path().into())),
                 Ok(None) => None,
                 Err(e) => {
                     error!("loading repo '{}': {}", src.cache_path().display(), e);
-                    None
+                    Some((None, src.cache_path().into()))
                 }
             }
         }))
         .await;
 
         for (repo, path) in repos.into_iter().flatten() {
-            self.cached_repos_new.insert(path, repo);
+            match repo {
+                Some(repo) => {
+                    self.cached_repos_new.insert(path, repo);
+                }
+                None => {
+                    // the repository loaded before may be no longer trusted
+                    self.cached_repos_new.remove(&path);
+                }
+            }
         }
 
         Ok(())
@@ -56,10 +67,16 @@ impl RepoHolder {
     async fn load_repo_from_source<IO: EnvironmentIo>(
         client: Option<&impl HttpClient>,
         io: &IO,
+        update: UpdateRepositoryMode,
         source: &RepoSource<'_>,
     ) -> io::Result<Option<LocalCachedRepository>> {
-        if let Some(url) = &source.url() {
-            RepoHolder::load_remote_repo(client, io, source.headers(), source.cache_path(), url)
+        if let Some(url) = source.url().filter(|url| url.scheme() == "file") {
+            let update = update != UpdateRepositoryMode::None;
+            RepoHolder::load_file_repo(io, update, source, url)
+                .await
+                .map(Some)
+        } else if let Some(url) = &source.url() {
+            RepoHolder::load_remote_repo(client, io, update, source, url)
                 .await
                 .map(Some)
         } else {
@@ -69,38 +86,76 @@ impl RepoHolder {
         }
     }
 
+    /// Loads the remote repository and updates the cache if possible.
+    ///
+    /// If the repository has public keys, the cache not verified with the keys is not used,
+    /// and the cache is removed if the downloaded repository is not correctly signed.
+    /// With [`UpdateRepositoryMode::IfOutdated`], the cache fetched within the TTL is used as is.
     async fn load_remote_repo(
         client: Option<&impl HttpClient>,
         io: &impl EnvironmentIo,
-        headers: &IndexMap<Box<str>, Box<str>>,
-        path: &Path,
+        update: UpdateRepositoryMode,
+        source: &RepoSource<'_>,
         remote_url: &Url,
     ) -> io::Result<LocalCachedRepository> {
-        if let Some(mut loaded) = try_load_json::<LocalCachedRepository>(io, path).await? {
-            if let Some(client) = client {
+        let headers = source.headers();
+        let path = source.cache_path();
+        let fingerprint = keys_fingerprint(source.public_keys());
+        let loaded = try_load_json::<LocalCachedRepository>(io, path).await?;
+        // the cache verified with other keys is fetched and verified again
+        let loaded = loaded.filter(|x| x.is_verified_with(fingerprint.as_deref()));
+        if let Some(mut loaded) = loaded {
+            let fresh = update == UpdateRepositoryMode::IfOutdated
+                && loaded.is_fetched_within(source.ttl());
+            if fresh {
+                log::debug!("skipping fetching {} fetched recently", remote_url);
+            } else if let Some(client) = client {
                 // if it's possible to download remote repo, try to update with that
-                match RemoteRepository::download_with_etag(
-                    client,
-                    remote_url,
-                    loaded.headers(),
-                    loaded.vrc_get.as_ref().map(|x| x.etag.as_ref()),
-                )
-                .await
-                {
-                    Ok(None) => log::debug!("cache matched downloading {}", remote_url),
+                let downloaded = match resolve_headers(loaded.headers()) {
+                    Ok(headers) => {
+                        RemoteRepository::download_with_etag(
+                            client,
+                            remote_url,
+                            &headers,
+                            loaded.vrc_get.as_ref().map(|x| x.etag.as_ref()),
+                            source.public_keys(),
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e.into()),
+                };
+                let fetched = match downloaded {
+                    Ok(None) => {
+                        log::debug!("cache matched downloading {}", remote_url);
+                        true
+                    }
                     Ok(Some((remote_repo, etag))) => {
                         loaded.set_repo(remote_repo);
                         loaded.set_etag(etag);
-
-                        io.write(path, &to_vec_pretty_os_eol(&loaded)?)
-                            .await
-                            .unwrap_or_else(|e| {
-                                error!("writing local repo cache '{}': {}", path.display(), e)
-                            });
+                        true
+                    }
+                    Err(e) if is_signature_error(&e) => {
+                        // the cache is no longer trusted
+                        io.remove_file(path).await.unwrap_or_else(|e| {
+                            error!("removing local repo cache '{}': {}", path.display(), e)
+                        });
+                        return Err(e);
                     }
                     Err(e) => {
                         error!("fetching remote repo '{}': {}", remote_url, e);
+                        false
                     }
+                };
+
+                if fetched {
+                    loaded.set_fetched_at(SystemTime::now());
+                    loaded.set_verified_keys(fingerprint);
+
+                    io.write(path, &to_vec_pretty_os_eol(&loaded)?)
+                        .await
+                        .unwrap_or_else(|e| {
+                            error!("writing local repo cache '{}': {}", path.display(), e)
+                        });
                 }
             }
 
@@ -113,12 +168,21 @@ impl RepoHolder {
                     "offline mode",
                 ));
             };
-            let (remote_repo, etag) =
-                RemoteRepository::download(client, remote_url, headers).await?;
+            let (remote_repo, etag) = RemoteRepository::download_with_etag(
+                client,
+                remote_url,
+                &resolve_headers(headers)?,
+                None,
+                source.public_keys(),
+            )
+            .await?
+            .expect("downloading without etag always returns Some");
 
             let mut local_cache = LocalCachedRepository::new(remote_repo, headers.clone());
 
             local_cache.set_etag(etag);
+            local_cache.set_fetched_at(SystemTime::now());
+            local_cache.set_verified_keys(fingerprint);
 
             io.write(path, &to_vec_pretty_os_eol(&local_cache)?)
                 .await
@@ -130,6 +194,77 @@ impl RepoHolder {
         }
     }
 
+    /// Loads the repository from `file:` URL.
+    ///
+    /// Unlike remote repositories, this does not require the http client so works in offline mode.
+    async fn load_file_repo(
+        io: &impl EnvironmentIo,
+        update: bool,
+        source: &RepoSource<'_>,
+        file_url: &Url,
+    ) -> io::Result<LocalCachedRepository> {
+        let path = source.cache_path();
+        let fingerprint = keys_fingerprint(source.public_keys());
+        let loaded = try_load_json::<LocalCachedRepository>(io, path).await?;
+        // the cache verified with other keys is loaded and verified again
+        let loaded = loaded.filter(|x| x.is_verified_with(fingerprint.as_deref()));
+        if !update {
+            if let Some(loaded) = loaded {
+                return Ok(loaded);
+            }
+        }
+
+        let current_etag = (loaded.as_ref())
+            .and_then(|x| x.vrc_get.as_ref())
+            .map(|x| x.etag.as_ref());
+        let (repo, etag) = match RemoteRepository::load_file_with_etag(
+            io,
+            file_url,
+            current_etag,
+            source.public_keys(),
+        )
+        .await
+        {
+            Ok(None) => {
+                log::debug!("cache matched loading {}", file_url);
+                return Ok(loaded.unwrap());
+            }
+            Ok(Some(repo_and_etag)) => repo_and_etag,
+            Err(e) => match loaded {
+                Some(_) if is_signature_error(&e) => {
+                    // the cache is no longer trusted
+                    io.remove_file(path).await.unwrap_or_else(|e| {
+                        error!("removing local repo cache '{}': {}", path.display(), e)
+                    });
+                    return Err(e);
+                }
+                Some(loaded) => {
+                    error!("loading file repo '{}': {}", file_url, e);
+                    return Ok(loaded);
+                }
+                None => return Err(e),
+            },
+        };
+
+        let mut local_cache = match loaded {
+            Some(mut loaded) => {
+                loaded.set_repo(repo);
+                loaded
+            }
+            None => LocalCachedRepository::new(repo, source.headers().clone()),
+        };
+        local_cache.set_etag(etag);
+        local_cache.set_verified_keys(fingerprint);
+
+        io.write(path, &to_vec_pretty_os_eol(&local_cache)?)
+            .await
+            .unwrap_or_else(|e| {
+                error!("writing local repo cache '{}': {}", path.display(), e);
+            });
+
+        Ok(local_cache)
+    }
+
     async fn load_local_repo(
         io: &impl EnvironmentIo,
         path: &Path,
@@ -1,11 +1,14 @@
 use indexmap::IndexMap;
 use std::path::Path;
+use std::time::Duration;
 use url::Url;
 
 pub(crate) struct RepoSource<'a> {
     cache_path: &'a Path,
     headers: &'a IndexMap<Box<str>, Box<str>>,
     url: Option<&'a Url>,
+    public_keys: &'a [Box<str>],
+    ttl: Duration,
 }
 
 impl<'a> RepoSource<'a> {
@@ -18,9 +21,22 @@ impl<'a> RepoSource<'a> {
             cache_path,
             headers,
             url,
+            public_keys: &[],
+            ttl: Duration::ZERO,
         }
     }
 
+    pub fn with_public_keys(self, public_keys: &'a [Box<str>]) -> Self {
+        Self {
+            public_keys,
+            ..self
+        }
+    }
+
+    pub fn with_ttl(self, ttl: Duration) -> Self {
+        Self { ttl, ..self }
+    }
+
     pub fn cache_path(&self) -> &Path {
         self.cache_path
     }
@@ -32,4 +48,12 @@ impl<'a> RepoSource<'a> {
     pub fn url(&self) -> Option<&Url> {
         self.url
     }
+
+    pub fn public_keys(&self) -> &[Box<str>] {
+        self.public_keys
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
 }
@@ -1,7 +1,10 @@
+use crate::environment::HttpClientSettings;
 use crate::io;
 use crate::io::EnvironmentIo;
 use crate::utils::{read_json_file, SaveController};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
 
 /// since this file is vrc-get specific, additional keys can be removed
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -11,6 +14,24 @@ struct AsJson {
     ignore_official_repository: bool,
     #[serde(default)]
     ignore_curated_repository: bool,
+    /// The URL of the proxy for all requests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    proxy: Option<Box<str>>,
+    /// The comma-separated hosts not to use the proxy, like `NO_PROXY` environment variable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    no_proxy: Option<Box<str>>,
+    /// The path to PEM file with the additional root certificates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ca_bundle: Option<Box<Path>>,
+    /// The timeout of each request in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request_timeout: Option<u64>,
+    /// The seconds not to fetch the repositories again after fetched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    repository_ttl: Option<u64>,
+    /// Extract remote packages once into the package store and hard link them into projects
+    #[serde(default)]
+    package_store: bool,
 }
 
 #[derive(Debug)]
@@ -24,13 +45,12 @@ impl VrcGetSettings {
     pub async fn load(io: &impl EnvironmentIo) -> io::Result<Self> {
         //let parsed = load_json_or_default(io, JSON_PATH.as_ref()).await?;
 
-        let parsed = match io.open(JSON_PATH.as_ref()).await {
-            Ok(file) => {
+        let parsed = match read_json(io).await? {
+            Some(parsed) => {
                 log::warn!("vrc-get specific settings file is experimental feature!");
-                read_json_file::<AsJson>(file, JSON_PATH.as_ref()).await?
+                parsed
             }
-            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Default::default(),
-            Err(e) => return Err(e),
+            None => Default::default(),
         };
 
         Ok(Self {
@@ -38,6 +58,23 @@ impl VrcGetSettings {
         })
     }
 
+    /// Loads the settings for the http client.
+    ///
+    /// Since the http client is created before loading the environment,
+    /// this reads the settings file without loading whole settings.
+    pub async fn load_http_client_settings(
+        io: &impl EnvironmentIo,
+    ) -> io::Result<HttpClientSettings> {
+        let parsed = read_json(io).await?.unwrap_or_default();
+
+        Ok(HttpClientSettings {
+            proxy: parsed.proxy,
+            no_proxy: parsed.no_proxy,
+            ca_bundle: parsed.ca_bundle,
+            request_timeout: parsed.request_timeout.map(Duration::from_secs),
+        })
+    }
+
     pub fn ignore_official_repository(&self) -> bool {
         self.controller.ignore_official_repository
     }
@@ -56,7 +93,29 @@ impl VrcGetSettings {
         self.controller.as_mut().ignore_curated_repository = value;
     }
 
+    pub fn repository_ttl(&self) -> Option<Duration> {
+        self.controller.repository_ttl.map(Duration::from_secs)
+    }
+
+    pub fn package_store(&self) -> bool {
+        self.controller.package_store
+    }
+
+    pub fn set_package_store(&mut self, value: bool) {
+        self.controller.as_mut().package_store = value;
+    }
+
     pub async fn save(&mut self, io: &impl EnvironmentIo) -> io::Result<()> {
         self.controller.save(io, JSON_PATH.as_ref()).await
     }
 }
+
+async fn read_json(io: &impl EnvironmentIo) -> io::Result<Option<AsJson>> {
+    match io.open(JSON_PATH.as_ref()).await {
+        Ok(file) => Ok(Some(
+            read_json_file::<AsJson>(file, JSON_PATH.as_ref()).await?,
+        )),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
@@ -1,6 +1,7 @@
 use std::ffi::OsString;
 use std::future::Future;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 pub(crate) use futures::io::{
     copy, empty, sink, AsyncRead, AsyncSeek, AsyncWrite, BufReader, Error, ErrorKind, Result,
@@ -37,7 +38,18 @@ pub trait EnvironmentIo: Sync + IoTrait {
 /// Absolute paths are not allowed and relative paths should be resolved as a relative path from the project folder.
 ///
 /// [UnityProject]: crate::unity_project::UnityProject
-pub trait ProjectIo: Sync + IoTrait {}
+pub trait ProjectIo: Sync + IoTrait {
+    /// Creates a hard link at `link` in the project to the file at absolute path `original`.
+    ///
+    /// This is used to install packages from the package store without copying files.
+    /// Callers should fall back to copying if this fails, for example, because the file
+    /// system doesn't support hard links or `original` is on another volume.
+    /// Returns [`ErrorKind::Unsupported`] by default.
+    fn hard_link(&self, original: &Path, link: &Path) -> impl Future<Output = Result<()>> + Send {
+        let _ = (original, link);
+        async { Err(Error::from(ErrorKind::Unsupported)) }
+    }
+}
 
 pub trait FileSystemProjectIo {
     fn location(&self) -> &Path;
@@ -124,18 +136,35 @@ impl From<std::fs::FileType> for FileType {
 #[derive(Debug, Clone)]
 pub struct Metadata {
     file_type: FileType,
+    len: u64,
+    modified: Option<SystemTime>,
 }
 
 impl Metadata {
     pub fn file() -> Self {
         Self {
             file_type: FileType::file(),
+            len: 0,
+            modified: None,
         }
     }
 
     pub fn dir() -> Self {
         Self {
             file_type: FileType::dir(),
+            len: 0,
+            modified: None,
+        }
+    }
+
+    pub fn with_len(self, len: u64) -> Self {
+        Self { len, ..self }
+    }
+
+    pub fn with_modified(self, modified: SystemTime) -> Self {
+        Self {
+            modified: Some(modified),
+            ..self
         }
     }
 
@@ -150,12 +179,25 @@ impl Metadata {
     pub fn is_dir(&self) -> bool {
         self.file_type.is_dir
     }
+
+    /// The size of the file in bytes
+    #[allow(clippy::len_without_is_empty)] // same as std::fs::Metadata
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// The last modification time of the file if available
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
 }
 
 impl From<std::fs::Metadata> for Metadata {
     fn from(value: std::fs::Metadata) -> Self {
         Self {
             file_type: value.file_type().into(),
+            len: value.len(),
+            modified: value.modified().ok(),
         }
     }
 }
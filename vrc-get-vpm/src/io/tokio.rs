@@ -145,7 +145,11 @@ impl DefaultProjectIo {
     }
 }
 
-impl ProjectIo for DefaultProjectIo {}
+impl ProjectIo for DefaultProjectIo {
+    async fn hard_link(&self, original: &Path, link: &Path) -> io::Result<()> {
+        fs::hard_link(original, self.resolve(link)?).await
+    }
+}
 
 impl FileSystemProjectIo for DefaultProjectIo {
     #[inline]
@@ -14,6 +14,7 @@ use version::{ReleaseType, UnityVersion, Version, VersionRange};
 
 pub mod environment;
 pub mod io;
+mod pack;
 mod package_manifest;
 pub mod repository;
 mod structs;
@@ -31,13 +32,16 @@ pub mod unity_hub;
 use crate::repository::local::LocalCachedRepository;
 
 pub use environment::Environment;
+pub use pack::PackedPackage;
 pub use package_manifest::PackageManifest;
 pub use package_manifest::PartialUnityVersion;
 pub use structs::setting::UserRepoSetting;
 pub use traits::EnvironmentIoHolder;
 pub use traits::HttpClient;
+pub use traits::InstallProgress;
 pub use traits::PackageCollection;
 pub use traits::RemotePackageDownloader;
+pub use traits::ResumableResponse;
 pub use unity_project::UnityProject;
 pub use version_selector::VersionSelector;
 
@@ -107,6 +111,14 @@ impl<'a> PackageInfo<'a> {
         }
     }
 
+    /// The path to the package folder if the package is a local user package.
+    pub fn local_path(self) -> Option<&'a Path> {
+        match self.inner {
+            PackageInfoInner::Remote(_, _) => None,
+            PackageInfoInner::Local(_, path) => Some(path),
+        }
+    }
+
     #[allow(unused)]
     pub fn is_remote(self) -> bool {
         matches!(self.inner, PackageInfoInner::Remote(_, _))
@@ -0,0 +1,132 @@
+//! Packing a package folder into a zip file for release.
+//!
+//! The zip file is reproducible: the entries are sorted by path and have a fixed
+//! modification date, so the same folder always gives the same `zipSHA256`.
+
+use crate::io;
+use crate::io::{DirEntry, IoTrait};
+use crate::utils::{deserialize_json_slice, MapResultExt};
+use crate::PackageManifest;
+use async_zip::base::write::ZipFileWriter;
+use async_zip::{Compression, ZipDateTimeBuilder, ZipEntryBuilder};
+use futures::prelude::*;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// The package zip file built by [`PackedPackage::pack`].
+#[derive(Debug)]
+pub struct PackedPackage {
+    manifest: PackageManifest,
+    package_json: Map<String, Value>,
+    zip: Vec<u8>,
+    zip_sha256: String,
+    files: Vec<Box<Path>>,
+}
+
+impl PackedPackage {
+    /// Packs the package folder of `io` into a zip file.
+    ///
+    /// `package.json` at the root of the folder must be a valid package manifest.
+    /// Hidden files and folders, whose name starts with `.` like `.git`, are not included.
+    /// `excludes` are the paths relative to the folder to be excluded additionally.
+    pub async fn pack(io: &impl IoTrait, excludes: &[&Path]) -> io::Result<PackedPackage> {
+        let mut package_json = vec![];
+        io.open("package.json".as_ref())
+            .await?
+            .read_to_end(&mut package_json)
+            .await?;
+        let package_json = package_json
+            .strip_prefix(b"\xEF\xBB\xBF")
+            .unwrap_or(&package_json);
+        let manifest = deserialize_json_slice::<PackageManifest>(package_json)?;
+        let json = deserialize_json_slice::<Map<String, Value>>(package_json)?;
+
+        let files = collect_files(io, excludes).await?;
+
+        // 1980-01-01 00:00:00 is the earliest date representable in zip files
+        let date = ZipDateTimeBuilder::new().year(1980).month(1).day(1).build();
+        let mut writer = ZipFileWriter::new(futures::io::Cursor::new(Vec::new()));
+        for path in &files {
+            let mut content = vec![];
+            io.open(path).await?.read_to_end(&mut content).await?;
+
+            let name = (path.iter())
+                .map(|x| x.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let entry = ZipEntryBuilder::new(name.into(), Compression::Deflate)
+                .last_modification_date(date);
+            writer
+                .write_entry_whole(entry, &content)
+                .await
+                .err_mapped()?;
+        }
+        let zip = writer.close().await.err_mapped()?.into_inner();
+        let zip_sha256 = hex::encode(Sha256::digest(&zip));
+
+        Ok(PackedPackage {
+            manifest,
+            package_json: json,
+            zip,
+            zip_sha256,
+            files: files.into_iter().map(PathBuf::into_boxed_path).collect(),
+        })
+    }
+
+    pub fn manifest(&self) -> &PackageManifest {
+        &self.manifest
+    }
+
+    /// The bytes of the zip file.
+    pub fn zip(&self) -> &[u8] {
+        &self.zip
+    }
+
+    /// The sha256 of the zip file in lowercase hex.
+    pub fn zip_sha256(&self) -> &str {
+        &self.zip_sha256
+    }
+
+    /// The paths of the files in the zip file, relative to the package folder.
+    pub fn files(&self) -> &[Box<Path>] {
+        &self.files
+    }
+
+    /// Returns `package.json` with `url` and `zipSHA256` to be added to the repository.
+    pub fn repository_entry(&self, url: &Url) -> Map<String, Value> {
+        let mut json = self.package_json.clone();
+        json.insert("url".into(), url.to_string().into());
+        json.insert("zipSHA256".into(), self.zip_sha256.clone().into());
+        json
+    }
+}
+
+/// Collects the files to be packed in sorted order.
+async fn collect_files(io: &impl IoTrait, excludes: &[&Path]) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut directories = vec![PathBuf::new()];
+
+    while let Some(directory) = directories.pop() {
+        let mut read_dir = io.read_dir(&directory).await?;
+        while let Some(entry) = read_dir.try_next().await? {
+            let file_name = entry.file_name();
+            if file_name.to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = directory.join(file_name);
+            if excludes.iter().any(|x| path.starts_with(x)) {
+                continue;
+            }
+            if entry.file_type().await?.is_dir() {
+                directories.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
@@ -0,0 +1,191 @@
+//! Building a repository from a folder of package zip files.
+
+use crate::io;
+use crate::io::{DirEntry, IoTrait};
+use crate::repository::RemoteRepository;
+use crate::utils::{deserialize_json_slice, walk_dir_relative, MapResultExt};
+use crate::version::Version;
+use crate::PackageManifest;
+use async_zip::base::read::seek::ZipFileReader;
+use futures::prelude::*;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+use url::Url;
+
+/// The problem found while building the repository.
+///
+/// The zip files with problems are not added to the repository.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BuildRepositoryWarning {
+    /// The zip file does not contain `package.json` at the root.
+    NoPackageJson { path: Box<Path> },
+    /// `package.json` in the zip file is not a valid package manifest.
+    InvalidPackageJson { path: Box<Path>, error: io::Error },
+    /// The same version of the package is also in another zip file, which is used instead.
+    DuplicatedVersion {
+        name: Box<str>,
+        version: Version,
+        path: Box<Path>,
+        used: Box<Path>,
+    },
+}
+
+impl fmt::Display for BuildRepositoryWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildRepositoryWarning::NoPackageJson { path } => {
+                write!(f, "{}: package.json not found", path.display())
+            }
+            BuildRepositoryWarning::InvalidPackageJson { path, error } => {
+                write!(f, "{}: invalid package.json: {error}", path.display())
+            }
+            BuildRepositoryWarning::DuplicatedVersion {
+                name,
+                version,
+                path,
+                used,
+            } => write!(
+                f,
+                "{}: {name}@{version} is duplicated with {}",
+                path.display(),
+                used.display()
+            ),
+        }
+    }
+}
+
+impl RemoteRepository {
+    /// Builds the repository from the package zip files in the folder of `io`.
+    ///
+    /// The zip files are searched recursively and `package.json` of each zip file is added to
+    /// the repository with `url` resolved from `base_url` and `zipSHA256` of the zip file.
+    /// The `url` of the repository will be `index.json` in `base_url`.
+    pub async fn build_from_zips(
+        io: &impl IoTrait,
+        base_url: &Url,
+        name: &str,
+        id: Option<&str>,
+    ) -> io::Result<(RemoteRepository, Vec<BuildRepositoryWarning>)> {
+        let mut base_url = base_url.clone();
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+        let index_url = join_url(&base_url, "index.json")?;
+
+        let mut zip_paths = vec![];
+        let mut iterator = pin!(walk_dir_relative(io, vec![PathBuf::new()]));
+        while let Some((path, entry)) = iterator.next().await {
+            let is_zip = path.extension().map(|x| x.eq_ignore_ascii_case("zip")) == Some(true);
+            if is_zip && entry.file_type().await?.is_file() {
+                zip_paths.push(path);
+            }
+        }
+        zip_paths.sort();
+
+        let mut warnings = vec![];
+        let mut packages = Map::new();
+        let mut used_paths = HashMap::<(Box<str>, Version), Box<Path>>::new();
+
+        for path in zip_paths {
+            let mut bytes = vec![];
+            io.open(&path).await?.read_to_end(&mut bytes).await?;
+
+            let Some(package_json) = read_package_json(&bytes).await? else {
+                warnings.push(BuildRepositoryWarning::NoPackageJson { path: path.into() });
+                continue;
+            };
+
+            let (manifest, mut json) = match parse_package_json(&package_json) {
+                Ok(parsed) => parsed,
+                Err(error) => {
+                    warnings.push(BuildRepositoryWarning::InvalidPackageJson {
+                        path: path.into(),
+                        error,
+                    });
+                    continue;
+                }
+            };
+
+            let key = (manifest.name().into(), manifest.version().clone());
+            if let Some(used) = used_paths.get(&key) {
+                warnings.push(BuildRepositoryWarning::DuplicatedVersion {
+                    name: key.0,
+                    version: key.1,
+                    path: path.into(),
+                    used: used.clone(),
+                });
+                continue;
+            }
+
+            let relative = (path.iter())
+                .map(|x| x.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let url = join_url(&base_url, &relative)?;
+            json.insert("url".into(), url.to_string().into());
+            json.insert(
+                "zipSHA256".into(),
+                hex::encode(Sha256::digest(&bytes)).into(),
+            );
+
+            let versions = packages
+                .entry(manifest.name())
+                .or_insert_with(|| json!({ "versions": {} }));
+            versions["versions"]
+                .as_object_mut()
+                .unwrap()
+                .insert(manifest.version().to_string(), Value::Object(json));
+
+            used_paths.insert(key, path.into());
+        }
+
+        let index = json!({
+            "name": name,
+            "id": id.unwrap_or(index_url.as_str()),
+            "url": index_url.as_str(),
+            "packages": packages,
+        });
+        let Value::Object(index) = index else {
+            unreachable!()
+        };
+
+        Ok((RemoteRepository::parse(index)?, warnings))
+    }
+}
+
+/// Reads `package.json` at the root of the zip file.
+async fn read_package_json(zip: &[u8]) -> io::Result<Option<Vec<u8>>> {
+    let mut reader = ZipFileReader::new(futures::io::Cursor::new(zip))
+        .await
+        .err_mapped()?;
+
+    let index = (reader.file().entries().iter())
+        .position(|x| x.filename().as_str().ok() == Some("package.json"));
+    let Some(index) = index else {
+        return Ok(None);
+    };
+
+    let mut buffer = vec![];
+    (reader.reader_without_entry(index).await.err_mapped()?)
+        .read_to_end(&mut buffer)
+        .await?;
+    Ok(Some(buffer))
+}
+
+fn parse_package_json(bytes: &[u8]) -> io::Result<(PackageManifest, Map<String, Value>)> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let manifest = deserialize_json_slice::<PackageManifest>(bytes)?;
+    let json = deserialize_json_slice::<Map<String, Value>>(bytes)?;
+    Ok((manifest, json))
+}
+
+fn join_url(base_url: &Url, relative: &str) -> io::Result<Url> {
+    base_url
+        .join(relative)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
@@ -0,0 +1,184 @@
+//! Credential references in the headers of user repositories.
+//!
+//! Instead of the secret itself, header values can contain the following references,
+//! which are resolved every time the request is made and never saved to the disk.
+//!
+//! - `${env:NAME}` is replaced with the value of the environment variable `NAME`.
+//! - `${command:COMMAND}` is replaced with the standard output of `COMMAND` run with the shell,
+//!   without trailing newlines. `COMMAND` cannot contain `}`.
+//!
+//! For example, `Authorization: Bearer ${command:gh auth token}`.
+//!
+//! The references are only resolved for the headers of user repositories, not for the headers of
+//! packages in the repository since they are controlled by the owner of the repository.
+
+use crate::io;
+use indexmap::IndexMap;
+use std::borrow::Cow;
+use std::fmt;
+use std::process::{Command, ExitStatus, Stdio};
+
+const ENV_PREFIX: &str = "${env:";
+const COMMAND_PREFIX: &str = "${command:";
+
+/// The error resolving credential references in the header values.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CredentialError {
+    /// The reference is not closed with `}`.
+    Unterminated,
+    /// The referenced environment variable is not set or not valid unicode.
+    MissingEnvironmentVariable(Box<str>),
+    /// The credential helper command cannot be started.
+    RunHelper { command: Box<str>, error: io::Error },
+    /// The credential helper command exited with non-zero status.
+    HelperFailed {
+        command: Box<str>,
+        status: ExitStatus,
+    },
+    /// The credential helper command printed non-utf8 value.
+    InvalidHelperOutput { command: Box<str> },
+}
+
+impl fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CredentialError::Unterminated => {
+                f.write_str("credential reference in header is not closed with '}'")
+            }
+            CredentialError::MissingEnvironmentVariable(name) => {
+                write!(f, "environment variable '{name}' for header is not set")
+            }
+            CredentialError::RunHelper { command, error } => {
+                write!(f, "running credential helper '{command}': {error}")
+            }
+            CredentialError::HelperFailed { command, status } => {
+                write!(f, "credential helper '{command}' failed: {status}")
+            }
+            CredentialError::InvalidHelperOutput { command } => {
+                write!(f, "credential helper '{command}' printed non-utf8 value")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CredentialError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CredentialError::RunHelper { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<CredentialError> for io::Error {
+    fn from(value: CredentialError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, value)
+    }
+}
+
+/// Returns true if the header value contains credential references,
+/// which read environment variables or run credential helper commands.
+///
+/// This is useful to reject headers from untrusted sources like deep links.
+pub fn uses_credential_reference(value: &str) -> bool {
+    value.contains(ENV_PREFIX) || value.contains(COMMAND_PREFIX)
+}
+
+/// Resolves the credential references in the header values.
+///
+/// The resolved headers should only be used for the request and should not be saved.
+pub fn resolve_headers(
+    headers: &IndexMap<Box<str>, Box<str>>,
+) -> Result<IndexMap<Box<str>, Box<str>>, CredentialError> {
+    let mut resolved = IndexMap::with_capacity(headers.len());
+    for (name, value) in headers {
+        let value = match resolve_header_value(value)? {
+            Cow::Borrowed(_) => value.clone(),
+            Cow::Owned(value) => value.into(),
+        };
+        resolved.insert(name.clone(), value);
+    }
+    Ok(resolved)
+}
+
+fn resolve_header_value(value: &str) -> Result<Cow<'_, str>, CredentialError> {
+    if !value.contains(ENV_PREFIX) && !value.contains(COMMAND_PREFIX) {
+        return Ok(Cow::Borrowed(value));
+    }
+
+    let mut resolved = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some((start, prefix)) = find_reference(rest) {
+        resolved.push_str(&rest[..start]);
+        let body = &rest[start + prefix.len()..];
+        let end = body.find('}').ok_or(CredentialError::Unterminated)?;
+        let argument = &body[..end];
+
+        if prefix == ENV_PREFIX {
+            let env = std::env::var(argument)
+                .map_err(|_| CredentialError::MissingEnvironmentVariable(argument.into()))?;
+            resolved.push_str(&env);
+        } else {
+            resolved.push_str(&run_credential_helper(argument)?);
+        }
+
+        rest = &body[end + 1..];
+    }
+
+    resolved.push_str(rest);
+    Ok(Cow::Owned(resolved))
+}
+
+/// Finds the first reference in `value` and returns the index and the prefix of it.
+fn find_reference(value: &str) -> Option<(usize, &'static str)> {
+    [ENV_PREFIX, COMMAND_PREFIX]
+        .into_iter()
+        .filter_map(|prefix| value.find(prefix).map(|index| (index, prefix)))
+        .min_by_key(|&(index, _)| index)
+}
+
+fn run_credential_helper(command: &str) -> Result<String, CredentialError> {
+    let output = shell_command(command)
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|error| CredentialError::RunHelper {
+            command: command.into(),
+            error,
+        })?;
+
+    if !output.status.success() {
+        return Err(CredentialError::HelperFailed {
+            command: command.into(),
+            status: output.status,
+        });
+    }
+
+    let mut value =
+        String::from_utf8(output.stdout).map_err(|_| CredentialError::InvalidHelperOutput {
+            command: command.into(),
+        })?;
+    value.truncate(value.trim_end_matches(['\r', '\n']).len());
+    Ok(value)
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C")
+        .raw_arg(command)
+        .creation_flags(CREATE_NO_WINDOW);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
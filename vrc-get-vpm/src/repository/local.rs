@@ -2,6 +2,7 @@ use crate::repository::{RemotePackages, RemoteRepository};
 use crate::{PackageCollection, PackageInfo, PackageManifest, VersionSelector};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use url::Url;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -49,6 +50,48 @@ impl LocalCachedRepository {
         }
     }
 
+    pub(crate) fn set_verified_keys(&mut self, fingerprint: Option<Box<str>>) {
+        if fingerprint.is_some() {
+            self.vrc_get
+                .get_or_insert_with(Default::default)
+                .verified_keys = fingerprint;
+        } else if let Some(x) = self.vrc_get.as_mut() {
+            x.verified_keys = None;
+        }
+    }
+
+    /// Returns true if the repository was verified with the public keys of the `fingerprint`.
+    ///
+    /// The repository without public keys (`None` fingerprint) is always trusted.
+    pub(crate) fn is_verified_with(&self, fingerprint: Option<&str>) -> bool {
+        let Some(fingerprint) = fingerprint else {
+            return true;
+        };
+        (self.vrc_get.as_ref()).and_then(|x| x.verified_keys.as_deref()) == Some(fingerprint)
+    }
+
+    pub(crate) fn set_fetched_at(&mut self, time: SystemTime) {
+        let seconds = (time.duration_since(UNIX_EPOCH))
+            .unwrap_or(Duration::ZERO)
+            .as_secs();
+        self.vrc_get.get_or_insert_with(Default::default).fetched_at = Some(seconds);
+    }
+
+    /// Returns when the repository was last fetched from the remote server.
+    pub fn fetched_at(&self) -> Option<SystemTime> {
+        (self.vrc_get.as_ref())
+            .and_then(|x| x.fetched_at)
+            .map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    /// Returns true if the repository was fetched within `ttl` so fetching again can be skipped.
+    pub fn is_fetched_within(&self, ttl: Duration) -> bool {
+        self.fetched_at()
+            .and_then(|fetched_at| fetched_at.elapsed().ok())
+            .map(|elapsed| elapsed < ttl)
+            .unwrap_or(false)
+    }
+
     pub fn url(&self) -> Option<&Url> {
         self.repo().url()
     }
@@ -74,6 +117,14 @@ impl LocalCachedRepository {
 pub struct VrcGetMeta {
     #[serde(default, skip_serializing_if = "str::is_empty")]
     pub etag: Box<str>,
+    /// The unix time in seconds when the repository was last fetched
+    #[serde(rename = "fetchedAt")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetched_at: Option<u64>,
+    /// The fingerprint of the public keys the repository was verified with
+    #[serde(rename = "verifiedKeys")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified_keys: Option<Box<str>>,
 }
 
 impl PackageCollection for LocalCachedRepository {
@@ -1,6 +1,12 @@
+mod build;
+mod credentials;
 pub(crate) mod local;
-mod remote;
+pub(crate) mod remote;
+pub(crate) mod signature;
 
+pub use build::BuildRepositoryWarning;
+pub use credentials::{resolve_headers, uses_credential_reference, CredentialError};
 pub use local::LocalCachedRepository;
 pub use remote::RemotePackages;
 pub use remote::RemoteRepository;
+pub use signature::{is_valid_public_key, SignatureError, SigningKey};
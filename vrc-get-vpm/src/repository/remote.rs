@@ -1,3 +1,5 @@
+use crate::io::EnvironmentIo;
+use crate::repository::signature::{signature_url, verify_signature, SignatureError};
 use crate::traits::HttpClient;
 use crate::utils::{deserialize_json, deserialize_json_slice};
 use crate::version::Version;
@@ -8,7 +10,9 @@ use indexmap::IndexMap;
 use serde::de::{DeserializeSeed, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::pin::pin;
 use url::Url;
 
@@ -46,18 +50,26 @@ impl RemoteRepository {
         url: &Url,
         headers: &IndexMap<Box<str>, Box<str>>,
     ) -> io::Result<(RemoteRepository, Option<Box<str>>)> {
-        match Self::download_with_etag(client, url, headers, None).await {
-            Ok(None) => unreachable!("downloading without etag should must return Ok(Some)"),
+        match Self::download_with_etag(client, url, headers, None, &[]).await {
+            Ok(None) => unreachable!("downloading without etag always returns Ok(Some)"),
             Ok(Some(repo_and_etag)) => Ok(repo_and_etag),
             Err(err) => Err(err),
         }
     }
 
+    /// Downloads the repository if the etag is not matched.
+    ///
+    /// If `public_keys` is not empty, the signature at the URL with `.sig` appended is also
+    /// downloaded and the repository must be signed with one of `public_keys`.
+    /// See [`SignatureError`] for the errors with the signature.
+    ///
+    /// [`SignatureError`]: crate::repository::SignatureError
     pub async fn download_with_etag(
         client: &impl HttpClient,
         url: &Url,
         headers: &IndexMap<Box<str>, Box<str>>,
         current_etag: Option<&str>,
+        public_keys: &[Box<str>],
     ) -> io::Result<Option<(RemoteRepository, Option<Box<str>>)>> {
         let Some((mut stream, etag)) = client.get_with_etag(url, headers, current_etag).await?
         else {
@@ -67,14 +79,77 @@ impl RemoteRepository {
         let mut bytes = Vec::new();
         pin!(stream).read_to_end(&mut bytes).await?;
 
-        let no_bom = bytes
-            .strip_prefix(b"\xEF\xBB\xBF")
-            .unwrap_or(bytes.as_ref());
+        if !public_keys.is_empty() {
+            let signature = download_signature(client, url, headers)
+                .await
+                .map_err(SignatureError::Missing)?;
+            verify_signature(&bytes, &signature, public_keys)?;
+        }
+
+        Ok(Some((Self::parse_bytes(&bytes, url)?, etag)))
+    }
+
+    /// Loads the repository from `file:` URL.
+    ///
+    /// If the URL points to a directory, `index.json` in the directory will be loaded.
+    pub async fn load_file(io: &impl EnvironmentIo, url: &Url) -> io::Result<RemoteRepository> {
+        match Self::load_file_with_etag(io, url, None, &[]).await {
+            Ok(None) => unreachable!("loading without etag always returns Ok(Some)"),
+            Ok(Some((repo, _))) => Ok(repo),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Loads the repository from `file:` URL if the file is changed.
+    ///
+    /// The sha256 of the file is used as the etag, so this returns `Ok(None)`
+    /// if the contents of the file are not changed.
+    /// Like [`RemoteRepository::download_with_etag`], the repository must be signed with
+    /// one of `public_keys` if `public_keys` is not empty.
+    pub async fn load_file_with_etag(
+        io: &impl EnvironmentIo,
+        url: &Url,
+        current_etag: Option<&str>,
+        public_keys: &[Box<str>],
+    ) -> io::Result<Option<(RemoteRepository, Option<Box<str>>)>> {
+        let mut path = file_url_to_path(url)?;
+        if io.is_dir(&path).await {
+            path.push("index.json");
+        }
+
+        let mut bytes = Vec::new();
+        io.open(&path).await?.read_to_end(&mut bytes).await?;
+
+        let etag = format!("sha256:{}", hex::encode(Sha256::digest(&bytes)));
+        if current_etag == Some(etag.as_str()) {
+            return Ok(None);
+        }
+
+        if !public_keys.is_empty() {
+            let mut signature_path = path.into_os_string();
+            signature_path.push(".sig");
+            let mut signature = Vec::new();
+            async {
+                io.open(signature_path.as_ref())
+                    .await?
+                    .read_to_end(&mut signature)
+                    .await
+            }
+            .await
+            .map_err(SignatureError::Missing)?;
+            verify_signature(&bytes, &signature, public_keys)?;
+        }
+
+        Ok(Some((Self::parse_bytes(&bytes, url)?, Some(etag.into()))))
+    }
+
+    fn parse_bytes(bytes: &[u8], url: &Url) -> io::Result<RemoteRepository> {
+        let no_bom = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
         let json = deserialize_json_slice(no_bom)?;
 
         let mut repo = RemoteRepository::parse(json)?;
         repo.set_url_if_none(|| url.clone());
-        Ok(Some((repo, etag)))
+        Ok(repo)
     }
 
     pub(crate) fn set_id_if_none(&mut self, f: impl FnOnce() -> Box<str>) {
@@ -127,6 +202,29 @@ impl RemoteRepository {
     pub fn get_package_version(&self, name: &str, version: &Version) -> Option<&PackageManifest> {
         self.parsed.packages.get(name)?.versions.get(version)
     }
+
+    /// Returns the original json of the package version including unknown fields.
+    pub(crate) fn get_package_version_json(
+        &self,
+        name: &str,
+        version: &Version,
+    ) -> Option<&JsonMap> {
+        let versions = self
+            .actual
+            .get("packages")?
+            .as_object()?
+            .get(name)?
+            .as_object()?
+            .get("versions")?
+            .as_object()?;
+
+        versions.values().filter_map(Value::as_object).find(|json| {
+            (json.get("version").and_then(Value::as_str))
+                .and_then(|x| x.parse::<Version>().ok())
+                .as_ref()
+                == Some(version)
+        })
+    }
 }
 
 impl Serialize for RemoteRepository {
@@ -306,3 +404,34 @@ impl<'de, 'a> DeserializeSeed<'de> for ErrorProofManifest<'a> {
         }
     }
 }
+
+async fn download_signature(
+    client: &impl HttpClient,
+    url: &Url,
+    headers: &IndexMap<Box<str>, Box<str>>,
+) -> io::Result<Vec<u8>> {
+    let url = signature_url(url);
+    let Some((stream, _)) = client.get_with_etag(&url, headers, None).await? else {
+        unreachable!("downloading without etag always returns Some")
+    };
+
+    let mut signature = Vec::new();
+    pin!(stream).read_to_end(&mut signature).await?;
+    Ok(signature)
+}
+
+/// Returns the path of `file:` URL, or error if the URL is not a file URL.
+pub(crate) fn file_url_to_path(url: &Url) -> io::Result<PathBuf> {
+    if url.scheme() != "file" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{url} is not a file URL"),
+        ));
+    }
+    url.to_file_path().map_err(|()| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{url} is not a valid file URL"),
+        )
+    })
+}
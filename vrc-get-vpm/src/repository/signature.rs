@@ -0,0 +1,166 @@
+//! Detached ed25519 signatures of repository indexes.
+//!
+//! The signature of `index.json` is placed at the URL with `.sig` appended, like `index.json.sig`,
+//! and contains the base64-encoded ed25519 signature over the raw bytes of `index.json`.
+//! The public keys and the signing keys are also base64-encoded.
+
+use crate::io;
+use base64::prelude::*;
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use url::Url;
+
+/// The error verifying the signature of the repository.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SignatureError {
+    /// The signature is not found or cannot be downloaded.
+    Missing(io::Error),
+    /// The signature is not a valid base64-encoded ed25519 signature.
+    Malformed,
+    /// The signature is not made with any of the trusted public keys.
+    Untrusted,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::Missing(e) => write!(f, "signature of repository not found: {e}"),
+            SignatureError::Malformed => f.write_str("signature of repository is malformed"),
+            SignatureError::Untrusted => {
+                f.write_str("signature of repository does not match any trusted public key")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SignatureError::Missing(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<SignatureError> for io::Error {
+    fn from(value: SignatureError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, value)
+    }
+}
+
+/// The key to sign repository indexes with.
+pub struct SigningKey {
+    pkcs8: Box<[u8]>,
+    key_pair: Ed25519KeyPair,
+}
+
+impl SigningKey {
+    /// Generates a new random signing key.
+    pub fn generate() -> io::Result<SigningKey> {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .map_err(|_| io::Error::other("generating signing key"))?;
+        Self::from_pkcs8(pkcs8.as_ref())
+    }
+
+    /// Parses the signing key saved with [`SigningKey::to_base64`].
+    pub fn from_base64(key: &str) -> io::Result<SigningKey> {
+        let pkcs8 = BASE64_STANDARD
+            .decode(key.trim())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Self::from_pkcs8(&pkcs8)
+    }
+
+    fn from_pkcs8(pkcs8: &[u8]) -> io::Result<SigningKey> {
+        let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(pkcs8)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        Ok(SigningKey {
+            pkcs8: pkcs8.into(),
+            key_pair,
+        })
+    }
+
+    /// Returns the signing key in base64-encoded PKCS#8 to be saved.
+    pub fn to_base64(&self) -> String {
+        BASE64_STANDARD.encode(&self.pkcs8)
+    }
+
+    /// Returns the public key to be trusted by the users of the repository.
+    pub fn public_key(&self) -> String {
+        BASE64_STANDARD.encode(self.key_pair.public_key())
+    }
+
+    /// Signs the raw bytes of `index.json` and returns the contents of the signature file.
+    pub fn sign(&self, index_json: &[u8]) -> String {
+        BASE64_STANDARD.encode(self.key_pair.sign(index_json))
+    }
+}
+
+impl fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("public_key", &self.public_key())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns true if the public key is a valid base64-encoded ed25519 public key.
+pub fn is_valid_public_key(public_key: &str) -> bool {
+    BASE64_STANDARD
+        .decode(public_key)
+        .map(|x| x.len() == 32)
+        .unwrap_or(false)
+}
+
+/// Returns the fingerprint of `public_keys` saved with the cache verified with the keys,
+/// or `None` if no keys are configured so the repository is not verified.
+pub(crate) fn keys_fingerprint(public_keys: &[Box<str>]) -> Option<Box<str>> {
+    if public_keys.is_empty() {
+        return None;
+    }
+
+    let mut keys = public_keys.iter().map(|x| x.trim()).collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+    let hash = Sha256::digest(keys.join("\n"));
+    Some(format!("sha256:{}", hex::encode(hash)).into())
+}
+
+/// Returns true if the error is caused by the signature of the repository.
+pub(crate) fn is_signature_error(error: &io::Error) -> bool {
+    (error.get_ref()).is_some_and(|e| e.downcast_ref::<SignatureError>().is_some())
+}
+
+/// Returns the URL of the signature for the repository at `url`.
+pub(crate) fn signature_url(url: &Url) -> Url {
+    let mut url = url.clone();
+    url.set_path(&format!("{}.sig", url.path()));
+    url
+}
+
+/// Verifies the signature over `index_json` is made with one of `public_keys`.
+pub(crate) fn verify_signature(
+    index_json: &[u8],
+    signature: &[u8],
+    public_keys: &[Box<str>],
+) -> Result<(), SignatureError> {
+    let signature = BASE64_STANDARD
+        .decode(signature.trim_ascii())
+        .map_err(|_| SignatureError::Malformed)?;
+
+    let trusted = (public_keys.iter())
+        .filter_map(|key| BASE64_STANDARD.decode(key.as_bytes()).ok())
+        .any(|key| {
+            UnparsedPublicKey::new(&ED25519, key)
+                .verify(index_json, &signature)
+                .is_ok()
+        });
+
+    if trusted {
+        Ok(())
+    } else {
+        Err(SignatureError::Untrusted)
+    }
+}
@@ -3,6 +3,7 @@ pub mod setting {
     use indexmap::IndexMap;
     use serde::{Deserialize, Serialize};
     use std::path::Path;
+    use std::time::Duration;
     use url::Url;
 
     #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -18,6 +19,12 @@ pub mod setting {
         pub(crate) id: Option<Box<str>>,
         #[serde(default)]
         headers: IndexMap<Box<str>, Box<str>>,
+        // vrc-get extension: the repository must be signed with one of these keys if not empty
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        pub(crate) public_keys: Vec<Box<str>>,
+        // vrc-get extension: the repository is not fetched again within this seconds
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub(crate) ttl: Option<u64>,
     }
 
     impl UserRepoSetting {
@@ -33,6 +40,8 @@ pub mod setting {
                 id: id.or(url.as_ref().map(Url::to_string).map(Into::into)),
                 url,
                 headers: IndexMap::new(),
+                public_keys: Vec::new(),
+                ttl: None,
             }
         }
 
@@ -56,8 +65,23 @@ pub mod setting {
             &self.headers
         }
 
+        /// The base64-encoded ed25519 public keys trusted to sign the repository.
+        ///
+        /// If this is empty, the signature of the repository is not checked.
+        pub fn public_keys(&self) -> &[Box<str>] {
+            &self.public_keys
+        }
+
+        /// The duration the repository is not fetched again after fetched.
+        ///
+        /// If this is not set, the global setting is used.
+        pub fn ttl(&self) -> Option<Duration> {
+            self.ttl.map(Duration::from_secs)
+        }
+
         pub(crate) fn to_source(&self) -> RepoSource {
             RepoSource::new(&self.local_path, &self.headers, self.url.as_ref())
+                .with_public_keys(&self.public_keys)
         }
     }
 }
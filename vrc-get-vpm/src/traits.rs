@@ -7,6 +7,7 @@ use core::iter::Iterator;
 use core::option::Option;
 use futures::prelude::*;
 use indexmap::IndexMap;
+use reqwest::StatusCode;
 use std::convert::Infallible;
 use url::Url;
 
@@ -45,13 +46,53 @@ pub trait RemotePackageDownloader {
     type FileStream: AsyncRead + AsyncSeek + Unpin;
 
     /// Get package from remote server.
+    ///
+    /// The progress of downloading should be reported to `progress`.
     fn get_package(
         &self,
         repository: &LocalCachedRepository,
         package: &PackageManifest,
+        progress: &impl InstallProgress,
     ) -> impl Future<Output = io::Result<Self::FileStream>> + Send;
+
+    /// Returns true if the remote packages should be extracted into the package store
+    /// in the environment folder and hard linked into the project instead of extracted directly.
+    fn use_package_store(&self) -> bool {
+        false
+    }
 }
 
+/// The observer of the progress of installing packages.
+///
+/// Packages are installed concurrently so events for multiple packages may be interleaved.
+/// All methods do nothing by default.
+pub trait InstallProgress: Sync {
+    /// Called when downloading the package is started.
+    ///
+    /// `total` is the size of the zip file if the server reported it with `Content-Length`.
+    fn download_started(&self, _package: &PackageManifest, _total: Option<u64>) {}
+
+    /// Called when some bytes of the package are downloaded.
+    ///
+    /// `downloaded` is the total number of bytes downloaded so far.
+    fn download_progress(&self, _package: &PackageManifest, _downloaded: u64) {}
+
+    /// Called when extracting the package is started.
+    ///
+    /// `total` is the number of entries in the zip file.
+    fn extract_started(&self, _package: &PackageManifest, _total: usize) {}
+
+    /// Called when an entry of the package is extracted.
+    ///
+    /// `extracted` is the number of entries extracted so far.
+    fn extract_progress(&self, _package: &PackageManifest, _extracted: usize) {}
+
+    /// Called when the package is installed to the project.
+    fn package_installed(&self, _package: &PackageManifest) {}
+}
+
+impl InstallProgress for () {}
+
 /// The HTTP Client.
 pub trait HttpClient: Sync {
     /// Get resource from the URL with specified headers
@@ -76,6 +117,47 @@ pub trait HttpClient: Sync {
         headers: &IndexMap<Box<str>, Box<str>>,
         current_etag: Option<&str>,
     ) -> impl Future<Output = io::Result<Option<(impl AsyncRead + Send, Option<Box<str>>)>>> + Send;
+
+    /// Get resource from the URL with specified headers, resuming the previous download if possible
+    ///
+    /// `resume` is the size of the partially downloaded data and the validator returned
+    /// with the previous response. The client should request the rest of the resource
+    /// if the resource is not changed since the previous download, like with `Range` and `If-Range`.
+    /// Returning the whole resource with [`ResumableResponse::offset`] zero is always allowed.
+    ///
+    /// The default implementation never resumes and gets the whole resource with [`HttpClient::get`]
+    /// without the size of the resource.
+    ///
+    /// Note: If remote server returns error status code, this function should return error.
+    fn get_resumable(
+        &self,
+        url: &Url,
+        headers: &IndexMap<&str, &str>,
+        resume: Option<(u64, &str)>,
+    ) -> impl Future<Output = io::Result<ResumableResponse<impl AsyncRead + Send>>> + Send {
+        let _ = resume;
+        async move {
+            let stream = self.get(url, headers).await?;
+            Ok(ResumableResponse {
+                stream,
+                offset: 0,
+                total: None,
+                validator: None,
+            })
+        }
+    }
+}
+
+/// The response of [`HttpClient::get_resumable`].
+pub struct ResumableResponse<R> {
+    /// The stream of the resource from `offset`.
+    pub stream: R,
+    /// The position in the resource the stream starts from. Zero if the download is not resumed.
+    pub offset: u64,
+    /// The size of the whole resource if known.
+    pub total: Option<u64>,
+    /// The validator like `ETag` to resume the download later, if the server supports it.
+    pub validator: Option<Box<str>>,
 }
 
 impl HttpClient for reqwest::Client {
@@ -133,6 +215,72 @@ impl HttpClient for reqwest::Client {
 
         Ok(Some((response_stream, etag)))
     }
+
+    async fn get_resumable(
+        &self,
+        url: &Url,
+        headers: &IndexMap<&str, &str>,
+        resume: Option<(u64, &str)>,
+    ) -> io::Result<ResumableResponse<impl AsyncRead>> {
+        let request = |resume: Option<(u64, &str)>| {
+            let mut request = self.get(url.to_owned());
+            for (&name, &header) in headers {
+                request = request.header(name, header);
+            }
+            if let Some((offset, validator)) = resume {
+                request = request
+                    .header("Range", format!("bytes={offset}-"))
+                    .header("If-Range", validator.to_owned());
+            }
+            request.send()
+        };
+
+        let mut response = request(resume).await.err_mapped()?;
+        if resume.is_some() && response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+            // the partial data is larger than the resource: download from the start
+            response = request(None).await.err_mapped()?;
+        }
+        let response = response.error_for_status().err_mapped()?;
+
+        let offset = match resume {
+            Some((offset, _)) if response.status() == StatusCode::PARTIAL_CONTENT => {
+                let range_start = (response.headers().get("Content-Range"))
+                    .and_then(|x| x.to_str().ok())
+                    .and_then(|x| x.strip_prefix("bytes "))
+                    .and_then(|x| x.split_once('-'))
+                    .and_then(|(start, _)| start.parse::<u64>().ok());
+                if range_start != Some(offset) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "unexpected Content-Range of the partial response",
+                    ));
+                }
+                offset
+            }
+            _ => 0,
+        };
+
+        let total = response.content_length().map(|x| x + offset);
+
+        // If-Range requires strong ETag or Last-Modified
+        let validator = (response.headers().get("ETag"))
+            .filter(|x| !x.as_bytes().starts_with(b"W/"))
+            .or_else(|| response.headers().get("Last-Modified"))
+            .and_then(|x| x.to_str().ok())
+            .map(Into::into);
+
+        let stream = response
+            .bytes_stream()
+            .map(|x| x.err_mapped())
+            .into_async_read();
+
+        Ok(ResumableResponse {
+            stream,
+            offset,
+            total,
+            validator,
+        })
+    }
 }
 
 impl HttpClient for Infallible {
@@ -1,21 +1,31 @@
 mod add_package;
+mod changes_journal;
 mod find_legacy_assets;
+mod link_package;
+mod local_modifications;
 mod migrate_unity_2022;
 mod migrate_vpm;
+mod overrides;
 mod package_resolution;
 pub mod pending_project_changes;
+mod project_changes_plan;
+mod project_settings;
 mod project_type;
 mod remove_package;
 mod resolve;
+mod reverse_dependencies;
 mod upm_manifest;
+mod verify_packages;
 mod vpm_manifest;
 
+use crate::unity_project::changes_journal::ChangesJournal;
+use crate::unity_project::project_settings::ProjectSettings;
 use crate::unity_project::upm_manifest::UpmManifest;
 use crate::unity_project::vpm_manifest::VpmManifest;
 use crate::utils::{try_load_json, PathBufExt};
-use crate::version::{UnityVersion, Version, VersionRange};
+use crate::version::{DependencyRange, UnityVersion, Version, VersionRange};
 use crate::{io, PackageManifest};
-use futures::future::try_join;
+use futures::future::try_join3;
 use futures::prelude::*;
 use indexmap::IndexMap;
 use lazy_static::lazy_static;
@@ -29,10 +39,17 @@ use crate::io::{DirEntry, FileSystemProjectIo, ProjectIo};
 use crate::package_manifest::LooseManifest;
 pub use add_package::AddPackageErr;
 pub use add_package::AddPackageOperation;
+pub use changes_journal::RecoverChangesResult;
+pub use link_package::LinkPackageErr;
+pub use local_modifications::{LocalModificationsErr, LocallyModifiedPackage};
 pub use migrate_unity_2022::MigrateUnity2022Error;
 pub use migrate_vpm::MigrateVpmError;
+pub use overrides::ActiveOverride;
 pub use pending_project_changes::PendingProjectChanges;
+pub use project_changes_plan::{LoadPlanErr, ProjectChangesPlan};
 pub use resolve::ResolvePackageErr;
+pub use reverse_dependencies::{DependencyPath, DependencyPathReason, DependencyPathStep};
+pub use verify_packages::{PackageIssue, PackageVerification, VerifyReport};
 
 #[derive(Debug)]
 pub struct UnityProject<IO: ProjectIo> {
@@ -41,6 +58,8 @@ pub struct UnityProject<IO: ProjectIo> {
     manifest: VpmManifest,
     // manifest.json
     upm_manifest: UpmManifest,
+    /// vrc-get-settings.json
+    settings: ProjectSettings,
     /// unity version parsed
     unity_version: Option<UnityVersion>,
     /// unity revision parsed
@@ -49,6 +68,10 @@ pub struct UnityProject<IO: ProjectIo> {
     unlocked_packages: Vec<(Box<str>, Option<PackageManifest>)>,
     /// packages installed in the directory and licked in vpm-manifest.json
     installed_packages: HashMap<Box<str>, PackageManifest>,
+    /// the journal of the interrupted apply_pending_changes
+    interrupted_changes: Option<ChangesJournal>,
+    /// true if the journal is written by this instance and should be removed on save
+    committed_journal: bool,
 }
 
 // basic lifecycle
@@ -56,7 +79,46 @@ impl<IO: ProjectIo> UnityProject<IO> {
     pub async fn load(io: IO) -> io::Result<Self> {
         let manifest = VpmManifest::load(&io).await?;
         let upm_manifest = UpmManifest::load(&io).await?;
+        let settings = ProjectSettings::load(&io).await?;
 
+        let (installed_packages, unlocked_packages) =
+            Self::read_packages(&io, &manifest, &settings).await?;
+
+        let (unity_version, unity_revision) = Self::try_read_unity_version(&io).await;
+
+        let interrupted_changes = match ChangesJournal::load(&io).await {
+            Ok(journal) => journal,
+            Err(e) => {
+                log::error!("reading the journal of interrupted changes failed: {e}");
+                None
+            }
+        };
+
+        Ok(Self {
+            io,
+            manifest,
+            upm_manifest,
+            settings,
+            unity_version,
+            unity_revision,
+            unlocked_packages,
+            installed_packages,
+            interrupted_changes,
+            committed_journal: false,
+        })
+    }
+}
+
+impl<IO: ProjectIo> UnityProject<IO> {
+    #[allow(clippy::type_complexity)]
+    async fn read_packages(
+        io: &IO,
+        manifest: &VpmManifest,
+        settings: &ProjectSettings,
+    ) -> io::Result<(
+        HashMap<Box<str>, PackageManifest>,
+        Vec<(Box<str>, Option<PackageManifest>)>,
+    )> {
         let mut installed_packages = HashMap::new();
         let mut unlocked_packages = vec![];
 
@@ -72,7 +134,7 @@ impl<IO: ProjectIo> UnityProject<IO> {
                     if !dir_entry.file_type().await?.is_dir() {
                         continue;
                     }
-                    let read = Self::try_read_unlocked_package(&io, dir_entry).await;
+                    let read = Self::try_read_unlocked_package(io, dir_entry).await;
                     let mut is_installed = false;
                     if let Some(parsed) = &read.1 {
                         if parsed.name() == read.0.as_ref()
@@ -90,21 +152,31 @@ impl<IO: ProjectIo> UnityProject<IO> {
             }
         }
 
-        let (unity_version, unity_revision) = Self::try_read_unity_version(&io).await;
+        // linked packages are not in the Packages folder but installed without being locked
+        for (name, linked) in settings.linked_packages() {
+            let package_json_path = PathBuf::from("Packages")
+                .joined(&linked.path)
+                .joined("package.json");
+            let parsed = match try_load_json::<LooseManifest>(io, &package_json_path).await {
+                Ok(Some(parsed)) => Some(parsed.0),
+                Ok(None) => {
+                    log::warn!(
+                        "package.json of linked package {name} not found at {}",
+                        linked.path.display()
+                    );
+                    None
+                }
+                Err(e) => {
+                    log::warn!("reading package.json of linked package {name}: {e}");
+                    None
+                }
+            };
+            unlocked_packages.push((name.into(), parsed));
+        }
 
-        Ok(Self {
-            io,
-            manifest,
-            upm_manifest,
-            unity_version,
-            unity_revision,
-            unlocked_packages,
-            installed_packages,
-        })
+        Ok((installed_packages, unlocked_packages))
     }
-}
 
-impl<IO: ProjectIo> UnityProject<IO> {
     async fn try_read_unlocked_package(
         io: &IO,
         dir_entry: IO::DirEntry,
@@ -194,11 +266,16 @@ impl<IO: ProjectIo> UnityProject<IO> {
     }
 
     pub async fn save(&mut self) -> io::Result<()> {
-        try_join(
+        try_join3(
             self.manifest.save(&self.io),
             self.upm_manifest.save(&self.io),
+            self.settings.save(&self.io),
         )
         .await?;
+        if self.committed_journal {
+            ChangesJournal::remove(&self.io).await?;
+            self.committed_journal = false;
+        }
         Ok(())
     }
 }
@@ -213,6 +290,10 @@ impl<IO: ProjectIo> UnityProject<IO> {
         self.manifest.dependencies().map(|(name, _)| name)
     }
 
+    pub fn get_dependency(&self, name: &str) -> Option<&DependencyRange> {
+        self.manifest.get_dependency(name)
+    }
+
     pub fn get_locked(&self, name: &str) -> Option<LockedDependencyInfo> {
         self.manifest.get_locked(name)
     }
@@ -221,6 +302,15 @@ impl<IO: ProjectIo> UnityProject<IO> {
         self.manifest.get_locked(name).is_some()
     }
 
+    /// Returns the packages held at the locked version.
+    pub fn held_packages(&self) -> impl Iterator<Item = &str> {
+        self.settings.held_packages()
+    }
+
+    pub fn is_held(&self, name: &str) -> bool {
+        self.settings.is_held(name)
+    }
+
     pub fn all_packages(&self) -> impl Iterator<Item = LockedDependencyInfo> {
         let dependencies_locked = self.manifest.all_locked();
 
@@ -268,6 +358,29 @@ impl<IO: ProjectIo> UnityProject<IO> {
     }
 }
 
+// holding packages
+impl<IO: ProjectIo> UnityProject<IO> {
+    /// Holds the package at the currently locked version.
+    ///
+    /// Held packages are never upgraded or downgraded by [`add_package_request`];
+    /// if moving a held package is necessary, it's reported as a conflict instead.
+    /// This returns false if the package is not locked.
+    ///
+    /// [`add_package_request`]: UnityProject::add_package_request
+    pub fn hold_package(&mut self, name: &str) -> bool {
+        if !self.is_locked(name) {
+            return false;
+        }
+        self.settings.hold(name);
+        true
+    }
+
+    /// Releases the hold of the package. This returns false if the package is not held.
+    pub fn unhold_package(&mut self, name: &str) -> bool {
+        self.settings.unhold(name)
+    }
+}
+
 impl<IO: FileSystemProjectIo + ProjectIo> UnityProject<IO> {
     pub fn project_dir(&self) -> &Path {
         self.io.location()
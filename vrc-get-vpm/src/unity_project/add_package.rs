@@ -1,5 +1,5 @@
 use crate::io::ProjectIo;
-use crate::unity_project::pending_project_changes::RemoveReason;
+use crate::unity_project::pending_project_changes::{RemoveReason, ResolutionExplanation};
 use crate::unity_project::vpm_manifest::VpmManifest;
 use crate::unity_project::{package_resolution, PendingProjectChanges};
 use crate::version::DependencyRange;
@@ -10,16 +10,27 @@ use std::fmt;
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum AddPackageErr {
-    DependencyNotFound { dependency_name: Box<str> },
-    UpgradingNonLockedPackage { package_name: Box<str> },
-    DowngradingNonLockedPackage { package_name: Box<str> },
-    UpgradingWithDowngrade { package_name: Box<str> },
+    DependencyNotFound {
+        dependency_name: Box<str>,
+        explanation: Box<ResolutionExplanation>,
+    },
+    UpgradingNonLockedPackage {
+        package_name: Box<str>,
+    },
+    DowngradingNonLockedPackage {
+        package_name: Box<str>,
+    },
+    UpgradingWithDowngrade {
+        package_name: Box<str>,
+    },
 }
 
 impl fmt::Display for AddPackageErr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            AddPackageErr::DependencyNotFound { dependency_name } => write!(
+            AddPackageErr::DependencyNotFound {
+                dependency_name, ..
+            } => write!(
                 f,
                 "Package {dependency_name} (maybe dependencies of the package) not found"
             ),
@@ -68,6 +79,12 @@ impl<IO: ProjectIo> UnityProject<IO> {
         let mut changes = super::pending_project_changes::Builder::new();
 
         for &request in packages {
+            if self.is_moving_held_package(request) {
+                debug!("Package {} is held so it cannot be changed", request.name());
+                changes.conflicts_hold(request.name().into());
+                continue;
+            }
+
             match operation {
                 AddPackageOperation::InstallToDependencies => {
                     let add_to_dependencies = self
@@ -184,10 +201,12 @@ impl<IO: ProjectIo> UnityProject<IO> {
             }),
             self.manifest.all_locked(),
             &self.unlocked_packages,
+            self.settings.held_packages(),
             |pkg| self.manifest.get_locked(pkg),
             self.unity_version(),
             env,
             adding_packages,
+            self.settings.overrides(),
             allow_prerelease,
         )?;
 
@@ -196,9 +215,17 @@ impl<IO: ProjectIo> UnityProject<IO> {
         }
 
         for (package, conflicts_with) in result.conflicts {
+            if self.is_held(&package) {
+                debug!("Package {package} is held so it cannot be changed");
+                changes.conflicts_hold(package.clone());
+            }
             changes.conflict_multiple(package, conflicts_with);
         }
 
+        for (package, explanation) in result.conflict_explanations {
+            changes.explain_conflict(package, explanation);
+        }
+
         for name in result
             .found_legacy_packages
             .into_iter()
@@ -209,4 +236,11 @@ impl<IO: ProjectIo> UnityProject<IO> {
 
         Ok(changes.build_resolve(self).await)
     }
+
+    fn is_moving_held_package(&self, package: PackageInfo) -> bool {
+        self.is_held(package.name())
+            && (self.manifest.get_locked(package.name()))
+                .map(|locked| locked.version() != package.version())
+                .unwrap_or(false)
+    }
 }
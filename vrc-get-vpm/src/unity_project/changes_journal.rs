@@ -0,0 +1,239 @@
+//! The journal of [`UnityProject::apply_pending_changes`].
+//!
+//! Applying changes is not atomic: packages are moved to the temporary folder, new packages are
+//! extracted, and then `vpm-manifest.json` is saved by the caller.
+//! To recover the project if the process is killed in the middle of them, we write the journal
+//! describing the changes before modifying anything, and remove it after the manifest is saved.
+
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+
+use futures::prelude::*;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::io;
+use crate::io::{DirEntry, ProjectIo};
+use crate::unity_project::pending_project_changes::{
+    remove_assets, PKG_TEMP_DIR, REMOVED_FILE_PREFIX, TEMP_DIR,
+};
+use crate::utils::{to_vec_pretty_os_eol, try_load_json, walk_dir_relative};
+use crate::version::{DependencyRange, Version, VersionRange};
+use crate::UnityProject;
+
+/// The journal is in the Library folder, which is not scanned by the package manager of Unity
+/// nor committed to version control, and not removed when Unity exits unlike the Temp folder.
+pub(super) const JOURNAL_PATH: &str = "Library/vrc-get/changes-journal.json";
+const JOURNAL_TEMP_PATH: &str = "Library/vrc-get/changes-journal.json.tmp";
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct ChangesJournal {
+    pub(super) state: JournalState,
+    /// The folder the removed packages are moved to
+    pub(super) temp_dir: Box<Path>,
+    #[serde(default)]
+    pub(super) installing: Vec<Box<str>>,
+    #[serde(default)]
+    pub(super) removing: Vec<Box<str>>,
+    #[serde(default)]
+    pub(super) add_locked: Vec<JournalLocked>,
+    #[serde(default)]
+    pub(super) add_dependencies: Vec<JournalDependency>,
+    #[serde(default)]
+    pub(super) remove_legacy_files: Vec<Box<Path>>,
+    #[serde(default)]
+    pub(super) remove_legacy_folders: Vec<Box<Path>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(super) enum JournalState {
+    /// Moving the packages to be removed or updated to the temporary folder
+    Moving,
+    /// Extracting the new packages to the Packages folder
+    Installing,
+    /// All packages are installed. Only the manifest and cleanups are remaining
+    Installed,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct JournalLocked {
+    pub(super) name: Box<str>,
+    pub(super) version: Version,
+    #[serde(default)]
+    pub(super) dependencies: IndexMap<Box<str>, VersionRange>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct JournalDependency {
+    pub(super) name: Box<str>,
+    pub(super) version: DependencyRange,
+}
+
+impl ChangesJournal {
+    pub(super) async fn load(io: &impl ProjectIo) -> io::Result<Option<Self>> {
+        match try_load_json(io, JOURNAL_PATH.as_ref()).await? {
+            Some(journal) => Ok(Some(journal)),
+            // interrupted after removing the journal and before renaming the new one
+            None => try_load_json(io, JOURNAL_TEMP_PATH.as_ref()).await,
+        }
+    }
+
+    pub(super) async fn write(
+        &mut self,
+        io: &impl ProjectIo,
+        state: JournalState,
+    ) -> io::Result<()> {
+        self.state = state;
+
+        // write to another file first so that either the journal or the new one is complete.
+        // rename may not replace the existing file so the journal is removed before renaming
+        io.create_dir_all(Path::new(JOURNAL_PATH).parent().unwrap())
+            .await?;
+        io.write(JOURNAL_TEMP_PATH.as_ref(), &to_vec_pretty_os_eol(self)?)
+            .await?;
+        remove_if_exists(io, JOURNAL_PATH.as_ref()).await?;
+        io.rename(JOURNAL_TEMP_PATH.as_ref(), JOURNAL_PATH.as_ref())
+            .await
+    }
+
+    pub(super) async fn remove(io: &impl ProjectIo) -> io::Result<()> {
+        remove_if_exists(io, JOURNAL_PATH.as_ref()).await?;
+        remove_if_exists(io, JOURNAL_TEMP_PATH.as_ref()).await
+    }
+}
+
+async fn remove_if_exists(io: &impl ProjectIo, path: &Path) -> io::Result<()> {
+    match io.remove_file(path).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// The result of [`UnityProject::recover_interrupted_changes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RecoverChangesResult {
+    /// There were no interrupted changes
+    NothingToRecover,
+    /// The packages were interrupted before installed so restored to the state before the changes
+    RolledBack,
+    /// All packages were installed before interrupted so the remaining changes are applied
+    RolledForward,
+}
+
+impl<IO: ProjectIo> UnityProject<IO> {
+    /// Returns true if the previous [`apply_pending_changes`] was interrupted and the project
+    /// should be recovered with [`recover_interrupted_changes`].
+    ///
+    /// [`apply_pending_changes`]: UnityProject::apply_pending_changes
+    /// [`recover_interrupted_changes`]: UnityProject::recover_interrupted_changes
+    pub fn has_interrupted_changes(&self) -> bool {
+        self.interrupted_changes.is_some()
+    }
+
+    /// Recovers the project from interrupted [`apply_pending_changes`].
+    ///
+    /// If the packages were not fully installed, this restores the previous packages.
+    /// Otherwise, this applies the remaining changes including saving `vpm-manifest.json`.
+    ///
+    /// [`apply_pending_changes`]: UnityProject::apply_pending_changes
+    pub async fn recover_interrupted_changes(&mut self) -> io::Result<RecoverChangesResult> {
+        let Some(journal) = self.interrupted_changes.take() else {
+            return Ok(RecoverChangesResult::NothingToRecover);
+        };
+
+        let result = match journal.state {
+            JournalState::Moving | JournalState::Installing => {
+                if journal.state == JournalState::Installing {
+                    // remove partially installed packages
+                    for name in &journal.installing {
+                        let package_dir = PathBuf::from(format!("Packages/{name}"));
+                        match self.io.remove_dir_all(&package_dir).await {
+                            Ok(()) => {}
+                            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                            Err(e) => return Err(e),
+                        }
+                    }
+                }
+
+                restore_moved_packages(&self.io, &journal.temp_dir).await?;
+                cleanup_temp_dir(&self.io, &journal.temp_dir).await;
+                ChangesJournal::remove(&self.io).await?;
+
+                RecoverChangesResult::RolledBack
+            }
+            JournalState::Installed => {
+                cleanup_temp_dir(&self.io, &journal.temp_dir).await;
+                remove_assets(
+                    &self.io,
+                    journal.remove_legacy_files.iter().map(Box::as_ref),
+                    journal.remove_legacy_folders.iter().map(Box::as_ref),
+                )
+                .await;
+
+                for locked in journal.add_locked {
+                    self.manifest
+                        .add_locked(&locked.name, locked.version, locked.dependencies);
+                }
+                for dependency in journal.add_dependencies {
+                    self.manifest
+                        .add_dependency(&dependency.name, dependency.version);
+                }
+                self.unlink_packages(journal.removing.iter().map(Box::as_ref));
+                self.manifest
+                    .remove_packages(journal.removing.iter().map(Box::as_ref));
+                self.settings
+                    .remove_packages(journal.removing.iter().map(Box::as_ref));
+
+                // saving the project removes the journal
+                self.committed_journal = true;
+                self.save().await?;
+
+                RecoverChangesResult::RolledForward
+            }
+        };
+
+        // reload packages to reflect the recovered packages
+        (self.installed_packages, self.unlocked_packages) =
+            Self::read_packages(&self.io, &self.manifest, &self.settings).await?;
+
+        Ok(result)
+    }
+}
+
+/// Moves files in the temporary folder back to the Packages folder.
+///
+/// This can restore partially moved packages since this moves files one by one.
+async fn restore_moved_packages(io: &impl ProjectIo, temp_dir: &Path) -> io::Result<()> {
+    let mut iterator = pin!(walk_dir_relative(io, vec![temp_dir.into()]));
+    while let Some((moved, entry)) = iterator.next().await {
+        if entry.file_type().await?.is_dir() {
+            continue;
+        }
+
+        let relative = moved.strip_prefix(temp_dir).unwrap();
+        let mut original = Path::new("Packages").join(relative);
+        if let Some(name) = original.file_name().unwrap().to_str() {
+            if let Some(stripped) = name.strip_prefix(REMOVED_FILE_PREFIX) {
+                original = original.parent().unwrap().join(stripped);
+            }
+        }
+
+        io.create_dir_all(original.parent().unwrap()).await?;
+        io.rename(&moved, &original).await?;
+    }
+
+    Ok(())
+}
+
+async fn cleanup_temp_dir(io: &impl ProjectIo, temp_dir: &Path) {
+    io.remove_dir_all(temp_dir).await.ok();
+    io.remove_dir(PKG_TEMP_DIR.as_ref()).await.ok();
+    // remove temp dir also if it's empty
+    io.remove_dir(TEMP_DIR.as_ref()).await.ok();
+}
@@ -0,0 +1,140 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::io::{FileSystemProjectIo, ProjectIo};
+use crate::unity_project::project_settings::LinkedPackage;
+use crate::{PackageInfo, UnityProject};
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LinkPackageErr {
+    NotLocalPackage { package_name: Box<str> },
+    AlreadyInstalled { package_name: Box<str> },
+    NotRelativeToProject { package_name: Box<str> },
+}
+
+impl fmt::Display for LinkPackageErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkPackageErr::NotLocalPackage { package_name } => {
+                write!(f, "Package {package_name} is not a local user package")
+            }
+            LinkPackageErr::AlreadyInstalled { package_name } => write!(
+                f,
+                "Package {package_name} is already installed to the Packages folder"
+            ),
+            LinkPackageErr::NotRelativeToProject { package_name } => write!(
+                f,
+                "Package {package_name} cannot be referenced with a relative path from the project"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LinkPackageErr {}
+
+// linking packages
+impl<IO: ProjectIo> UnityProject<IO> {
+    /// Returns the linked packages and the paths to them relative to the `Packages` folder.
+    pub fn linked_packages(&self) -> impl Iterator<Item = (&str, &Path)> {
+        (self.settings.linked_packages()).map(|(name, linked)| (name, linked.path.as_ref()))
+    }
+
+    pub fn is_linked(&self, name: &str) -> bool {
+        self.settings.get_linked(name).is_some()
+    }
+
+    /// Removes `file:` references of the linked packages from `Packages/manifest.json`.
+    ///
+    /// The record in `vrc-get-settings.json` is removed with other packages.
+    pub(super) fn unlink_packages<'a>(&mut self, names: impl Iterator<Item = &'a str>) {
+        for name in names {
+            if self.is_linked(name) {
+                self.upm_manifest.remove_dependency(name);
+            }
+        }
+    }
+}
+
+impl<IO: FileSystemProjectIo + ProjectIo> UnityProject<IO> {
+    /// Links the local user package to the project instead of copying it.
+    ///
+    /// This adds `file:` reference relative to the `Packages` folder to `Packages/manifest.json`
+    /// so Unity loads the package from the original folder.
+    /// The package cannot be linked if it's on another drive than the project.
+    /// The dependencies of the linked package are read from the folder when the project is loaded
+    /// and treated like unlocked packages, so use [`resolve_request`] to install them.
+    ///
+    /// Use [`remove_request`] to unlink the package.
+    ///
+    /// [`resolve_request`]: UnityProject::resolve_request
+    /// [`remove_request`]: UnityProject::remove_request
+    pub fn link_package(&mut self, package: PackageInfo) -> Result<(), LinkPackageErr> {
+        let Some(path) = package.local_path() else {
+            return Err(LinkPackageErr::NotLocalPackage {
+                package_name: package.name().into(),
+            });
+        };
+
+        let linked = self.is_linked(package.name());
+        let in_packages_folder = self.unlocked_packages.iter().any(|(name, json)| {
+            name.as_ref() == package.name()
+                || json.as_ref().map(|x| x.name()) == Some(package.name())
+        });
+        if self.is_locked(package.name()) || (!linked && in_packages_folder) {
+            return Err(LinkPackageErr::AlreadyInstalled {
+                package_name: package.name().into(),
+            });
+        }
+
+        // the package is read through the project io, which only accepts relative paths
+        let packages_folder = self.project_dir().join("Packages");
+        let Some(relative) = relative_path(&packages_folder, path) else {
+            return Err(LinkPackageErr::NotRelativeToProject {
+                package_name: package.name().into(),
+            });
+        };
+        // UPM accepts forward slashes on every platform
+        let reference = relative.to_string_lossy().replace('\\', "/");
+        self.upm_manifest
+            .add_file_dependency(package.name(), &reference);
+        self.settings.add_linked(
+            package.name(),
+            LinkedPackage {
+                path: Path::new(&reference).into(),
+            },
+        );
+
+        let manifest = package.package_json().clone();
+        match (self.unlocked_packages.iter_mut()).find(|(name, _)| name.as_ref() == package.name())
+        {
+            Some((_, json)) => *json = Some(manifest),
+            None => (self.unlocked_packages).push((package.name().into(), Some(manifest))),
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns `path` relative to `base`, or `None` if it cannot be relative like on another drive.
+fn relative_path(base: &Path, path: &Path) -> Option<PathBuf> {
+    // the project may be opened with a path relative to the current directory
+    let (Ok(base), Ok(path)) = (std::path::absolute(base), std::path::absolute(path)) else {
+        return None;
+    };
+
+    let base = base.components().collect::<Vec<_>>();
+    let path = path.components().collect::<Vec<_>>();
+    let common = (base.iter().zip(&path)).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        // on another drive
+        return None;
+    }
+
+    let mut relative = PathBuf::new();
+    for _ in common..base.len() {
+        relative.push("..");
+    }
+    relative.extend(&path[common..]);
+    Some(relative)
+}
@@ -0,0 +1,137 @@
+//! Detecting local modifications of installed packages.
+//!
+//! Updating or removing packages replaces the whole `Packages/<name>` folder, so changes made
+//! directly in the folder would be lost. We compare the installed files with the zip file of
+//! the package cached in the environment to find such changes before applying changes.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::environment::package_cache_path;
+use crate::io;
+use crate::io::{EnvironmentIo, ProjectIo};
+use crate::traits::EnvironmentIoHolder;
+use crate::unity_project::pending_project_changes::PackageChange;
+use crate::unity_project::PendingProjectChanges;
+use crate::utils::{compare_with_zip, FilesDiff};
+use crate::version::Version;
+use crate::UnityProject;
+
+/// The installed package with files modified after installation.
+#[derive(Debug, Clone)]
+pub struct LocallyModifiedPackage {
+    name: Box<str>,
+    modified_files: Vec<Box<Path>>,
+}
+
+impl LocallyModifiedPackage {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The files modified or added after installation, relative to the project.
+    pub fn modified_files(&self) -> &[Box<Path>] {
+        &self.modified_files
+    }
+}
+
+/// The error returned by [`UnityProject::apply_pending_changes`] wrapped in [`io::Error`]
+/// if the changes would overwrite the local modifications.
+#[derive(Debug)]
+pub struct LocalModificationsErr {
+    packages: Vec<LocallyModifiedPackage>,
+}
+
+impl LocalModificationsErr {
+    pub(super) fn new(packages: Vec<LocallyModifiedPackage>) -> Self {
+        Self { packages }
+    }
+
+    pub fn packages(&self) -> &[LocallyModifiedPackage] {
+        &self.packages
+    }
+}
+
+impl fmt::Display for LocalModificationsErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the following files of packages to be overwritten are modified locally:")?;
+        for package in &self.packages {
+            for file in &package.modified_files {
+                write!(f, "\n- {}", file.display())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LocalModificationsErr {}
+
+impl<IO: ProjectIo> UnityProject<IO> {
+    /// Finds installed packages with local modifications which would be lost by the changes.
+    ///
+    /// The packages to be updated or removed are compared with the zip files cached in the
+    /// environment. Packages without cached zip files are not checked.
+    pub async fn find_local_modifications<Env: EnvironmentIoHolder>(
+        &self,
+        env: &Env,
+        changes: &PendingProjectChanges<'_>,
+    ) -> io::Result<Vec<LocallyModifiedPackage>> {
+        let mut modified_packages = vec![];
+
+        for (name, change) in &changes.package_changes {
+            let overwritten = match change {
+                PackageChange::Install(install) => install.package.is_some(),
+                PackageChange::Remove(_) => true,
+            };
+            if !overwritten {
+                continue;
+            }
+            let Some(installed) = self.installed_packages.get(name) else {
+                continue;
+            };
+
+            let diff =
+                compare_with_cached_zip(&self.io, env.io(), name, installed.version()).await?;
+            if let Some(diff) = diff {
+                // removed files are not lost by overwriting
+                let mut modified_files = diff.modified;
+                modified_files.extend(diff.added);
+                modified_files.sort();
+
+                if !modified_files.is_empty() {
+                    modified_packages.push(LocallyModifiedPackage {
+                        name: name.clone(),
+                        modified_files,
+                    });
+                }
+            }
+        }
+
+        modified_packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(modified_packages)
+    }
+}
+
+/// Compares the files in the package folder with the zip file of the package cached in the
+/// environment, or returns `None` if the zip file of the package is not cached.
+pub(super) async fn compare_with_cached_zip(
+    io: &impl ProjectIo,
+    env_io: &impl EnvironmentIo,
+    name: &str,
+    version: &Version,
+) -> io::Result<Option<FilesDiff>> {
+    let zip_file = match env_io.open(&package_cache_path(name, version)).await {
+        Ok(zip_file) => zip_file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            log::debug!("cached zip for {name}@{version} not found");
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    };
+
+    let package_dir = PathBuf::from(format!("Packages/{name}"));
+    let diff = compare_with_zip(io, io::BufReader::new(zip_file), &package_dir).await?;
+
+    Ok(Some(diff))
+}
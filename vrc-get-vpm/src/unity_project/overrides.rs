@@ -0,0 +1,79 @@
+use crate::io::ProjectIo;
+use crate::version::VersionRange;
+use crate::UnityProject;
+
+/// A dependency of some package whose declared range is replaced by the project override.
+#[derive(Debug, Clone, Copy)]
+pub struct ActiveOverride<'a> {
+    dependent: &'a str,
+    dependency: &'a str,
+    declared: &'a VersionRange,
+    overridden: &'a VersionRange,
+}
+
+impl<'a> ActiveOverride<'a> {
+    /// The package declaring the dependency.
+    pub fn dependent(&self) -> &'a str {
+        self.dependent
+    }
+
+    /// The name of the overridden dependency.
+    pub fn dependency(&self) -> &'a str {
+        self.dependency
+    }
+
+    /// The range declared in `vpmDependencies` of the dependent.
+    pub fn declared(&self) -> &'a VersionRange {
+        self.declared
+    }
+
+    /// The range used in place of the declared range.
+    pub fn overridden(&self) -> &'a VersionRange {
+        self.overridden
+    }
+}
+
+impl<IO: ProjectIo> UnityProject<IO> {
+    /// Returns the version ranges overriding the ranges declared by dependents.
+    ///
+    /// The overrides are used for transitive dependencies by the resolver in place of
+    /// the ranges declared in `vpmDependencies` of the packages.
+    pub fn overrides(&self) -> impl Iterator<Item = (&str, &VersionRange)> {
+        (self.settings.overrides().iter()).map(|(name, range)| (name.as_ref(), range))
+    }
+
+    pub fn get_override(&self, name: &str) -> Option<&VersionRange> {
+        self.settings.overrides().get(name)
+    }
+
+    /// Overrides the version range of the dependency declared by every dependent.
+    pub fn set_override(&mut self, name: &str, range: VersionRange) {
+        self.settings.set_override(name, range);
+    }
+
+    /// Removes the override. This returns false if the package is not overridden.
+    pub fn remove_override(&mut self, name: &str) -> bool {
+        self.settings.remove_override(name)
+    }
+
+    /// Returns every dependency of the installed packages whose range is overridden.
+    pub fn active_overrides(&self) -> Vec<ActiveOverride<'_>> {
+        let overrides = self.settings.overrides();
+        let mut active = vec![];
+
+        for package in self.all_packages() {
+            for (dependency, declared) in package.dependencies() {
+                if let Some(overridden) = overrides.get(dependency) {
+                    active.push(ActiveOverride {
+                        dependent: package.name(),
+                        dependency,
+                        declared,
+                        overridden,
+                    });
+                }
+            }
+        }
+
+        active
+    }
+}
@@ -1,9 +1,20 @@
 use crate::traits::PackageCollection;
+use crate::unity_project::pending_project_changes::{
+    DependencyRequirement, RejectReason, RejectedVersion, ResolutionExplanation,
+};
 use crate::unity_project::{AddPackageErr, LockedDependencyInfo};
 use crate::version::{DependencyRange, UnityVersion, Version, VersionRange};
-use crate::{PackageInfo, PackageManifest, VersionSelector};
+use crate::{unity_compatible, PackageInfo, PackageManifest, VersionSelector};
+use indexmap::IndexMap;
 use std::collections::{HashMap, HashSet, VecDeque};
 
+/// The maximum number of alternative versions tried while backtracking.
+///
+/// The search space grows exponentially with the number of packages, so we give up and
+/// fall back to the first (greedy) result once we've tried this many alternatives.
+const MAX_BACKTRACKS: usize = 1024;
+
+#[derive(Clone)]
 struct PackageQueue<'a> {
     pending_queue: VecDeque<PackageInfo<'a>>,
 }
@@ -29,16 +40,26 @@ impl<'a> PackageQueue<'a> {
     }
 }
 
+#[derive(Clone)]
 struct ResolutionContext<'env, 'a>
 where
     'env: 'a,
 {
     allow_prerelease: bool,
     pub pending_queue: PackageQueue<'env>,
+    // dependencies of the last added package which are not checked yet
+    pending_dependencies: VecDeque<(PackageInfo<'env>, &'env str, &'a VersionRange)>,
     dependencies: HashMap<&'a str, DependencyInfo<'env, 'a>>,
+    // dependency -> the range used in place of the ranges declared by dependents
+    overrides: &'a IndexMap<Box<str>, VersionRange>,
     unlocked_names: HashSet<&'a str>,
+    // packages kept at the locked version
+    held_names: HashSet<&'a str>,
+    // dependency -> the package which made the dependency to be added
+    introduced_by: HashMap<&'env str, &'env str>,
 }
 
+#[derive(Clone, Copy)]
 struct Legacy<'env>(&'env [Box<str>]);
 
 impl<'env> Default for Legacy<'env> {
@@ -48,7 +69,7 @@ impl<'env> Default for Legacy<'env> {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 struct DependencyInfo<'env, 'a> {
     using: Option<PackageInfo<'env>>,
     current: Option<&'a Version>,
@@ -116,12 +137,20 @@ where
 }
 
 impl<'env, 'a> ResolutionContext<'env, 'a> {
-    fn new(allow_prerelease: bool, packages: Vec<PackageInfo<'env>>) -> Self {
+    fn new(
+        allow_prerelease: bool,
+        packages: Vec<PackageInfo<'env>>,
+        overrides: &'a IndexMap<Box<str>, VersionRange>,
+    ) -> Self {
         let mut this = Self {
             dependencies: HashMap::new(),
+            overrides,
             pending_queue: PackageQueue::new(packages),
+            pending_dependencies: VecDeque::new(),
             allow_prerelease,
             unlocked_names: HashSet::new(),
+            held_names: HashSet::new(),
+            introduced_by: HashMap::new(),
         };
 
         for pkg in &this.pending_queue.pending_queue {
@@ -156,6 +185,10 @@ where
         self.unlocked_names.insert(name);
     }
 
+    pub(crate) fn add_held_name(&mut self, name: &'a str) {
+        self.held_names.insert(name);
+    }
+
     pub(crate) fn add_unlocked_manifest(&mut self, manifest: &'a PackageManifest) {
         let info = self.dependencies.entry(manifest.name()).or_default();
         info.set_using_info(
@@ -178,6 +211,7 @@ where
         }
 
         for (dependency, range) in manifest.vpm_dependencies() {
+            let range = self.overridden_range(dependency, range);
             self.dependencies
                 .entry(dependency)
                 .or_default()
@@ -213,6 +247,7 @@ where
         }
 
         for (dependency, range) in locked.dependencies() {
+            let range = self.overridden_range(dependency, range);
             self.dependencies
                 .entry(dependency)
                 .or_default()
@@ -253,6 +288,7 @@ where
             self.dependencies.get_mut(*dep).unwrap().remove_range(name);
         }
         for (dependency, range) in vpm_dependencies.iter() {
+            let range = self.overridden_range(dependency, range);
             self.dependencies
                 .entry(dependency)
                 .or_default()
@@ -278,6 +314,11 @@ where
         true
     }
 
+    /// Returns the range overridden by the project if any, or `range` declared by the dependent.
+    fn overridden_range(&self, dependency: &str, range: &'a VersionRange) -> &'a VersionRange {
+        self.overrides.get(dependency).unwrap_or(range)
+    }
+
     pub(crate) fn should_add_package(&self, name: &'a str, range: &'a VersionRange) -> bool {
         let entry = self.dependencies.get(name).unwrap();
 
@@ -295,6 +336,14 @@ where
             return false;
         }
 
+        if self.held_names.contains(name) {
+            // the locked version is kept and conflicts are reported if it doesn't match
+            log::debug!(
+                "processing package {name}: dependency {name} version {range}: held package"
+            );
+            return false;
+        }
+
         let mut install = true;
         let allow_prerelease = entry.allow_pre || self.allow_prerelease;
 
@@ -320,8 +369,246 @@ where
     }
 }
 
+impl<'env, 'a> ResolutionContext<'env, 'a>
+where
+    'env: 'a,
+{
+    /// Processes pending packages until we need to choose a version of some dependency.
+    ///
+    /// Returns the package requiring the dependency, the name of the dependency and
+    /// the candidates in preferred order, or `None` if there are nothing to process anymore.
+    #[allow(clippy::type_complexity)]
+    fn run_until_decision(
+        &mut self,
+        env: &'env impl PackageCollection,
+        unity_version: Option<UnityVersion>,
+    ) -> Result<Option<(PackageInfo<'env>, &'env str, Vec<PackageInfo<'env>>)>, AddPackageErr> {
+        loop {
+            if let Some((source, dependency, range)) = self.pending_dependencies.pop_front() {
+                let name = source.name();
+                log::debug!("processing package {name}: dependency {dependency} version {range}");
+
+                if self.should_add_package(dependency, range) {
+                    let candidates = find_candidates(
+                        env,
+                        source,
+                        dependency,
+                        range,
+                        unity_version,
+                        self.allow_prerelease,
+                    );
+
+                    if candidates.is_empty() {
+                        let mut requirement_chain = self.requirement_chain(name);
+                        requirement_chain.push(dependency.into());
+                        let allow_prerelease = self.allow_prerelease
+                            || source.version().is_pre()
+                            || self.dependencies[dependency].allow_pre;
+
+                        return Err(AddPackageErr::DependencyNotFound {
+                            dependency_name: dependency.into(),
+                            explanation: Box::new(self.explain(
+                                env,
+                                dependency,
+                                None,
+                                requirement_chain,
+                                unity_version,
+                                allow_prerelease,
+                            )),
+                        });
+                    }
+
+                    return Ok(Some((source, dependency, candidates)));
+                }
+            } else if let Some(x) = self.pending_queue.next_package() {
+                log::debug!("processing package {} version {}", x.name(), x.version());
+
+                if self.add_package(x) {
+                    for (dependency, range) in x.vpm_dependencies() {
+                        let range = self.overridden_range(dependency, range);
+                        self.pending_dependencies
+                            .push_back((x, dependency.as_ref(), range));
+                    }
+                }
+            } else {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+/// Finds the versions of `dependency` which can be used for the dependency of `source`.
+///
+/// The first element is the one the resolver have been choosing before backtracking is introduced,
+/// and the rest are the other matching versions in descending order.
+fn find_candidates<'env>(
+    env: &'env impl PackageCollection,
+    source: PackageInfo<'env>,
+    dependency: &str,
+    range: &VersionRange,
+    unity_version: Option<UnityVersion>,
+    allow_prerelease: bool,
+) -> Vec<PackageInfo<'env>> {
+    // selectors in the preferred order.
+    let mut selectors = Vec::with_capacity(3);
+    if allow_prerelease {
+        selectors.push(VersionSelector::range_for(unity_version, range, true));
+        selectors.push(VersionSelector::range_for(None, range, true));
+    } else {
+        selectors.push(VersionSelector::range_for(unity_version, range, false));
+        selectors.push(VersionSelector::range_for(None, range, false));
+        if source.version().is_pre() {
+            selectors.push(VersionSelector::range_for(None, range, true));
+        }
+    }
+
+    let Some(preferred) = selectors
+        .iter()
+        .find_map(|&selector| env.find_package_by_name(dependency, selector))
+    else {
+        return vec![];
+    };
+
+    let mut candidates = vec![preferred];
+
+    for selector in selectors {
+        let mut matching = env
+            .find_packages(dependency)
+            .filter(|x| selector.satisfies(x.package_json()))
+            .filter(|x| candidates.iter().all(|c| c.version() != x.version()))
+            .collect::<Vec<_>>();
+        matching.sort_by(|a, b| b.version().cmp(a.version()));
+        matching.dedup_by(|a, b| a.version() == b.version());
+        candidates.extend(matching);
+    }
+
+    candidates
+}
+
+struct Resolver<'env, Env: PackageCollection> {
+    env: &'env Env,
+    unity_version: Option<UnityVersion>,
+    remaining_backtracks: usize,
+}
+
+enum SearchResult<'env, 'a>
+where
+    'env: 'a,
+{
+    /// Resolved without any conflicts
+    Resolved(ResolutionContext<'env, 'a>),
+    /// Resolved but with some conflicts
+    Conflicted(ResolutionContext<'env, 'a>),
+    Failed(AddPackageErr),
+}
+
+impl<'env, Env: PackageCollection> Resolver<'env, Env> {
+    /// Searches for the resolution without conflicts with depth-first search.
+    ///
+    /// If all candidates are failed, this returns the result of the first candidate
+    /// at each decision, which is same as the result of the greedy resolution.
+    fn search<'a>(&mut self, mut context: ResolutionContext<'env, 'a>) -> SearchResult<'env, 'a>
+    where
+        'env: 'a,
+    {
+        let (source, dependency, candidates) =
+            match context.run_until_decision(self.env, self.unity_version) {
+                Err(err) => return SearchResult::Failed(err),
+                Ok(None) => {
+                    return if context.collect_conflicts().is_empty() {
+                        SearchResult::Resolved(context)
+                    } else {
+                        SearchResult::Conflicted(context)
+                    };
+                }
+                Ok(Some(decision)) => decision,
+            };
+
+        let mut first_result = None;
+
+        for (index, candidate) in candidates.into_iter().enumerate() {
+            if index != 0 {
+                if self.remaining_backtracks == 0 {
+                    log::debug!("backtracking limit reached while resolving {dependency}");
+                    break;
+                }
+                self.remaining_backtracks -= 1;
+                log::debug!(
+                    "backtracking: trying {dependency} version {}",
+                    candidate.version()
+                );
+            }
+
+            let mut branch = context.clone();
+            // remove existing if existing
+            branch.pending_queue.add_pending_package(candidate);
+            branch.introduced_by.insert(dependency, source.name());
+
+            match self.search(branch) {
+                resolved @ SearchResult::Resolved(_) => return resolved,
+                other => {
+                    if first_result.is_none() {
+                        first_result = Some(other);
+                    }
+                }
+            }
+        }
+
+        first_result.unwrap()
+    }
+}
+
 impl<'env, 'a> ResolutionContext<'env, 'a> {
-    pub(crate) fn build_result(self) -> PackageResolutionResult<'env> {
+    /// Returns the chain of packages which made `name` to be required, ending with `name`.
+    fn requirement_chain(&self, name: &str) -> Vec<Box<str>> {
+        let mut chain = vec![Box::<str>::from(name)];
+        let mut current = name;
+        while let Some(&parent) = self.introduced_by.get(current) {
+            if chain.iter().any(|x| x.as_ref() == parent) {
+                // circular dependency
+                break;
+            }
+            chain.push(parent.into());
+            current = parent;
+        }
+        chain.reverse();
+        chain
+    }
+
+    fn explain(
+        &self,
+        env: &impl PackageCollection,
+        name: &str,
+        version: Option<&Version>,
+        requirement_chain: Vec<Box<str>>,
+        unity_version: Option<UnityVersion>,
+        allow_prerelease: bool,
+    ) -> ResolutionExplanation {
+        let requirements = self
+            .dependencies
+            .get(name)
+            .into_iter()
+            .flat_map(|info| info.requirements.iter())
+            .filter(|(&source, _)| {
+                self.dependencies
+                    .get(source)
+                    .map(|x| !x.is_legacy())
+                    .unwrap_or_default()
+            })
+            .map(|(&source, &range)| (Some(source).filter(|x| !x.is_empty()), range));
+
+        explain_resolution(
+            env,
+            name,
+            version,
+            requirement_chain,
+            requirements,
+            unity_version,
+            allow_prerelease,
+        )
+    }
+
+    fn collect_conflicts(&self) -> HashMap<Box<str>, Vec<Box<str>>> {
         let mut conflicts = HashMap::<Box<str>, Vec<Box<str>>>::new();
         for (&name, info) in &self.dependencies {
             if !info.is_legacy() && info.touched {
@@ -355,6 +642,31 @@ impl<'env, 'a> ResolutionContext<'env, 'a> {
                 }
             }
         }
+        conflicts
+    }
+
+    pub(crate) fn build_result(
+        self,
+        env: &impl PackageCollection,
+        unity_version: Option<UnityVersion>,
+    ) -> PackageResolutionResult<'env> {
+        let conflicts = self.collect_conflicts();
+
+        let conflict_explanations = conflicts
+            .keys()
+            .map(|name| {
+                let info = &self.dependencies[name.as_ref()];
+                let explanation = self.explain(
+                    env,
+                    name,
+                    info.current,
+                    self.requirement_chain(name),
+                    unity_version,
+                    info.allow_pre || self.allow_prerelease,
+                );
+                (name.clone(), explanation)
+            })
+            .collect();
 
         let found_legacy_packages = self
             .dependencies
@@ -373,6 +685,7 @@ impl<'env, 'a> ResolutionContext<'env, 'a> {
         PackageResolutionResult {
             new_packages,
             conflicts,
+            conflict_explanations,
             found_legacy_packages,
         }
     }
@@ -382,6 +695,8 @@ pub struct PackageResolutionResult<'env> {
     pub new_packages: Vec<PackageInfo<'env>>,
     // conflict dependency -> conflicting package[])
     pub conflicts: HashMap<Box<str>, Vec<Box<str>>>,
+    // conflict dependency -> explanation of the conflict
+    pub conflict_explanations: HashMap<Box<str>, ResolutionExplanation>,
     // list of names of legacy packages we found
     pub found_legacy_packages: Vec<Box<str>>,
 }
@@ -391,13 +706,15 @@ pub(crate) fn collect_adding_packages<'a, 'env>(
     dependencies: impl Iterator<Item = (&'a str, &'a DependencyRange)>,
     locked_dependencies: impl Iterator<Item = LockedDependencyInfo<'a>>,
     unlocked_packages: &'a [(Box<str>, Option<PackageManifest>)],
+    held_packages: impl Iterator<Item = &'a str>,
     get_locked: impl Fn(&str) -> Option<LockedDependencyInfo<'a>>,
     unity_version: Option<UnityVersion>,
     env: &'env impl PackageCollection,
     packages: Vec<PackageInfo<'env>>,
+    overrides: &'a IndexMap<Box<str>, VersionRange>,
     allow_prerelease: bool,
 ) -> Result<PackageResolutionResult<'env>, AddPackageErr> {
-    let mut context = ResolutionContext::<'env, '_>::new(allow_prerelease, packages);
+    let mut context = ResolutionContext::<'env, '_>::new(allow_prerelease, packages, overrides);
 
     // first, add dependencies
     let root_dependencies = dependencies
@@ -441,56 +758,89 @@ pub(crate) fn collect_adding_packages<'a, 'env>(
         }
     }
 
-    while let Some(x) = context.pending_queue.next_package() {
-        log::debug!("processing package {} version {}", x.name(), x.version());
-        let name = x.name();
-        let vpm_dependencies = &x.vpm_dependencies();
+    for held in held_packages {
+        context.add_held_name(held);
+    }
 
-        if context.add_package(x) {
-            // add new dependencies
-            for (dependency, range) in vpm_dependencies.iter() {
-                log::debug!("processing package {name}: dependency {dependency} version {range}");
+    let mut resolver = Resolver {
+        env,
+        unity_version,
+        remaining_backtracks: MAX_BACKTRACKS,
+    };
 
-                if context.should_add_package(dependency, range) {
-                    fn get_package<'env>(
-                        env: &'env impl PackageCollection,
-                        dependency: &str,
-                        unity_version: Option<UnityVersion>,
-                        range: &VersionRange,
-                        allow_prerelease: bool,
-                    ) -> Option<PackageInfo<'env>> {
-                        env.find_package_by_name(
-                            dependency,
-                            VersionSelector::range_for(unity_version, range, allow_prerelease),
-                        )
-                        .or_else(|| {
-                            env.find_package_by_name(
-                                dependency,
-                                VersionSelector::range_for(None, range, allow_prerelease),
-                            )
-                        })
-                    }
+    match resolver.search(context) {
+        SearchResult::Resolved(context) | SearchResult::Conflicted(context) => {
+            Ok(context.build_result(env, unity_version))
+        }
+        SearchResult::Failed(err) => Err(err),
+    }
+}
 
-                    let mut found;
-                    if allow_prerelease {
-                        found = get_package(env, dependency, unity_version, range, true);
-                    } else {
-                        found = get_package(env, dependency, unity_version, range, false);
-                        if found.is_none() && x.version().is_pre() {
-                            found = get_package(env, dependency, None, range, true);
-                        }
-                    }
+/// Builds the explanation why `package` cannot be resolved without conflicts.
+///
+/// `requirements` are pairs of the dependent (`None` for the project) and the range it requires.
+pub(crate) fn explain_resolution<'a>(
+    env: &impl PackageCollection,
+    package: &str,
+    version: Option<&Version>,
+    requirement_chain: Vec<Box<str>>,
+    requirements: impl Iterator<Item = (Option<&'a str>, &'a VersionRange)>,
+    unity_version: Option<UnityVersion>,
+    allow_prerelease: bool,
+) -> ResolutionExplanation {
+    let mut requirements = requirements.collect::<Vec<_>>();
+    requirements.sort_by_key(|&(dependent, _)| dependent);
 
-                    let found = found.ok_or_else(|| AddPackageErr::DependencyNotFound {
-                        dependency_name: dependency.clone(),
-                    })?;
+    let mut versions = env.find_packages(package).collect::<Vec<_>>();
+    versions.sort_by(|a, b| b.version().cmp(a.version()));
+    versions.dedup_by(|a, b| a.version() == b.version());
 
-                    // remove existing if existing
-                    context.pending_queue.add_pending_package(found);
+    let rejected_versions = versions
+        .into_iter()
+        .filter(|x| Some(x.version()) != version)
+        .filter_map(|x| {
+            let mut reasons = vec![];
+            if requirements
+                .iter()
+                .any(|(_, range)| !range.match_pre(x.version(), true))
+            {
+                reasons.push(RejectReason::OutOfRange);
+            }
+            if x.is_yanked() {
+                reasons.push(RejectReason::Yanked);
+            }
+            if let Some(unity) = unity_version {
+                if !unity_compatible(x.package_json(), unity) {
+                    reasons.push(RejectReason::IncompatibleUnity);
                 }
             }
-        }
-    }
+            if x.version().is_pre() && !allow_prerelease {
+                reasons.push(RejectReason::Prerelease);
+            }
 
-    Ok(context.build_result())
+            if reasons.is_empty() {
+                None
+            } else {
+                Some(RejectedVersion::new(x.version().clone(), reasons))
+            }
+        })
+        .collect();
+
+    let requirements = requirements
+        .into_iter()
+        .map(|(dependent, range)| {
+            let satisfied = version
+                .map(|v| range.match_pre(v, allow_prerelease))
+                .unwrap_or(false);
+            DependencyRequirement::new(dependent.map(Into::into), range.clone(), satisfied)
+        })
+        .collect();
+
+    ResolutionExplanation::new(
+        package.into(),
+        version.cloned(),
+        requirement_chain,
+        requirements,
+        rejected_versions,
+    )
 }
@@ -1,9 +1,14 @@
+use crate::environment::store_package;
 use crate::io;
 use crate::io::{DirEntry, ProjectIo};
-use crate::traits::EnvironmentIoHolder;
+use crate::traits::{EnvironmentIoHolder, InstallProgress};
+use crate::unity_project::changes_journal::{
+    ChangesJournal, JournalDependency, JournalLocked, JournalState,
+};
 use crate::unity_project::find_legacy_assets::collect_legacy_assets;
-use crate::utils::{copy_recursive, extract_zip, walk_dir_relative};
-use crate::version::DependencyRange;
+use crate::unity_project::local_modifications::LocalModificationsErr;
+use crate::utils::{copy_recursive, extract_zip, link_or_copy_recursive, walk_dir_relative};
+use crate::version::{DependencyRange, Version, VersionRange};
 use crate::{
     unity_compatible, PackageInfo, PackageInfoInner, RemotePackageDownloader, UnityProject,
 };
@@ -11,6 +16,7 @@ use either::Either;
 use futures::future::{join, join_all, try_join_all};
 use futures::prelude::*;
 use log::debug;
+use serde::{Deserialize, Serialize};
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::marker::PhantomData;
@@ -32,6 +38,8 @@ pub struct PendingProjectChanges<'env> {
     pub(crate) remove_legacy_folders: Vec<(Box<Path>, &'env str)>,
 
     pub(crate) conflicts: HashMap<Box<str>, ConflictInfo>,
+
+    pub(crate) overwrite_local_modifications: bool,
 }
 
 #[derive(Debug)]
@@ -58,9 +66,9 @@ impl<'env> PackageChange<'env> {
 
 #[derive(Debug)]
 pub struct Install<'env> {
-    package: Option<PackageInfo<'env>>,
-    add_to_locked: bool,
-    to_dependencies: Option<DependencyRange>,
+    pub(super) package: Option<PackageInfo<'env>>,
+    pub(super) add_to_locked: bool,
+    pub(super) to_dependencies: Option<DependencyRange>,
 }
 
 impl<'env> Install<'env> {
@@ -79,8 +87,8 @@ impl<'env> Install<'env> {
 
 #[derive(Debug)]
 pub struct Remove<'env> {
-    reason: RemoveReason,
-    _phantom: PhantomData<&'env ()>,
+    pub(super) reason: RemoveReason,
+    pub(super) _phantom: PhantomData<&'env ()>,
 }
 
 impl<'env> Remove<'env> {
@@ -89,17 +97,21 @@ impl<'env> Remove<'env> {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum RemoveReason {
     Requested,
     Legacy,
     Unused,
+    /// The package depends on the requested package
+    Dependent,
 }
 
 #[derive(Debug, Default)]
 pub struct ConflictInfo {
-    conflicts_packages: Vec<Box<str>>,
-    conflicts_with_unity: bool,
+    pub(super) conflicts_packages: Vec<Box<str>>,
+    pub(super) conflicts_with_unity: bool,
+    pub(super) conflicts_with_hold: bool,
+    pub(super) explanation: Option<ResolutionExplanation>,
 }
 
 impl ConflictInfo {
@@ -110,6 +122,135 @@ impl ConflictInfo {
     pub fn conflicts_with_unity(&self) -> bool {
         self.conflicts_with_unity
     }
+
+    /// Returns true if the package is held but the changes require moving it to another version.
+    ///
+    /// The held package is kept at the locked version.
+    pub fn conflicts_with_hold(&self) -> bool {
+        self.conflicts_with_hold
+    }
+
+    /// The detailed explanation of the version conflict if available.
+    pub fn explanation(&self) -> Option<&ResolutionExplanation> {
+        self.explanation.as_ref()
+    }
+}
+
+/// Describes why the resolver couldn't find a version of a package which satisfies all requirements.
+#[derive(Debug, Clone)]
+pub struct ResolutionExplanation {
+    package: Box<str>,
+    version: Option<Version>,
+    requirement_chain: Vec<Box<str>>,
+    requirements: Vec<DependencyRequirement>,
+    rejected_versions: Vec<RejectedVersion>,
+}
+
+impl ResolutionExplanation {
+    pub(crate) fn new(
+        package: Box<str>,
+        version: Option<Version>,
+        requirement_chain: Vec<Box<str>>,
+        requirements: Vec<DependencyRequirement>,
+        rejected_versions: Vec<RejectedVersion>,
+    ) -> Self {
+        Self {
+            package,
+            version,
+            requirement_chain,
+            requirements,
+            rejected_versions,
+        }
+    }
+
+    /// The name of the package.
+    pub fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// The version of the package the resolver chose. `None` if no version was found.
+    pub fn version(&self) -> Option<&Version> {
+        self.version.as_ref()
+    }
+
+    /// The chain of packages which made the package required.
+    ///
+    /// This starts with the package requested by the user and ends with the package itself.
+    pub fn requirement_chain(&self) -> &[Box<str>] {
+        &self.requirement_chain
+    }
+
+    /// The version ranges imposed on the package by the dependents.
+    pub fn requirements(&self) -> &[DependencyRequirement] {
+        &self.requirements
+    }
+
+    /// The versions of the package which were considered but not used, newest first.
+    pub fn rejected_versions(&self) -> &[RejectedVersion] {
+        &self.rejected_versions
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DependencyRequirement {
+    dependent: Option<Box<str>>,
+    range: VersionRange,
+    satisfied: bool,
+}
+
+impl DependencyRequirement {
+    pub(crate) fn new(dependent: Option<Box<str>>, range: VersionRange, satisfied: bool) -> Self {
+        Self {
+            dependent,
+            range,
+            satisfied,
+        }
+    }
+
+    /// The name of the package requiring the range. `None` for dependencies of the project.
+    pub fn dependent(&self) -> Option<&str> {
+        self.dependent.as_deref()
+    }
+
+    pub fn range(&self) -> &VersionRange {
+        &self.range
+    }
+
+    /// Whether the range is satisfied by the chosen version.
+    pub fn is_satisfied(&self) -> bool {
+        self.satisfied
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RejectedVersion {
+    version: Version,
+    reasons: Vec<RejectReason>,
+}
+
+impl RejectedVersion {
+    pub(crate) fn new(version: Version, reasons: Vec<RejectReason>) -> Self {
+        Self { version, reasons }
+    }
+
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    pub fn reasons(&self) -> &[RejectReason] {
+        &self.reasons
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RejectReason {
+    /// The version doesn't match the range required by some dependents
+    OutOfRange,
+    Yanked,
+    IncompatibleUnity,
+    /// The version is a prerelease but prerelease is not allowed
+    Prerelease,
 }
 
 pub(crate) struct Builder<'env> {
@@ -225,11 +366,25 @@ impl<'env> Builder<'env> {
         self
     }
 
+    pub fn explain_conflict(
+        &mut self,
+        name: Box<str>,
+        explanation: ResolutionExplanation,
+    ) -> &mut Self {
+        self.conflicts.entry(name).or_default().explanation = Some(explanation);
+        self
+    }
+
     pub fn conflicts_unity(&mut self, name: Box<str>) -> &mut Self {
         self.conflicts.entry(name).or_default().conflicts_with_unity = true;
         self
     }
 
+    pub fn conflicts_hold(&mut self, name: Box<str>) -> &mut Self {
+        self.conflicts.entry(name).or_default().conflicts_with_hold = true;
+        self
+    }
+
     pub fn remove(&mut self, name: Box<str>, reason: RemoveReason) -> &mut Self {
         match self.package_changes.entry(name) {
             Entry::Occupied(mut e) => match e.get_mut() {
@@ -315,6 +470,8 @@ impl<'env> Builder<'env> {
 
             remove_legacy_files: vec![],
             remove_legacy_folders: vec![],
+
+            overwrite_local_modifications: false,
         }
     }
 
@@ -350,6 +507,8 @@ impl<'env> Builder<'env> {
 
             remove_legacy_files: legacy_assets.files,
             remove_legacy_folders: legacy_assets.folders,
+
+            overwrite_local_modifications: false,
         }
     }
 
@@ -493,20 +652,64 @@ impl PendingProjectChanges<'_> {
     pub fn conflicts(&self) -> &HashMap<Box<str>, ConflictInfo> {
         &self.conflicts
     }
+
+    /// Sets whether to overwrite the installed packages even if they are modified locally.
+    ///
+    /// By default, the packages are not overwritten and
+    /// [`UnityProject::apply_pending_changes`] fails with [`LocalModificationsErr`]
+    /// if files of the packages to be updated or removed are modified.
+    ///
+    /// [`LocalModificationsErr`]: crate::unity_project::LocalModificationsErr
+    pub fn set_overwrite_local_modifications(&mut self, overwrite: bool) {
+        self.overwrite_local_modifications = overwrite;
+    }
 }
 
-static TEMP_DIR: &str = "Temp";
-static PKG_TEMP_DIR: &str = "Temp/vrc-get";
+pub(super) static TEMP_DIR: &str = "Temp";
+pub(super) static PKG_TEMP_DIR: &str = "Temp/vrc-get";
 
 impl<IO: ProjectIo> UnityProject<IO> {
     /// Applies the changes specified in `AddPackageRequest` to the project.
+    ///
+    /// The changes are journaled until the project is saved, so interrupted changes can be
+    /// recovered with [`recover_interrupted_changes`](Self::recover_interrupted_changes).
     pub async fn apply_pending_changes<'env, Env: RemotePackageDownloader + EnvironmentIoHolder>(
         &mut self,
         env: &'env Env,
         request: PendingProjectChanges<'env>,
     ) -> io::Result<()> {
+        self.apply_pending_changes_with_progress(env, request, &())
+            .await
+    }
+
+    /// Applies the changes like [`apply_pending_changes`](Self::apply_pending_changes),
+    /// reporting the progress of downloading and extracting packages to `progress`.
+    pub async fn apply_pending_changes_with_progress<
+        'env,
+        Env: RemotePackageDownloader + EnvironmentIoHolder,
+    >(
+        &mut self,
+        env: &'env Env,
+        request: PendingProjectChanges<'env>,
+        progress: &impl InstallProgress,
+    ) -> io::Result<()> {
+        if self.interrupted_changes.is_some() {
+            return Err(io::Error::other(
+                "the previous changes to the project were interrupted. recover the project first",
+            ));
+        }
+
+        if !request.overwrite_local_modifications {
+            let modified = self.find_local_modifications(env, &request).await?;
+            if !modified.is_empty() {
+                return Err(io::Error::other(LocalModificationsErr::new(modified)));
+            }
+        }
+
         let mut installs = Vec::new();
         let mut remove_names = Vec::new();
+        let mut add_locked = Vec::new();
+        let mut add_dependencies = Vec::new();
 
         for (name, change) in request.package_changes {
             match change {
@@ -519,11 +722,17 @@ impl<IO: ProjectIo> UnityProject<IO> {
                                 package.version().clone(),
                                 package.vpm_dependencies().clone(),
                             );
+                            add_locked.push(JournalLocked {
+                                name: package.name().into(),
+                                version: package.version().clone(),
+                                dependencies: package.vpm_dependencies().clone(),
+                            });
                         }
                     }
 
                     if let Some(version) = change.to_dependencies {
-                        self.manifest.add_dependency(&name, version);
+                        self.manifest.add_dependency(&name, version.clone());
+                        add_dependencies.push(JournalDependency { name, version });
                     }
                 }
                 PackageChange::Remove(_) => {
@@ -532,31 +741,67 @@ impl<IO: ProjectIo> UnityProject<IO> {
             }
         }
 
+        self.unlink_packages(remove_names.iter().map(Box::as_ref));
         self.manifest
             .remove_packages(remove_names.iter().map(Box::as_ref));
+        self.settings
+            .remove_packages(remove_names.iter().map(Box::as_ref));
 
         let remove_temp_dir = format!("{}/{}", PKG_TEMP_DIR, uuid::Uuid::new_v4());
         let remove_temp_dir = Path::new(&remove_temp_dir);
 
+        // write the journal before modifying packages so that we can recover the project
+        // if the process is killed while applying changes
+        let mut journal = ChangesJournal {
+            state: JournalState::Moving,
+            temp_dir: remove_temp_dir.into(),
+            installing: installs.iter().map(|x| x.name().into()).collect(),
+            removing: remove_names.clone(),
+            add_locked,
+            add_dependencies,
+            remove_legacy_files: (request.remove_legacy_files.iter())
+                .map(|(p, _)| p.clone())
+                .collect(),
+            remove_legacy_folders: (request.remove_legacy_folders.iter())
+                .map(|(p, _)| p.clone())
+                .collect(),
+        };
+        journal.write(&self.io, JournalState::Moving).await?;
+
         self.io.create_dir_all(remove_temp_dir).await?;
 
-        let removed = move_packages_to_temp(
+        let removed = match move_packages_to_temp(
             &self.io,
             (remove_names.iter().map(Box::as_ref)).chain(installs.iter().map(|x| x.name())),
             remove_temp_dir,
         )
-        .await?;
+        .await
+        {
+            Ok(removed) => removed,
+            Err(err) => {
+                // move_packages_to_temp restores moved packages
+                ChangesJournal::remove(&self.io).await.ok();
+                return Err(err);
+            }
+        };
+
+        journal.write(&self.io, JournalState::Installing).await?;
 
-        match install_packages(&self.io, env, &installs).await {
+        match install_packages(&self.io, env, &installs, progress).await {
             Ok(()) => {}
             Err(err) => {
                 // restore moved packages
                 restore_remove(&self.io, remove_temp_dir, removed.iter().copied()).await;
+                ChangesJournal::remove(&self.io).await.ok();
 
                 return Err(err);
             }
         }
 
+        journal.write(&self.io, JournalState::Installed).await?;
+        // the journal will be removed when the manifest is saved
+        self.committed_journal = true;
+
         self.io.remove_dir_all(remove_temp_dir).await.ok();
         self.io.remove_dir_all(PKG_TEMP_DIR.as_ref()).await.ok();
         // remove temp dir also if it's empty
@@ -576,7 +821,7 @@ impl<IO: ProjectIo> UnityProject<IO> {
     }
 }
 
-static REMOVED_FILE_PREFIX: &str = ".__removed_";
+pub(super) static REMOVED_FILE_PREFIX: &str = ".__removed_";
 
 async fn move_packages_to_temp<'a>(
     io: &impl ProjectIo,
@@ -674,19 +919,20 @@ async fn install_packages<Env: RemotePackageDownloader + EnvironmentIoHolder>(
     io: &impl ProjectIo,
     env: &Env,
     packages: &[PackageInfo<'_>],
+    progress: &impl InstallProgress,
 ) -> io::Result<()> {
     // resolve all packages
     try_join_all(
         packages
             .iter()
-            .map(|package| add_package(io, env, *package)),
+            .map(|package| add_package(io, env, *package, progress)),
     )
     .await?;
 
     Ok(())
 }
 
-async fn remove_assets(
+pub(super) async fn remove_assets(
     io: &impl ProjectIo,
     legacy_files: impl Iterator<Item = &Path>,
     legacy_folders: impl Iterator<Item = &Path>,
@@ -732,22 +978,36 @@ pub(crate) async fn add_package<Env: RemotePackageDownloader + EnvironmentIoHold
     io: &impl ProjectIo,
     env: &Env,
     package: PackageInfo<'_>,
+    progress: &impl InstallProgress,
 ) -> io::Result<()> {
     log::debug!("adding package {}", package.name());
     let dest_folder = PathBuf::from(format!("Packages/{}", package.name()));
     match package.inner {
         PackageInfoInner::Remote(package, user_repo) => {
-            let zip_file = env.get_package(user_repo, package).await?;
+            let zip_file = env.get_package(user_repo, package, progress).await?;
             let zip_file = io::BufReader::new(zip_file);
 
-            // remove dest folder before extract if exists
-            extract_zip(zip_file, io, &dest_folder).await?;
-
-            Ok(())
+            if env.use_package_store() {
+                let stored = store_package(env.io(), zip_file, package, progress).await?;
+                link_or_copy_recursive(env.io(), stored, io, dest_folder).await?;
+            } else {
+                // remove dest folder before extract if exists
+                extract_zip(zip_file, io, &dest_folder, |extracted, total| {
+                    if extracted == 0 {
+                        progress.extract_started(package, total);
+                    } else {
+                        progress.extract_progress(package, extracted);
+                    }
+                })
+                .await?;
+            }
         }
         PackageInfoInner::Local(_, path) => {
             copy_recursive(env.io(), path.into(), io, dest_folder).await?;
-            Ok(())
         }
     }
+
+    progress.package_installed(package.package_json());
+
+    Ok(())
 }
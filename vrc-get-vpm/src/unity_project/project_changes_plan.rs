@@ -0,0 +1,345 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::io::ProjectIo;
+use crate::unity_project::pending_project_changes::{
+    ConflictInfo, Install, PackageChange, Remove, RemoveReason,
+};
+use crate::unity_project::PendingProjectChanges;
+use crate::version::{DependencyRange, Version};
+use crate::{PackageCollection, PackageInfo, UnityProject};
+
+const PLAN_FORMAT_VERSION: u32 = 1;
+
+/// The serializable form of [`PendingProjectChanges`].
+///
+/// This can be saved to a file, reviewed, and applied later with [`ProjectChangesPlan::into_changes`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectChangesPlan {
+    format_version: u32,
+    /// The locked packages of the project when the plan was created
+    locked: BTreeMap<Box<str>, Version>,
+    #[serde(default)]
+    installs: Vec<PlannedInstall>,
+    #[serde(default)]
+    removes: Vec<PlannedRemove>,
+    #[serde(default)]
+    remove_legacy_files: Vec<PlannedLegacyAsset>,
+    #[serde(default)]
+    remove_legacy_folders: Vec<PlannedLegacyAsset>,
+    #[serde(default)]
+    conflicts: Vec<PlannedConflict>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PlannedInstall {
+    name: Box<str>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    package: Option<PlannedPackage>,
+    #[serde(default)]
+    add_to_locked: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    to_dependencies: Option<DependencyRange>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PlannedPackage {
+    version: Version,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    zip_sha256: Option<Box<str>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    url: Option<Box<str>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PlannedRemove {
+    name: Box<str>,
+    reason: RemoveReason,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PlannedLegacyAsset {
+    path: Box<Path>,
+    package: Box<str>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PlannedConflict {
+    name: Box<str>,
+    #[serde(default)]
+    packages: Vec<Box<str>>,
+    #[serde(default)]
+    unity_conflict: bool,
+    #[serde(default)]
+    hold_conflict: bool,
+}
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LoadPlanErr {
+    UnsupportedFormatVersion {
+        version: u32,
+    },
+    PackageNotFound {
+        package_name: Box<str>,
+        version: Version,
+    },
+    HashMismatch {
+        package_name: Box<str>,
+        version: Version,
+        planned: Option<Box<str>>,
+        actual: Option<Box<str>>,
+    },
+    LegacyAssetOfUnknownPackage {
+        package_name: Box<str>,
+    },
+    LockedPackageChanged {
+        package_name: Box<str>,
+    },
+}
+
+impl fmt::Display for LoadPlanErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadPlanErr::UnsupportedFormatVersion { version } => {
+                write!(f, "Unsupported plan format version: {version}")
+            }
+            LoadPlanErr::PackageNotFound {
+                package_name,
+                version,
+            } => write!(
+                f,
+                "Package {package_name} version {version} in the plan is not found in repositories"
+            ),
+            LoadPlanErr::HashMismatch {
+                package_name,
+                version,
+                planned,
+                actual,
+            } => write!(
+                f,
+                "Package {package_name} version {version} has changed since the plan was created: expected zipSHA256 {planned}, but repository has {actual}",
+                planned = planned.as_deref().unwrap_or("(none)"),
+                actual = actual.as_deref().unwrap_or("(none)"),
+            ),
+            LoadPlanErr::LegacyAssetOfUnknownPackage { package_name } => write!(
+                f,
+                "The plan removes legacy assets of {package_name} but the package is not installed by the plan"
+            ),
+            LoadPlanErr::LockedPackageChanged { package_name } => write!(
+                f,
+                "Locked version of {package_name} in the project has changed since the plan was created"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LoadPlanErr {}
+
+impl PendingProjectChanges<'_> {
+    /// Creates the serializable plan of the changes to the `project`.
+    ///
+    /// The locked packages of the `project` are recorded to the plan
+    /// so the plan cannot be applied after the project is changed.
+    pub fn to_plan(&self, project: &UnityProject<impl ProjectIo>) -> ProjectChangesPlan {
+        let mut installs = vec![];
+        let mut removes = vec![];
+
+        for (name, change) in &self.package_changes {
+            match change {
+                PackageChange::Install(install) => installs.push(PlannedInstall {
+                    name: name.clone(),
+                    package: install.install_package().map(|package| PlannedPackage {
+                        version: package.version().clone(),
+                        zip_sha256: package.package_json().zip_sha_256().map(Into::into),
+                        url: package.package_json().url().map(|x| x.as_str().into()),
+                    }),
+                    add_to_locked: install.is_adding_to_locked(),
+                    to_dependencies: install.to_dependencies().cloned(),
+                }),
+                PackageChange::Remove(remove) => removes.push(PlannedRemove {
+                    name: name.clone(),
+                    reason: remove.reason(),
+                }),
+            }
+        }
+
+        // sort to make the plan stable for reviewing
+        installs.sort_by(|a, b| a.name.cmp(&b.name));
+        removes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        fn legacy_assets(assets: &[(Box<Path>, &str)]) -> Vec<PlannedLegacyAsset> {
+            assets
+                .iter()
+                .map(|(path, package)| PlannedLegacyAsset {
+                    path: path.clone(),
+                    package: (*package).into(),
+                })
+                .collect()
+        }
+
+        let mut conflicts = (self.conflicts.iter())
+            .map(|(name, info)| PlannedConflict {
+                name: name.clone(),
+                packages: info.conflicting_packages().to_vec(),
+                unity_conflict: info.conflicts_with_unity(),
+                hold_conflict: info.conflicts_with_hold(),
+            })
+            .collect::<Vec<_>>();
+        conflicts.sort_by(|a, b| a.name.cmp(&b.name));
+
+        ProjectChangesPlan {
+            format_version: PLAN_FORMAT_VERSION,
+            locked: locked_versions(project),
+            installs,
+            removes,
+            remove_legacy_files: legacy_assets(&self.remove_legacy_files),
+            remove_legacy_folders: legacy_assets(&self.remove_legacy_folders),
+            conflicts,
+        }
+    }
+}
+
+impl ProjectChangesPlan {
+    /// Restores the changes to the `project` from the plan.
+    ///
+    /// This fails if the locked packages of the `project` have changed since the plan was created,
+    /// the packages in the plan are no longer available in the environment,
+    /// or the `zipSHA256` of the packages in the environment differs from the plan.
+    pub fn into_changes<'env>(
+        self,
+        env: &'env impl PackageCollection,
+        project: &UnityProject<impl ProjectIo>,
+    ) -> Result<PendingProjectChanges<'env>, LoadPlanErr> {
+        if self.format_version != PLAN_FORMAT_VERSION {
+            return Err(LoadPlanErr::UnsupportedFormatVersion {
+                version: self.format_version,
+            });
+        }
+
+        let actual_locked = locked_versions(project);
+        let changed = (self.locked.iter())
+            .find(|(name, version)| actual_locked.get(*name) != Some(*version))
+            .or_else(|| (actual_locked.iter()).find(|(name, _)| !self.locked.contains_key(*name)));
+        if let Some((name, _)) = changed {
+            return Err(LoadPlanErr::LockedPackageChanged {
+                package_name: name.clone(),
+            });
+        }
+
+        let mut package_changes = HashMap::new();
+        let mut installing = HashMap::new();
+
+        for install in self.installs {
+            let package = match install.package {
+                None => None,
+                Some(planned) => {
+                    let package = find_planned_package(env, &install.name, planned)?;
+                    installing.insert(install.name.clone(), package);
+                    Some(package)
+                }
+            };
+
+            package_changes.insert(
+                install.name,
+                PackageChange::Install(Install {
+                    package,
+                    add_to_locked: install.add_to_locked,
+                    to_dependencies: install.to_dependencies,
+                }),
+            );
+        }
+
+        for remove in self.removes {
+            package_changes.insert(
+                remove.name,
+                PackageChange::Remove(Remove {
+                    reason: remove.reason,
+                    _phantom: PhantomData,
+                }),
+            );
+        }
+
+        let legacy_assets = |assets: Vec<PlannedLegacyAsset>| {
+            assets
+                .into_iter()
+                .map(|asset| match installing.get(&asset.package) {
+                    Some(package) => Ok((asset.path, package.name())),
+                    None => Err(LoadPlanErr::LegacyAssetOfUnknownPackage {
+                        package_name: asset.package,
+                    }),
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let remove_legacy_files = legacy_assets(self.remove_legacy_files)?;
+        let remove_legacy_folders = legacy_assets(self.remove_legacy_folders)?;
+
+        let conflicts = self
+            .conflicts
+            .into_iter()
+            .map(|conflict| {
+                let info = ConflictInfo {
+                    conflicts_packages: conflict.packages,
+                    conflicts_with_unity: conflict.unity_conflict,
+                    conflicts_with_hold: conflict.hold_conflict,
+                    explanation: None,
+                };
+                (conflict.name, info)
+            })
+            .collect();
+
+        Ok(PendingProjectChanges {
+            package_changes,
+            remove_legacy_files,
+            remove_legacy_folders,
+            conflicts,
+            overwrite_local_modifications: false,
+        })
+    }
+}
+
+fn locked_versions(project: &UnityProject<impl ProjectIo>) -> BTreeMap<Box<str>, Version> {
+    (project.locked_packages())
+        .map(|locked| (locked.name().into(), locked.version().clone()))
+        .collect()
+}
+
+fn find_planned_package<'env>(
+    env: &'env impl PackageCollection,
+    name: &str,
+    planned: PlannedPackage,
+) -> Result<PackageInfo<'env>, LoadPlanErr> {
+    let candidates = env
+        .find_packages(name)
+        .filter(|x| x.version() == &planned.version)
+        .collect::<Vec<_>>();
+
+    let Some(&first) = candidates.first() else {
+        return Err(LoadPlanErr::PackageNotFound {
+            package_name: name.into(),
+            version: planned.version,
+        });
+    };
+
+    // there may be same version of the package in multiple repositories
+    candidates
+        .into_iter()
+        .find(|x| x.package_json().zip_sha_256() == planned.zip_sha256.as_deref())
+        .ok_or_else(|| LoadPlanErr::HashMismatch {
+            package_name: name.into(),
+            version: planned.version,
+            planned: planned.zip_sha256,
+            actual: first.package_json().zip_sha_256().map(Into::into),
+        })
+}
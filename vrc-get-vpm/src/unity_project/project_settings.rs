@@ -0,0 +1,127 @@
+use crate::io;
+use crate::io::ProjectIo;
+use crate::utils::{load_json_or_default, SaveController};
+use crate::version::VersionRange;
+use indexmap::{IndexMap, IndexSet};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// The vrc-get specific settings of the project.
+///
+/// This is not in `vpm-manifest.json` since VCC drops unknown fields when it rewrites the manifest.
+const SETTINGS_PATH: &str = "Packages/vrc-get-settings.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AsJson {
+    /// The packages held at the locked version
+    #[serde(default, skip_serializing_if = "IndexSet::is_empty")]
+    held: IndexSet<Box<str>>,
+    /// The version ranges used in place of the ranges declared by dependents
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    overrides: IndexMap<Box<str>, VersionRange>,
+    /// The packages linked with `file:` reference in manifest.json instead of copied
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    linked: IndexMap<Box<str>, LinkedPackage>,
+}
+
+/// The package linked to the project.
+///
+/// The package.json is read from `path` when the project is loaded
+/// since the package may be changed after linked.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct LinkedPackage {
+    /// The path relative to the `Packages` folder, same as the `file:` reference in manifest.json
+    pub(super) path: Box<Path>,
+}
+
+#[derive(Debug)]
+pub(super) struct ProjectSettings {
+    controller: SaveController<AsJson>,
+}
+
+impl ProjectSettings {
+    pub(super) async fn load(io: &impl ProjectIo) -> io::Result<Self> {
+        Ok(Self {
+            controller: SaveController::new(
+                load_json_or_default(io, SETTINGS_PATH.as_ref()).await?,
+            ),
+        })
+    }
+
+    pub(super) fn held_packages(&self) -> impl Iterator<Item = &str> {
+        self.controller.held.iter().map(Box::as_ref)
+    }
+
+    pub(super) fn is_held(&self, package: &str) -> bool {
+        self.controller.held.contains(package)
+    }
+
+    pub(super) fn hold(&mut self, package: &str) -> bool {
+        let mut added = false;
+        self.controller.may_changing(|json| {
+            added = json.held.insert(package.into());
+            added
+        });
+        added
+    }
+
+    pub(super) fn unhold(&mut self, package: &str) -> bool {
+        let mut removed = false;
+        self.controller.may_changing(|json| {
+            removed = json.held.shift_remove(package);
+            removed
+        });
+        removed
+    }
+
+    pub(super) fn overrides(&self) -> &IndexMap<Box<str>, VersionRange> {
+        &self.controller.overrides
+    }
+
+    pub(super) fn set_override(&mut self, package: &str, range: VersionRange) {
+        self.controller
+            .as_mut()
+            .overrides
+            .insert(package.into(), range);
+    }
+
+    pub(super) fn remove_override(&mut self, package: &str) -> bool {
+        let mut removed = false;
+        self.controller.may_changing(|json| {
+            removed = json.overrides.shift_remove(package).is_some();
+            removed
+        });
+        removed
+    }
+
+    pub(super) fn linked_packages(&self) -> impl Iterator<Item = (&str, &LinkedPackage)> {
+        (self.controller.linked.iter()).map(|(name, linked)| (name.as_ref(), linked))
+    }
+
+    pub(super) fn get_linked(&self, package: &str) -> Option<&LinkedPackage> {
+        self.controller.linked.get(package)
+    }
+
+    pub(super) fn add_linked(&mut self, package: &str, linked: LinkedPackage) {
+        self.controller
+            .as_mut()
+            .linked
+            .insert(package.into(), linked);
+    }
+
+    pub(crate) fn remove_packages<'a>(&mut self, names: impl Iterator<Item = &'a str>) {
+        for name in names {
+            self.controller.may_changing(|json| {
+                let held = json.held.shift_remove(name);
+                let linked = json.linked.shift_remove(name).is_some();
+                held || linked
+            });
+        }
+    }
+
+    pub(super) async fn save(&mut self, io: &impl ProjectIo) -> io::Result<()> {
+        self.controller.save(io, SETTINGS_PATH.as_ref()).await
+    }
+}
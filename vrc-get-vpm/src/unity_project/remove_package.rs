@@ -11,9 +11,29 @@ impl<IO: ProjectIo> UnityProject<IO> {
     /// Remove specified package from self project.
     ///
     /// This doesn't look packages not listed in vpm-maniefst.json.
+    /// Removing linked packages unlinks them.
     pub async fn remove_request(
         &self,
         remove: &[&str],
+    ) -> Result<PendingProjectChanges<'static>, RemovePackageErr> {
+        self.remove_request_impl(remove, false).await
+    }
+
+    /// Remove specified package and packages depending on them from self project.
+    ///
+    /// The locked packages depending on the removed packages are removed transitively
+    /// instead of being reported as conflicts.
+    pub async fn remove_cascade_request(
+        &self,
+        remove: &[&str],
+    ) -> Result<PendingProjectChanges<'static>, RemovePackageErr> {
+        self.remove_request_impl(remove, true).await
+    }
+
+    async fn remove_request_impl(
+        &self,
+        remove: &[&str],
+        cascade: bool,
     ) -> Result<PendingProjectChanges<'static>, RemovePackageErr> {
         use RemovePackageErr::*;
 
@@ -21,7 +41,7 @@ impl<IO: ProjectIo> UnityProject<IO> {
 
         let mut not_founds = Vec::new();
         for name in remove.iter().copied() {
-            if self.manifest.get_locked(name).is_none() {
+            if self.manifest.get_locked(name).is_none() && !self.is_linked(name) {
                 not_founds.push(name.into());
             }
         }
@@ -34,7 +54,28 @@ impl<IO: ProjectIo> UnityProject<IO> {
 
         // check for conflicts: if some package requires some packages to be removed, it's conflict.
 
-        let remove = remove.iter().copied().collect::<HashSet<_>>();
+        let requested = remove.iter().copied().collect::<HashSet<_>>();
+        let mut remove = requested.clone();
+
+        if cascade {
+            // collect locked packages depending on the packages to be removed transitively
+            loop {
+                let dependents = (self.manifest.all_locked())
+                    .filter(|dep| !remove.contains(dep.name()))
+                    .filter(|dep| {
+                        dep.dependencies()
+                            .keys()
+                            .any(|x| remove.contains(x.as_ref()))
+                    })
+                    .map(|dep| dep.name())
+                    .collect::<Vec<_>>();
+                if dependents.is_empty() {
+                    break;
+                }
+                remove.extend(dependents);
+            }
+        }
+
         let mut may_conflict = remove.clone();
 
         for name in (self.all_installed_packages())
@@ -59,11 +100,52 @@ impl<IO: ProjectIo> UnityProject<IO> {
         // there's no conflicts. So do remove
 
         for x in remove {
-            changes.remove(x.into(), RemoveReason::Requested);
+            let reason = if requested.contains(x) {
+                RemoveReason::Requested
+            } else {
+                RemoveReason::Dependent
+            };
+            changes.remove(x.into(), reason);
         }
 
         Ok(changes.build_resolve(self).await)
     }
+
+    /// Remove locked packages which are not required by the project anymore.
+    ///
+    /// The packages not reachable from `dependencies` section of vpm-manifest.json nor
+    /// dependencies of unlocked packages are removed.
+    pub async fn autoremove_request(&self) -> PendingProjectChanges<'static> {
+        let unlocked_dependencies = (self.unlocked_packages.iter())
+            .filter_map(|(_, pkg)| pkg.as_ref())
+            .flat_map(|pkg| pkg.vpm_dependencies().keys())
+            .map(Box::as_ref);
+        let roots = self.manifest.dependencies().map(|(name, _)| name);
+
+        let mut reachable = roots.chain(unlocked_dependencies).collect::<HashSet<_>>();
+        let mut queue = reachable.iter().copied().collect::<Vec<_>>();
+
+        while let Some(name) = queue.pop() {
+            let Some(locked) = self.manifest.get_locked(name) else {
+                continue;
+            };
+            for dependency in locked.dependencies().keys() {
+                if reachable.insert(dependency.as_ref()) {
+                    queue.push(dependency.as_ref());
+                }
+            }
+        }
+
+        let mut changes = pending_project_changes::Builder::new();
+
+        for locked in self.manifest.all_locked() {
+            if !reachable.contains(locked.name()) {
+                changes.remove(locked.name().into(), RemoveReason::Unused);
+            }
+        }
+
+        changes.build_resolve(self).await
+    }
 }
 
 #[derive(Debug)]
@@ -4,23 +4,29 @@ use std::fmt;
 use itertools::Itertools;
 
 use crate::io::ProjectIo;
+use crate::unity_project::pending_project_changes::ResolutionExplanation;
 use crate::unity_project::{
     package_resolution, pending_project_changes, AddPackageErr, LockedDependencyInfo,
     PendingProjectChanges,
 };
-use crate::version::DependencyRange;
+use crate::version::{DependencyRange, VersionRange};
 use crate::{PackageCollection, UnityProject, VersionSelector};
 
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum ResolvePackageErr {
-    DependencyNotFound { dependency_name: Box<str> },
+    DependencyNotFound {
+        dependency_name: Box<str>,
+        explanation: Box<ResolutionExplanation>,
+    },
 }
 
 impl fmt::Display for ResolvePackageErr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ResolvePackageErr::DependencyNotFound { dependency_name } => write!(
+            ResolvePackageErr::DependencyNotFound {
+                dependency_name, ..
+            } => write!(
                 f,
                 "Package {dependency_name} (maybe dependencies of the package) not found"
             ),
@@ -33,9 +39,13 @@ impl std::error::Error for ResolvePackageErr {}
 impl From<AddPackageErr> for ResolvePackageErr {
     fn from(value: AddPackageErr) -> Self {
         match value {
-            AddPackageErr::DependencyNotFound { dependency_name } => {
-                Self::DependencyNotFound { dependency_name }
-            }
+            AddPackageErr::DependencyNotFound {
+                dependency_name,
+                explanation,
+            } => Self::DependencyNotFound {
+                dependency_name,
+                explanation,
+            },
             AddPackageErr::UpgradingNonLockedPackage { .. }
             | AddPackageErr::DowngradingNonLockedPackage { .. }
             | AddPackageErr::UpgradingWithDowngrade { .. } => {
@@ -119,8 +129,20 @@ impl<IO: ProjectIo> UnityProject<IO> {
         for dep in self.manifest.all_locked() {
             let pkg = env
                 .find_package_by_name(dep.name(), VersionSelector::specific_version(dep.version()))
-                .ok_or_else(|| AddPackageErr::DependencyNotFound {
-                    dependency_name: dep.name().into(),
+                .ok_or_else(|| {
+                    let range = VersionRange::specific(dep.version().clone());
+                    AddPackageErr::DependencyNotFound {
+                        dependency_name: dep.name().into(),
+                        explanation: Box::new(package_resolution::explain_resolution(
+                            env,
+                            dep.name(),
+                            None,
+                            vec![dep.name().into()],
+                            [(None, &range)].into_iter(),
+                            self.unity_version(),
+                            true,
+                        )),
+                    }
                 })?;
 
             changes.install_already_locked(pkg);
@@ -155,8 +177,20 @@ impl<IO: ProjectIo> UnityProject<IO> {
                             range.as_range().contains_pre(),
                         ),
                     )
-                    .ok_or_else(|| AddPackageErr::DependencyNotFound {
-                        dependency_name: name.into(),
+                    .ok_or_else(|| {
+                        let range = range.as_range();
+                        AddPackageErr::DependencyNotFound {
+                            dependency_name: name.into(),
+                            explanation: Box::new(package_resolution::explain_resolution(
+                                env,
+                                name,
+                                None,
+                                vec![name.into()],
+                                [(None, &range)].into_iter(),
+                                self.unity_version(),
+                                range.contains_pre(),
+                            )),
+                        }
                     })?,
                 );
                 install_names.insert(name);
@@ -173,10 +207,12 @@ impl<IO: ProjectIo> UnityProject<IO> {
             self.manifest.dependencies(),
             self.manifest.all_locked(),
             &self.unlocked_packages,
+            self.settings.held_packages(),
             |pkg| self.manifest.get_locked(pkg),
             self.unity_version(),
             env,
             to_install,
+            self.settings.overrides(),
             allow_prerelease,
         )?;
 
@@ -194,6 +230,10 @@ impl<IO: ProjectIo> UnityProject<IO> {
             changes.conflict_multiple(package, conflicts_with);
         }
 
+        for (package, explanation) in result.conflict_explanations {
+            changes.explain_conflict(package, explanation);
+        }
+
         Ok(())
     }
 
@@ -226,9 +266,10 @@ impl<IO: ProjectIo> UnityProject<IO> {
             .iter()
             .filter_map(|(_, pkg)| pkg.as_ref())
             .flat_map(|pkg| {
-                pkg.vpm_dependencies()
-                    .into_iter()
-                    .map(|(k, v)| (k, v, pkg.version().is_pre()))
+                pkg.vpm_dependencies().into_iter().map(|(k, v)| {
+                    let v = self.settings.overrides().get(k).unwrap_or(v);
+                    (k, v, pkg.version().is_pre())
+                })
             });
 
         let unlocked_dependencies_versions = dependencies_of_unlocked_packages
@@ -271,6 +312,15 @@ impl<IO: ProjectIo> UnityProject<IO> {
                 )
                 .ok_or_else(|| AddPackageErr::DependencyNotFound {
                     dependency_name: pkg_name.clone(),
+                    explanation: Box::new(package_resolution::explain_resolution(
+                        env,
+                        pkg_name,
+                        None,
+                        vec![pkg_name.clone()],
+                        ranges.iter().map(|&range| (None, range)),
+                        self.unity_version,
+                        allow_prerelease,
+                    )),
                 })
             })
             .collect::<Result<Vec<_>, _>>()?;
@@ -283,10 +333,12 @@ impl<IO: ProjectIo> UnityProject<IO> {
             self.manifest.dependencies(),
             virtual_locked_dependencies.values().cloned(),
             &self.unlocked_packages,
+            self.settings.held_packages(),
             |pkg| virtual_locked_dependencies.get(pkg).cloned(),
             self.unity_version(),
             env,
             unlocked_dependencies,
+            self.settings.overrides(),
             allow_prerelease,
         )?;
 
@@ -298,6 +350,10 @@ impl<IO: ProjectIo> UnityProject<IO> {
             changes.conflict_multiple(package, conflicts_with);
         }
 
+        for (package, explanation) in result.conflict_explanations {
+            changes.explain_conflict(package, explanation);
+        }
+
         Ok(())
     }
 }
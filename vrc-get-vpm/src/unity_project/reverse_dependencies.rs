@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+
+use crate::io::ProjectIo;
+use crate::version::{DependencyRange, VersionRange};
+use crate::UnityProject;
+
+/// A path from a root of the project to some package.
+#[derive(Debug, Clone)]
+pub struct DependencyPath {
+    steps: Vec<DependencyPathStep>,
+}
+
+impl DependencyPath {
+    /// The steps of the path.
+    ///
+    /// The first step is the root of the path and the last step is the queried package.
+    pub fn steps(&self) -> &[DependencyPathStep] {
+        &self.steps
+    }
+
+    /// The root package of the path.
+    pub fn root(&self) -> &str {
+        self.steps[0].name()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DependencyPathStep {
+    name: Box<str>,
+    reason: DependencyPathReason,
+}
+
+impl DependencyPathStep {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Why the package is in the path.
+    pub fn reason(&self) -> &DependencyPathReason {
+        &self.reason
+    }
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum DependencyPathReason {
+    /// The package is in `dependencies` section of `vpm-manifest.json` with the range
+    Root(DependencyRange),
+    /// The package is installed without being locked
+    Unlocked,
+    /// The previous package depends on this package with the range
+    Dependency(VersionRange),
+    /// The previous package declares this package as legacy package
+    Legacy,
+}
+
+impl<IO: ProjectIo> UnityProject<IO> {
+    /// Returns every path from the roots of the project to the package.
+    ///
+    /// The roots are the packages in `dependencies` section and unlocked packages.
+    /// The edges are dependencies of locked (or unlocked) packages and legacy packages
+    /// declared by installed packages.
+    ///
+    /// If the package is not reachable from any root, empty vec will be returned.
+    pub fn dependency_paths_to(&self, package: &str) -> Vec<DependencyPath> {
+        let mut paths = vec![];
+        let mut current = vec![];
+        let mut visiting = HashSet::new();
+
+        let roots = self
+            .manifest
+            .dependencies()
+            .map(|(name, range)| (name, DependencyPathReason::Root(range.clone())))
+            .chain(
+                (self.unlocked_packages.iter())
+                    .filter_map(|(_, pkg)| pkg.as_ref())
+                    .map(|pkg| (pkg.name(), DependencyPathReason::Unlocked)),
+            );
+
+        for (name, reason) in roots {
+            self.collect_dependency_paths(
+                package,
+                name,
+                reason,
+                &mut current,
+                &mut visiting,
+                &mut paths,
+            );
+        }
+
+        paths
+    }
+
+    fn collect_dependency_paths<'a>(
+        &'a self,
+        target: &str,
+        name: &'a str,
+        reason: DependencyPathReason,
+        current: &mut Vec<DependencyPathStep>,
+        visiting: &mut HashSet<&'a str>,
+        paths: &mut Vec<DependencyPath>,
+    ) {
+        if !visiting.insert(name) {
+            // circular dependency
+            return;
+        }
+
+        current.push(DependencyPathStep {
+            name: name.into(),
+            reason,
+        });
+
+        if name == target {
+            paths.push(DependencyPath {
+                steps: current.clone(),
+            });
+        } else {
+            let locked = self.manifest.get_locked(name);
+            let installed = self.get_installed_package(name).or_else(|| {
+                (self.unlocked_packages.iter())
+                    .filter_map(|(_, pkg)| pkg.as_ref())
+                    .find(|pkg| pkg.name() == name)
+            });
+
+            let dependencies = match (&locked, installed) {
+                (Some(locked), _) => Some(locked.dependencies()),
+                (None, Some(installed)) => Some(installed.vpm_dependencies()),
+                (None, None) => None,
+            };
+
+            for (dependency, range) in dependencies.into_iter().flatten() {
+                self.collect_dependency_paths(
+                    target,
+                    dependency,
+                    DependencyPathReason::Dependency(range.clone()),
+                    current,
+                    visiting,
+                    paths,
+                );
+            }
+
+            for legacy in installed.into_iter().flat_map(|x| x.legacy_packages()) {
+                self.collect_dependency_paths(
+                    target,
+                    legacy,
+                    DependencyPathReason::Legacy,
+                    current,
+                    visiting,
+                    paths,
+                );
+            }
+        }
+
+        current.pop();
+        visiting.remove(name);
+    }
+}
@@ -141,6 +141,22 @@ impl UpmManifest {
             .insert(name.into(), UpmDependency::Version(version));
     }
 
+    pub(super) fn add_file_dependency(&mut self, name: &str, path: &str) {
+        let reference = format!("file:{path}");
+        self.controller
+            .as_mut()
+            .raw
+            .get_or_put_mut("dependencies", Map::new)
+            .as_object_mut()
+            .unwrap()
+            .insert(name.to_string(), Value::String(reference.clone()));
+        self.controller
+            .as_mut()
+            .as_json
+            .dependencies
+            .insert(name.into(), UpmDependency::OtherNotation(reference.into()));
+    }
+
     pub(super) fn remove_dependency(&mut self, name: &str) {
         self.controller
             .as_mut()
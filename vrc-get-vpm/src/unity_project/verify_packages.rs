@@ -0,0 +1,177 @@
+//! Verifying the installed packages against the locked versions.
+//!
+//! Each locked package is checked with `package.json` in the package folder, and with
+//! the zip file of the package cached in the environment if exists.
+
+use crate::environment::package_cache_path;
+use crate::io;
+use crate::io::{EnvironmentIo, ProjectIo};
+use crate::traits::EnvironmentIoHolder;
+use crate::unity_project::local_modifications::compare_with_cached_zip;
+use crate::utils::Sha256AsyncWrite;
+use crate::version::Version;
+use crate::{PackageCollection, UnityProject, VersionSelector};
+use std::path::Path;
+
+/// The problem found in the installed package.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum PackageIssue {
+    /// `Packages/<name>/package.json` does not exist or cannot be parsed.
+    MissingPackageJson,
+    /// The version in `package.json` is not the locked version.
+    VersionMismatch { installed: Version },
+    /// The cached zip file does not match `zipSHA256` in the repository.
+    ZipHashMismatch {
+        expected: Box<str>,
+        actual: Box<str>,
+    },
+    /// The file is different from the file in the zip file.
+    ModifiedFile { path: Box<Path> },
+    /// The file does not exist in the zip file.
+    ExtraFile { path: Box<Path> },
+    /// The file in the zip file does not exist in the package folder.
+    MissingFile { path: Box<Path> },
+    /// The files were not compared since the zip file of the package is not cached,
+    /// e.g. for local user packages.
+    FilesNotChecked,
+}
+
+/// The result of verifying one locked package.
+#[derive(Debug, Clone)]
+pub struct PackageVerification {
+    name: Box<str>,
+    version: Version,
+    files_checked: bool,
+    issues: Vec<PackageIssue>,
+}
+
+impl PackageVerification {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The locked version of the package.
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    /// Whether the files were compared with the cached zip file.
+    ///
+    /// This is false if the zip file of the package is not cached, e.g. for local user packages.
+    pub fn files_checked(&self) -> bool {
+        self.files_checked
+    }
+
+    pub fn issues(&self) -> &[PackageIssue] {
+        &self.issues
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// The result of [`UnityProject::verify_packages`].
+#[derive(Debug, Clone)]
+pub struct VerifyReport {
+    packages: Vec<PackageVerification>,
+}
+
+impl VerifyReport {
+    pub fn packages(&self) -> &[PackageVerification] {
+        &self.packages
+    }
+
+    /// Returns true if no issues are found in all packages.
+    pub fn is_ok(&self) -> bool {
+        self.packages.iter().all(PackageVerification::is_ok)
+    }
+}
+
+impl<IO: ProjectIo> UnityProject<IO> {
+    /// Verifies every locked package is installed as is.
+    ///
+    /// This checks the version in `package.json`, and compares the files in the package folder
+    /// with the zip file of the package cached in the environment. The cached zip file is also
+    /// checked with `zipSHA256` in the repository if the repository provides it.
+    pub async fn verify_packages<Env: PackageCollection + EnvironmentIoHolder>(
+        &self,
+        env: &Env,
+    ) -> io::Result<VerifyReport> {
+        let mut packages = vec![];
+
+        for locked in self.locked_packages() {
+            let name = locked.name();
+            let version = locked.version();
+            let mut issues = vec![];
+
+            match self.installed_packages.get(name) {
+                None => issues.push(PackageIssue::MissingPackageJson),
+                Some(installed) if installed.version() != version => {
+                    issues.push(PackageIssue::VersionMismatch {
+                        installed: installed.version().clone(),
+                    });
+                }
+                Some(_) => {}
+            }
+
+            let expected_hash = env
+                .find_package_by_name(name, VersionSelector::specific_version(version))
+                .and_then(|x| x.package_json().zip_sha_256().map(str::to_ascii_lowercase));
+            if let Some(expected) = expected_hash {
+                if let Some(actual) = cached_zip_hash(env.io(), name, version).await? {
+                    if actual != expected {
+                        issues.push(PackageIssue::ZipHashMismatch {
+                            expected: expected.into(),
+                            actual: actual.into(),
+                        });
+                    }
+                }
+            }
+
+            let diff = compare_with_cached_zip(&self.io, env.io(), name, version).await?;
+            let files_checked = diff.is_some();
+            if !files_checked {
+                issues.push(PackageIssue::FilesNotChecked);
+            }
+            if let Some(diff) = diff {
+                issues.extend(
+                    (diff.modified.into_iter()).map(|path| PackageIssue::ModifiedFile { path }),
+                );
+                issues
+                    .extend((diff.added.into_iter()).map(|path| PackageIssue::ExtraFile { path }));
+                issues.extend(
+                    (diff.missing.into_iter()).map(|path| PackageIssue::MissingFile { path }),
+                );
+            }
+
+            packages.push(PackageVerification {
+                name: name.into(),
+                version: version.clone(),
+                files_checked,
+                issues,
+            });
+        }
+
+        Ok(VerifyReport { packages })
+    }
+}
+
+/// Computes sha256 of the cached zip file, or returns `None` if the zip file is not cached.
+async fn cached_zip_hash(
+    io: &impl EnvironmentIo,
+    name: &str,
+    version: &Version,
+) -> io::Result<Option<String>> {
+    let mut zip_file = match io.open(&package_cache_path(name, version)).await {
+        Ok(zip_file) => zip_file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    let mut hasher = Sha256AsyncWrite::new(io::sink());
+    io::copy(&mut zip_file, &mut hasher).await?;
+
+    Ok(Some(hex::encode(&hasher.finalize().1[..])))
+}
@@ -0,0 +1,105 @@
+use crate::io;
+use crate::io::{DirEntry, IoTrait, SeekFrom};
+use crate::utils::{walk_dir_relative, MapResultExt};
+use async_zip::base::read::seek::ZipFileReader;
+use futures::prelude::*;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+
+/// The differences between the files in the folder and the entries of the zip file.
+///
+/// The paths are `folder` joined with the relative paths of the files.
+pub(crate) struct FilesDiff {
+    pub modified: Vec<Box<Path>>,
+    pub added: Vec<Box<Path>>,
+    pub missing: Vec<Box<Path>>,
+}
+
+impl FilesDiff {
+    pub fn is_empty(&self) -> bool {
+        self.modified.is_empty() && self.added.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// Compares the files in the folder with the entries of the zip file.
+///
+/// The files are compared chunk by chunk so large files are not loaded into memory.
+pub(crate) async fn compare_with_zip(
+    io: &impl IoTrait,
+    mut zip_file: impl AsyncBufRead + AsyncSeek + Unpin,
+    folder: &Path,
+) -> io::Result<FilesDiff> {
+    zip_file.seek(SeekFrom::Start(0)).await?;
+    let mut zip_reader = ZipFileReader::new(zip_file).await.err_mapped()?;
+
+    let mut diff = FilesDiff {
+        modified: vec![],
+        added: vec![],
+        missing: vec![],
+    };
+    let mut files_in_zip = HashSet::new();
+
+    for i in 0..zip_reader.file().entries().len() {
+        let entry = &zip_reader.file().entries()[i];
+        let Ok(filename) = entry.filename().as_str() else {
+            continue;
+        };
+        if filename.ends_with('/') {
+            continue;
+        }
+        let path = folder.join(filename);
+        files_in_zip.insert(path.clone());
+
+        let file = match io.open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                diff.missing.push(path.into_boxed_path());
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        let reader = zip_reader.reader_without_entry(i).await.err_mapped()?;
+
+        if !same_contents(reader, file).await? {
+            diff.modified.push(path.into_boxed_path());
+        }
+    }
+
+    let mut iterator = pin!(walk_dir_relative(io, vec![PathBuf::from(folder)]));
+    while let Some((path, entry)) = iterator.next().await {
+        if entry.file_type().await?.is_file() && !files_in_zip.contains(&path) {
+            diff.added.push(path.into_boxed_path());
+        }
+    }
+
+    diff.modified.sort();
+    diff.added.sort();
+    diff.missing.sort();
+
+    Ok(diff)
+}
+
+async fn same_contents(expected: impl AsyncRead, actual: impl AsyncRead) -> io::Result<bool> {
+    let mut expected = pin!(expected);
+    let mut actual = pin!(actual);
+    let mut expected_buffer = vec![0u8; 8 * 1024];
+    let mut actual_buffer = vec![0u8; 8 * 1024];
+
+    loop {
+        let read = expected.read(&mut expected_buffer).await?;
+        if read == 0 {
+            // the actual file must end here too
+            return Ok(actual.read(&mut actual_buffer[..1]).await? == 0);
+        }
+        match actual.read_exact(&mut actual_buffer[..read]).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+            Err(e) => return Err(e),
+        }
+        if expected_buffer[..read] != actual_buffer[..read] {
+            return Ok(false);
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use crate::io;
-use crate::io::{DirEntry, IoTrait};
+use crate::io::{DirEntry, EnvironmentIo, IoTrait, ProjectIo};
 use futures::prelude::*;
 use std::collections::VecDeque;
 use std::path::PathBuf;
@@ -37,3 +37,48 @@ pub(crate) async fn copy_recursive(
 
     Ok(())
 }
+
+/// Hard links the files in the environment folder into the project, or copies them if hard links
+/// cannot be created.
+pub(crate) async fn link_or_copy_recursive(
+    src_io: &impl EnvironmentIo,
+    src_dir: PathBuf,
+    dst_io: &impl ProjectIo,
+    dst_dir: PathBuf,
+) -> io::Result<()> {
+    // once linking fails, it's likely to fail for all files so we copy the rest
+    let mut copy = false;
+    let mut queue = VecDeque::new();
+    queue.push_front((src_dir, dst_dir));
+
+    while let Some((src_dir, dst_dir)) = queue.pop_back() {
+        let mut iter = src_io.read_dir(&src_dir).await?;
+        dst_io.create_dir_all(&dst_dir).await?;
+        while let Some(entry) = iter.try_next().await? {
+            let file_type = entry.file_type().await?;
+            let src = src_dir.join(entry.file_name());
+            let dst = dst_dir.join(entry.file_name());
+
+            if file_type.is_file() {
+                if !copy {
+                    match dst_io.hard_link(&src_io.resolve(&src), &dst).await {
+                        Ok(()) => continue,
+                        Err(err) => {
+                            log::debug!("hard linking {} failed, copying: {}", src.display(), err);
+                            copy = true;
+                        }
+                    }
+                }
+                let mut src_file = src_io.open(src.as_ref()).await?;
+                let mut dst_file = dst_io.create_new(dst.as_ref()).await?;
+                io::copy(&mut src_file, &mut dst_file).await?;
+            } else if file_type.is_dir() {
+                queue.push_front((src, dst));
+            } else {
+                panic!("unknown file type: none of file, dir, symlink")
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use crate::io;
-use crate::io::ProjectIo;
+use crate::io::IoTrait;
 use crate::io::SeekFrom;
 use crate::utils::MapResultExt;
 use async_zip::base::read::seek::ZipFileReader;
@@ -8,14 +8,17 @@ use std::path::{Component, Path};
 
 pub(crate) async fn extract_zip(
     mut zip_file: impl AsyncBufRead + AsyncSeek + Unpin,
-    io: &impl ProjectIo,
+    io: &impl IoTrait,
     dest_folder: &Path,
+    mut on_progress: impl FnMut(usize, usize),
 ) -> io::Result<()> {
     // extract zip file
     zip_file.seek(SeekFrom::Start(0)).await?;
 
     let mut zip_reader = ZipFileReader::new(zip_file).await.err_mapped()?;
-    for i in 0..zip_reader.file().entries().len() {
+    let total = zip_reader.file().entries().len();
+    on_progress(0, total);
+    for i in 0..total {
         let entry = &zip_reader.file().entries()[i];
         let Some(filename) = entry.filename().as_str().ok() else {
             return Err(io::Error::new(
@@ -41,6 +44,7 @@ pub(crate) async fn extract_zip(
             io::copy(&mut reader, &mut dest_file).await?;
             dest_file.flush().await?;
         }
+        on_progress(i + 1, total);
     }
 
     Ok(())
@@ -1,3 +1,4 @@
+mod compare_zip;
 mod copy_recursive;
 mod crlf_json_formatter;
 mod deup_deserializer;
@@ -18,7 +19,8 @@ use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::task::{ready, Context, Poll};
 
-pub(crate) use copy_recursive::copy_recursive;
+pub(crate) use compare_zip::{compare_with_zip, FilesDiff};
+pub(crate) use copy_recursive::{copy_recursive, link_or_copy_recursive};
 pub(crate) use crlf_json_formatter::to_vec_pretty_os_eol;
 pub(crate) use deup_deserializer::DedupForwarder;
 pub(crate) use extract_zip::extract_zip;
@@ -46,10 +48,46 @@ impl<T> MapResultExt<T> for Result<T, reqwest::Error> {
     type Output = io::Error;
 
     fn err_mapped(self) -> Result<T, Self::Output> {
-        self.map_err(|err| io::Error::new(io::ErrorKind::NotFound, err))
+        self.map_err(|err| {
+            let kind = if err.is_timeout() {
+                io::ErrorKind::TimedOut
+            } else {
+                io::ErrorKind::NotFound
+            };
+            io::Error::new(kind, HttpError(err))
+        })
+    }
+}
+
+/// The error of the http client, displayed with the causes.
+///
+/// The message of [`reqwest::Error`] doesn't include the cause like errors of the proxy or TLS,
+/// which is important to find the problem of the network configuration.
+#[derive(Debug)]
+struct HttpError(reqwest::Error);
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_with_causes(f, &self.0)
+    }
+}
+
+/// Writes the error and the causes of the error separated with `: `.
+pub(crate) fn write_with_causes(
+    f: &mut std::fmt::Formatter<'_>,
+    error: &dyn std::error::Error,
+) -> std::fmt::Result {
+    write!(f, "{error}")?;
+    let mut source = error.source();
+    while let Some(cause) = source {
+        write!(f, ": {cause}")?;
+        source = cause.source();
     }
+    Ok(())
 }
 
+impl std::error::Error for HttpError {}
+
 impl<T> MapResultExt<T> for Result<T, ZipError> {
     type Output = io::Error;
 
@@ -23,6 +23,11 @@ impl<W: AsyncWrite> Sha256AsyncWrite<W> {
         }
     }
 
+    /// Creates the writer continuing to hash after the data already hashed with `hasher`.
+    pub fn with_hasher(inner: W, hasher: Sha256) -> Self {
+        Self { inner, hasher }
+    }
+
     pub fn finalize(self) -> (W, Output<Sha256>) {
         (self.inner, self.hasher.finalize())
     }
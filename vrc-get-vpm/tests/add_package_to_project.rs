@@ -1,13 +1,14 @@
 use common::*;
 use futures::executor::block_on;
+use futures::AsyncReadExt;
 use std::collections::HashSet;
 use std::io;
 use std::path::Path;
 use vrc_get_vpm::io::IoTrait;
-use vrc_get_vpm::unity_project::pending_project_changes::RemoveReason;
+use vrc_get_vpm::unity_project::pending_project_changes::{RejectReason, RemoveReason};
 use vrc_get_vpm::unity_project::{AddPackageErr, AddPackageOperation};
 use vrc_get_vpm::version::Version;
-use vrc_get_vpm::PackageManifest;
+use vrc_get_vpm::{InstallProgress, PackageManifest};
 
 mod common;
 
@@ -885,8 +886,22 @@ fn not_found_err() {
             .expect_err("should fail");
 
         match &err {
-            AddPackageErr::DependencyNotFound { dependency_name } => {
+            AddPackageErr::DependencyNotFound {
+                dependency_name,
+                explanation,
+            } => {
                 assert_eq!(dependency_name.as_ref(), "com.vrchat.base");
+                assert_eq!(explanation.version(), None);
+                assert_eq!(
+                    explanation.requirement_chain(),
+                    &["com.vrchat.avatars".into(), "com.vrchat.base".into()]
+                );
+                assert_eq!(explanation.requirements().len(), 1);
+                let requirement = &explanation.requirements()[0];
+                assert_eq!(requirement.dependent(), Some("com.vrchat.avatars"));
+                assert_eq!(requirement.range().to_string(), "1.0.0");
+                assert!(!requirement.is_satisfied());
+                assert_eq!(explanation.rejected_versions().len(), 0);
             }
             _ => panic!("unexpected error: {:?}", err),
         }
@@ -1065,7 +1080,30 @@ fn conflict_requirements_of_installed_and_installing() {
         assert_eq!(
             base_conflict.conflicting_packages(),
             &["com.vrchat.avatars".into()]
-        )
+        );
+
+        let explanation = base_conflict.explanation().unwrap();
+        assert_eq!(explanation.version(), Some(&Version::new(1, 1, 0)));
+        assert_eq!(
+            explanation.requirement_chain(),
+            &["com.anatawa12.tool".into(), "com.vrchat.base".into()]
+        );
+        let requirements = explanation
+            .requirements()
+            .iter()
+            .map(|x| (x.dependent(), x.range().to_string(), x.is_satisfied()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            requirements,
+            vec![
+                (Some("com.anatawa12.tool"), "^1.1.0".to_string(), true),
+                (Some("com.vrchat.avatars"), "1.0.0".to_string(), false),
+            ]
+        );
+        assert_eq!(explanation.rejected_versions().len(), 1);
+        let rejected = &explanation.rejected_versions()[0];
+        assert_eq!(rejected.version(), &Version::new(1, 0, 0));
+        assert_eq!(rejected.reasons(), &[RejectReason::OutOfRange]);
     })
 }
 
@@ -1177,6 +1215,163 @@ fn conflict_requirements_of_installed_and_installing_related_to_dependencies() {
 
 // endregion
 
+// region backtracking
+
+#[test]
+fn backtrack_dependency_not_found() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new().build().await.unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.anatawa12.tool", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.library", ">=1.0.0"),
+            )
+            .add(
+                PackageManifest::new("com.anatawa12.library", Version::new(2, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.core", "^2.0.0"),
+            )
+            .add(
+                PackageManifest::new("com.anatawa12.library", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.core", "^1.0.0"),
+            )
+            .add(PackageManifest::new(
+                "com.anatawa12.core",
+                Version::new(1, 0, 0),
+            ))
+            .build();
+
+        let tool = collection.get_package("com.anatawa12.tool", Version::new(1, 0, 0));
+        let library = collection.get_package("com.anatawa12.library", Version::new(1, 0, 0));
+        let core = collection.get_package("com.anatawa12.core", Version::new(1, 0, 0));
+
+        let result = project
+            .add_package_request(
+                &collection,
+                &[tool],
+                AddPackageOperation::InstallToDependencies,
+                false,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.package_changes().len(), 3);
+        assert_eq!(result.remove_legacy_folders().len(), 0);
+        assert_eq!(result.remove_legacy_files().len(), 0);
+        assert_eq!(result.conflicts().len(), 0);
+
+        assert_installing_to_both(&result, &tool);
+        assert_installing_to_locked_only(&result, &library);
+        assert_installing_to_locked_only(&result, &core);
+    })
+}
+
+#[test]
+fn backtrack_conflict_with_locked() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.vrchat.avatars", Version::new(1, 0, 0))
+            .add_locked(
+                "com.vrchat.avatars",
+                Version::new(1, 0, 0),
+                &[("com.vrchat.base", "^1.0.0")],
+            )
+            .add_locked("com.vrchat.base", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.vrchat.avatars", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.vrchat.base", "^1.0.0"),
+            )
+            .add(PackageManifest::new(
+                "com.vrchat.base",
+                Version::new(2, 0, 0),
+            ))
+            .add(PackageManifest::new(
+                "com.vrchat.base",
+                Version::new(1, 5, 0),
+            ))
+            .add(PackageManifest::new(
+                "com.vrchat.base",
+                Version::new(1, 0, 0),
+            ))
+            .add(
+                PackageManifest::new("com.anatawa12.tool", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.vrchat.base", ">=1.1.0"),
+            )
+            .build();
+
+        let tool = collection.get_package("com.anatawa12.tool", Version::new(1, 0, 0));
+        let base_1_5_0 = collection.get_package("com.vrchat.base", Version::new(1, 5, 0));
+
+        let result = project
+            .add_package_request(
+                &collection,
+                &[tool],
+                AddPackageOperation::InstallToDependencies,
+                false,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.package_changes().len(), 2);
+        assert_eq!(result.remove_legacy_folders().len(), 0);
+        assert_eq!(result.remove_legacy_files().len(), 0);
+        assert_eq!(result.conflicts().len(), 0);
+
+        assert_installing_to_both(&result, &tool);
+        assert_installing_to_locked_only(&result, &base_1_5_0);
+    })
+}
+
+#[test]
+fn backtrack_keeps_greedy_result_if_no_solution() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new().build().await.unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.anatawa12.tool", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.library", ">=1.0.0"),
+            )
+            .add(
+                PackageManifest::new("com.anatawa12.library", Version::new(2, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.core", "^2.0.0"),
+            )
+            .add(
+                PackageManifest::new("com.anatawa12.library", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.extra", "^1.0.0"),
+            )
+            .build();
+
+        let tool = collection.get_package("com.anatawa12.tool", Version::new(1, 0, 0));
+
+        let err = project
+            .add_package_request(
+                &collection,
+                &[tool],
+                AddPackageOperation::InstallToDependencies,
+                false,
+            )
+            .await
+            .expect_err("should fail");
+
+        match &err {
+            AddPackageErr::DependencyNotFound {
+                dependency_name, ..
+            } => {
+                assert_eq!(dependency_name.as_ref(), "com.anatawa12.core");
+            }
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    })
+}
+
+// endregion
+
 // region rollback on error
 
 #[test]
@@ -1355,6 +1550,384 @@ fn rollback_error_in_error() {
 
 // endregion
 
+// region hold
+
+#[test]
+fn held_package_is_not_upgraded() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new()
+            .add_dependency("com.vrchat.avatars", Version::new(1, 0, 0))
+            .add_locked("com.vrchat.avatars", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        assert!(project.hold_package("com.vrchat.avatars"));
+        assert!(project.is_held("com.vrchat.avatars"));
+
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.vrchat.avatars", Version::new(1, 1, 0))
+                    .add_vpm_dependency("com.vrchat.base", "1.1.0"),
+            )
+            .add(PackageManifest::new(
+                "com.vrchat.base",
+                Version::new(1, 1, 0),
+            ))
+            .build();
+
+        let avatars_package = collection.get_package("com.vrchat.avatars", Version::new(1, 1, 0));
+
+        let result = project
+            .add_package_request(
+                &collection,
+                &[avatars_package],
+                AddPackageOperation::UpgradeLocked,
+                false,
+            )
+            .await
+            .unwrap();
+
+        // neither the held package nor the dependency of the new version should be installed
+        assert_eq!(result.package_changes().len(), 0);
+        assert_eq!(result.conflicts().len(), 1);
+        assert!(result.conflicts()["com.vrchat.avatars"].conflicts_with_hold());
+    })
+}
+
+#[test]
+fn upgrade_requiring_held_package_conflicts() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new()
+            .add_dependency("com.anatawa12.tool", Version::new(1, 0, 0))
+            .add_locked(
+                "com.anatawa12.tool",
+                Version::new(1, 0, 0),
+                &[("com.vrchat.avatars", "^1.0.0")],
+            )
+            .add_locked("com.vrchat.avatars", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        assert!(project.hold_package("com.vrchat.avatars"));
+
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.anatawa12.tool", Version::new(1, 1, 0))
+                    .add_vpm_dependency("com.vrchat.avatars", "^1.1.0"),
+            )
+            .add(PackageManifest::new(
+                "com.vrchat.avatars",
+                Version::new(1, 1, 0),
+            ))
+            .build();
+
+        let tool_package = collection.get_package("com.anatawa12.tool", Version::new(1, 1, 0));
+
+        let result = project
+            .add_package_request(
+                &collection,
+                &[tool_package],
+                AddPackageOperation::UpgradeLocked,
+                false,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.package_changes().len(), 1);
+        assert_installing_to_locked_only(&result, &tool_package);
+        assert!(result.conflicts()["com.vrchat.avatars"].conflicts_with_hold());
+    })
+}
+
+#[test]
+fn dependency_shared_with_held_package_is_installed() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new()
+            .add_dependency("com.anatawa12.tool", Version::new(1, 0, 0))
+            .add_dependency("com.anatawa12.avatar-tool", Version::new(1, 0, 0))
+            .add_locked(
+                "com.anatawa12.tool",
+                Version::new(1, 0, 0),
+                &[("com.vrchat.base", "^1.0.0")],
+            )
+            .add_locked(
+                "com.anatawa12.avatar-tool",
+                Version::new(1, 0, 0),
+                &[("com.vrchat.avatars", "^1.0.0")],
+            )
+            .add_locked(
+                "com.vrchat.avatars",
+                Version::new(1, 0, 0),
+                &[("com.vrchat.base", "^1.0.0")],
+            )
+            .add_locked("com.vrchat.base", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        assert!(project.hold_package("com.vrchat.avatars"));
+
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.anatawa12.tool", Version::new(1, 1, 0))
+                    .add_vpm_dependency("com.vrchat.base", "^1.1.0"),
+            )
+            .add(
+                PackageManifest::new("com.anatawa12.avatar-tool", Version::new(1, 1, 0))
+                    .add_vpm_dependency("com.vrchat.avatars", "^1.1.0"),
+            )
+            .add(
+                PackageManifest::new("com.vrchat.avatars", Version::new(1, 1, 0))
+                    .add_vpm_dependency("com.vrchat.base", "^1.1.0"),
+            )
+            .add(PackageManifest::new(
+                "com.vrchat.base",
+                Version::new(1, 1, 0),
+            ))
+            .build();
+
+        let tool_package = collection.get_package("com.anatawa12.tool", Version::new(1, 1, 0));
+        let avatar_tool_package =
+            collection.get_package("com.anatawa12.avatar-tool", Version::new(1, 1, 0));
+        let base_package = collection.get_package("com.vrchat.base", Version::new(1, 1, 0));
+
+        // the new version of the held package would be resolved before the tool requires base
+        let result = project
+            .add_package_request(
+                &collection,
+                &[tool_package, avatar_tool_package],
+                AddPackageOperation::UpgradeLocked,
+                false,
+            )
+            .await
+            .unwrap();
+
+        // base is required by the tool too so it should be upgraded
+        assert_eq!(result.package_changes().len(), 3);
+        assert_installing_to_locked_only(&result, &tool_package);
+        assert_installing_to_locked_only(&result, &avatar_tool_package);
+        assert_installing_to_locked_only(&result, &base_package);
+        assert!(result.conflicts()["com.vrchat.avatars"].conflicts_with_hold());
+    })
+}
+
+#[test]
+fn hold_is_saved_to_manifest() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new()
+            .add_dependency("com.vrchat.avatars", Version::new(1, 0, 0))
+            .add_locked("com.vrchat.avatars", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        assert!(!project.hold_package("com.vrchat.base"));
+        assert!(project.hold_package("com.vrchat.avatars"));
+        project.save().await.unwrap();
+
+        let mut settings = String::new();
+        project
+            .io()
+            .open("Packages/vrc-get-settings.json".as_ref())
+            .await
+            .unwrap()
+            .read_to_string(&mut settings)
+            .await
+            .unwrap();
+        let settings = serde_json::from_str::<serde_json::Value>(&settings).unwrap();
+        assert_eq!(settings["held"], serde_json::json!(["com.vrchat.avatars"]));
+
+        assert!(project.unhold_package("com.vrchat.avatars"));
+        assert!(!project.unhold_package("com.vrchat.avatars"));
+        assert_eq!(project.held_packages().count(), 0);
+    })
+}
+
+// endregion
+
+// region overrides
+
+#[test]
+fn override_transitive_dependency_range() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new().build().await.unwrap();
+        project.set_override("com.anatawa12.library", ">=2.0.0".parse().unwrap());
+
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.anatawa12.tool", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.library", "^1.0.0"),
+            )
+            .add(PackageManifest::new(
+                "com.anatawa12.library",
+                Version::new(1, 0, 0),
+            ))
+            .add(PackageManifest::new(
+                "com.anatawa12.library",
+                Version::new(2, 0, 0),
+            ))
+            .build();
+
+        let tool = collection.get_package("com.anatawa12.tool", Version::new(1, 0, 0));
+        let library = collection.get_package("com.anatawa12.library", Version::new(2, 0, 0));
+
+        let result = project
+            .add_package_request(
+                &collection,
+                &[tool],
+                AddPackageOperation::InstallToDependencies,
+                false,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.package_changes().len(), 2);
+        assert_eq!(result.conflicts().len(), 0);
+        assert_installing_to_locked_only(&result, &library);
+    })
+}
+
+#[test]
+fn override_resolves_conflict_with_locked() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new()
+            .add_dependency("com.anatawa12.tool", Version::new(1, 0, 0))
+            .add_locked(
+                "com.anatawa12.tool",
+                Version::new(1, 0, 0),
+                &[("com.anatawa12.library", "^1.0.0")],
+            )
+            .add_locked("com.anatawa12.library", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(PackageManifest::new(
+                "com.anatawa12.library",
+                Version::new(2, 0, 0),
+            ))
+            .build();
+
+        let library = collection.get_package("com.anatawa12.library", Version::new(2, 0, 0));
+
+        let result = project
+            .add_package_request(
+                &collection,
+                &[library],
+                AddPackageOperation::UpgradeLocked,
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result.conflicts()["com.anatawa12.library"].conflicting_packages(),
+            &["com.anatawa12.tool".into()]
+        );
+
+        project.set_override("com.anatawa12.library", "^2.0.0".parse().unwrap());
+
+        let result = project
+            .add_package_request(
+                &collection,
+                &[library],
+                AddPackageOperation::UpgradeLocked,
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.conflicts().len(), 0);
+        assert_installing_to_locked_only(&result, &library);
+
+        let active = project.active_overrides();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].dependent(), "com.anatawa12.tool");
+        assert_eq!(active[0].dependency(), "com.anatawa12.library");
+        assert_eq!(active[0].declared().to_string(), "^1.0.0");
+        assert_eq!(active[0].overridden().to_string(), "^2.0.0");
+    })
+}
+
+// endregion
+
+// region progress
+
+#[derive(Default)]
+struct RecordingProgress {
+    installed: std::sync::Mutex<Vec<String>>,
+}
+
+impl InstallProgress for RecordingProgress {
+    fn package_installed(&self, package: &PackageManifest) {
+        let mut installed = self.installed.lock().unwrap();
+        installed.push(package.name().to_string());
+    }
+}
+
+#[test]
+fn progress_reports_installed_packages() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new().build().await.unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.anatawa12.tool", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.library", "^1.0.0"),
+            )
+            .add(PackageManifest::new(
+                "com.anatawa12.library",
+                Version::new(1, 0, 0),
+            ))
+            .build();
+
+        let tool = collection.get_package("com.anatawa12.tool", Version::new(1, 0, 0));
+
+        let env_vfs = VirtualFileSystem::new();
+        for name in ["com.anatawa12.tool", "com.anatawa12.library"] {
+            let path = format!("Packages/{name}/1.0.0/package.json");
+            let json = format!(r#"{{"name":"{name}","version":"1.0.0"}}"#);
+            env_vfs
+                .add_file(path.as_ref(), json.as_bytes())
+                .await
+                .unwrap();
+        }
+        let env = VirtualEnvironment::new(env_vfs);
+
+        let resolve = project
+            .add_package_request(
+                &collection,
+                &[tool],
+                AddPackageOperation::InstallToDependencies,
+                false,
+            )
+            .await
+            .unwrap();
+
+        let progress = RecordingProgress::default();
+        project
+            .apply_pending_changes_with_progress(&env, resolve, &progress)
+            .await
+            .unwrap();
+
+        let mut installed = progress.installed.into_inner().unwrap();
+        installed.sort();
+        assert_eq!(
+            installed,
+            vec!["com.anatawa12.library", "com.anatawa12.tool"]
+        );
+
+        project
+            .io()
+            .metadata("Packages/com.anatawa12.library/package.json".as_ref())
+            .await
+            .unwrap();
+    })
+}
+
+// endregion
+
 // region unlocked
 
 #[test]
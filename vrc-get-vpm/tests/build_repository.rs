@@ -0,0 +1,93 @@
+use crate::common::*;
+use futures::executor::block_on;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use url::Url;
+use vrc_get_vpm::repository::{BuildRepositoryWarning, RemoteRepository};
+use vrc_get_vpm::version::Version;
+
+mod common;
+
+#[test]
+fn build_from_zips() {
+    block_on(async {
+        let tool = build_zip(&[(
+            "package.json",
+            r#"{"name":"com.anatawa12.tool","version":"1.0.0","displayName":"Tool"}"#,
+        )])
+        .await;
+        let tool_duplicated = build_zip(&[
+            (
+                "package.json",
+                r#"{"name":"com.anatawa12.tool","version":"1.0.0"}"#,
+            ),
+            ("Runtime/Tool.cs", ""),
+        ])
+        .await;
+        let library = build_zip(&[(
+            "package.json",
+            r#"{"name":"com.anatawa12.library","version":"2.0.0"}"#,
+        )])
+        .await;
+        let broken = build_zip(&[("README.md", "")]).await;
+
+        let vfs = VirtualFileSystem::new();
+        vfs.add_file("tool-1.0.0.zip".as_ref(), &tool)
+            .await
+            .unwrap();
+        vfs.add_file("tool-1.0.0-copy.zip".as_ref(), &tool_duplicated)
+            .await
+            .unwrap();
+        vfs.add_file("libs/library-2.0.0.zip".as_ref(), &library)
+            .await
+            .unwrap();
+        vfs.add_file("broken.zip".as_ref(), &broken).await.unwrap();
+        vfs.add_file("notes.txt".as_ref(), b"").await.unwrap();
+
+        let base_url = Url::parse("https://example.com/vpm").unwrap();
+        let (repo, warnings) =
+            RemoteRepository::build_from_zips(&vfs, &base_url, "Example", Some("com.example"))
+                .await
+                .unwrap();
+
+        assert_eq!(repo.name(), Some("Example"));
+        assert_eq!(repo.id(), Some("com.example"));
+        assert_eq!(
+            repo.url().unwrap().as_str(),
+            "https://example.com/vpm/index.json"
+        );
+
+        let tool_json = repo
+            .get_package_version("com.anatawa12.tool", &Version::new(1, 0, 0))
+            .unwrap();
+        assert_eq!(
+            tool_json.url().unwrap().as_str(),
+            "https://example.com/vpm/tool-1.0.0-copy.zip"
+        );
+        let expected_hash = hex::encode(Sha256::digest(&tool_duplicated));
+        assert_eq!(tool_json.zip_sha_256(), Some(expected_hash.as_str()));
+
+        let library_json = repo
+            .get_package_version("com.anatawa12.library", &Version::new(2, 0, 0))
+            .unwrap();
+        assert_eq!(
+            library_json.url().unwrap().as_str(),
+            "https://example.com/vpm/libs/library-2.0.0.zip"
+        );
+
+        assert_eq!(warnings.len(), 2, "{warnings:?}");
+        assert!(matches!(
+            &warnings[0],
+            BuildRepositoryWarning::NoPackageJson { path } if path.as_ref() == Path::new("broken.zip")
+        ));
+        assert!(matches!(
+            &warnings[1],
+            BuildRepositoryWarning::DuplicatedVersion { path, .. } if path.as_ref() == Path::new("tool-1.0.0.zip")
+        ));
+
+        // the built repository can be parsed again
+        let json = serde_json::to_value(&repo).unwrap();
+        let reparsed: RemoteRepository = serde_json::from_value(json).unwrap();
+        assert_eq!(reparsed.get_packages().count(), 2);
+    })
+}
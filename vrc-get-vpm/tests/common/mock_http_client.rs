@@ -0,0 +1,101 @@
+use futures::io::Cursor;
+use futures::AsyncRead;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Mutex;
+use url::Url;
+use vrc_get_vpm::{HttpClient, ResumableResponse};
+
+/// The http client serving the resources in memory for testing.
+///
+/// Like the servers supporting range requests with `If-Range`, the rest of the resource is returned
+/// if the download is resumed with the current ETag, otherwise the whole resource is returned.
+pub struct MockHttpClient {
+    resources: Mutex<HashMap<String, Resource>>,
+    requests: Mutex<Vec<(String, Option<u64>)>>,
+}
+
+#[derive(Clone)]
+struct Resource {
+    content: Vec<u8>,
+    etag: Box<str>,
+}
+
+impl MockHttpClient {
+    pub fn new() -> Self {
+        Self {
+            resources: Mutex::new(HashMap::new()),
+            requests: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn add(self, url: &str, content: &[u8], etag: &str) -> Self {
+        self.resources.lock().unwrap().insert(
+            url.into(),
+            Resource {
+                content: content.into(),
+                etag: etag.into(),
+            },
+        );
+        self
+    }
+
+    /// The requested URLs and the offsets of the resumed requests.
+    pub fn requests(&self) -> Vec<(String, Option<u64>)> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn resource(&self, url: &Url, offset: Option<u64>) -> io::Result<Resource> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((url.to_string(), offset));
+        (self.resources.lock().unwrap().get(url.as_str()))
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "404 Not Found"))
+    }
+}
+
+impl HttpClient for MockHttpClient {
+    async fn get(&self, url: &Url, _: &IndexMap<&str, &str>) -> io::Result<impl AsyncRead + Send> {
+        let Resource { content, .. } = self.resource(url, None)?;
+        Ok(Cursor::new(content))
+    }
+
+    async fn get_with_etag(
+        &self,
+        url: &Url,
+        _: &IndexMap<Box<str>, Box<str>>,
+        current_etag: Option<&str>,
+    ) -> io::Result<Option<(impl AsyncRead + Send, Option<Box<str>>)>> {
+        let Resource { content, etag } = self.resource(url, None)?;
+        if current_etag == Some(&etag) {
+            return Ok(None);
+        }
+        Ok(Some((Cursor::new(content), Some(etag))))
+    }
+
+    async fn get_resumable(
+        &self,
+        url: &Url,
+        _: &IndexMap<&str, &str>,
+        resume: Option<(u64, &str)>,
+    ) -> io::Result<ResumableResponse<impl AsyncRead + Send>> {
+        let Resource { content, etag } = self.resource(url, resume.map(|(offset, _)| offset))?;
+        let total = content.len() as u64;
+
+        let offset = match resume {
+            Some((offset, validator)) if validator == etag.as_ref() && offset < total => offset,
+            // the resource is changed (If-Range) or the range is not satisfiable
+            _ => 0,
+        };
+
+        Ok(ResumableResponse {
+            stream: Cursor::new(content[offset as usize..].to_vec()),
+            offset,
+            total: Some(total),
+            validator: Some(etag),
+        })
+    }
+}
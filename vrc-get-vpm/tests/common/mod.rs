@@ -2,11 +2,13 @@
 #![allow(dead_code)]
 #![allow(unused_imports)]
 
+mod mock_http_client;
 mod package_collection;
 mod virtual_environment;
 mod virtual_file_system;
 mod virtual_project_builder;
 
+pub use mock_http_client::MockHttpClient;
 pub use package_collection::PackageCollection;
 pub use package_collection::PackageCollectionBuilder;
 pub use virtual_environment::VirtualEnvironment;
@@ -18,6 +20,9 @@ use vrc_get_vpm::unity_project::PendingProjectChanges;
 use vrc_get_vpm::version::{DependencyRange, Version};
 use vrc_get_vpm::PackageInfo;
 
+use async_zip::base::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+
 pub fn assert_removed(result: &PendingProjectChanges, package: &str, reason: RemoveReason) {
     let package_change = result
         .package_changes()
@@ -95,3 +100,16 @@ pub fn assert_installing_to_dependencies_only(
         .expect("not installing to dependencies");
     assert_eq!(base_range, &DependencyRange::version(version));
 }
+
+/// Creates zip file on memory. The name of directory entries should end with `/`.
+pub async fn build_zip(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut writer = ZipFileWriter::new(futures::io::Cursor::new(Vec::new()));
+    for (name, content) in files {
+        let entry = ZipEntryBuilder::new((*name).into(), Compression::Stored);
+        writer
+            .write_entry_whole(entry, content.as_bytes())
+            .await
+            .unwrap();
+    }
+    writer.close().await.unwrap().into_inner()
+}
@@ -17,6 +17,12 @@ impl PackageCollectionBuilder {
         self
     }
 
+    /// Adds the package at `path` instead of the default path in the Packages folder.
+    pub fn add_at(mut self, json: PackageManifest, path: impl Into<PathBuf>) -> Self {
+        self.packages.push((json, path.into()));
+        self
+    }
+
     pub fn build(self) -> PackageCollection {
         PackageCollection {
             packages: self.packages,
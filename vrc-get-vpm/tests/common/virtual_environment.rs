@@ -1,18 +1,58 @@
-use crate::common::{PackageCollection, VirtualFileSystem};
+use crate::common::{PackageCollection, PackageCollectionBuilder, VirtualFileSystem};
 use indexmap::IndexMap;
 use serde_json::json;
 use vrc_get_vpm::io::{EnvironmentIo, IoTrait};
 use vrc_get_vpm::unity_project::pending_project_changes::Remove;
 use vrc_get_vpm::version::{Version, VersionRange};
-use vrc_get_vpm::{EnvironmentIoHolder, PackageManifest, RemotePackageDownloader, UnityProject};
+use vrc_get_vpm::PackageCollection as _;
+use vrc_get_vpm::{
+    EnvironmentIoHolder, InstallProgress, PackageInfo, PackageManifest, RemotePackageDownloader,
+    UnityProject, VersionSelector,
+};
 
 pub struct VirtualEnvironment {
     vfs: VirtualFileSystem,
+    collection: PackageCollection,
+    package_store: bool,
 }
 
 impl VirtualEnvironment {
     pub fn new(vfs: VirtualFileSystem) -> Self {
-        Self { vfs }
+        Self::with_collection(vfs, PackageCollectionBuilder::new().build())
+    }
+
+    pub fn with_collection(vfs: VirtualFileSystem, collection: PackageCollection) -> Self {
+        Self {
+            vfs,
+            collection,
+            package_store: false,
+        }
+    }
+
+    pub fn with_package_store(self) -> Self {
+        Self {
+            package_store: true,
+            ..self
+        }
+    }
+}
+
+impl vrc_get_vpm::PackageCollection for VirtualEnvironment {
+    fn get_all_packages(&self) -> impl Iterator<Item = PackageInfo<'_>> {
+        self.collection.get_all_packages()
+    }
+
+    fn find_packages(&self, package: &str) -> impl Iterator<Item = PackageInfo<'_>> {
+        self.collection.find_packages(package)
+    }
+
+    fn find_package_by_name(
+        &self,
+        package: &str,
+        package_selector: VersionSelector,
+    ) -> Option<PackageInfo<'_>> {
+        self.collection
+            .find_package_by_name(package, package_selector)
     }
 }
 
@@ -24,16 +64,22 @@ impl EnvironmentIoHolder for VirtualEnvironment {
 }
 
 impl RemotePackageDownloader for VirtualEnvironment {
-    type FileStream = futures::io::Cursor<&'static [u8]>;
+    type FileStream = <VirtualFileSystem as IoTrait>::FileStream;
 
-    fn get_package(
+    /// Returns the zip file cached in the environment folder. Downloading is not supported.
+    async fn get_package(
         &self,
         _repository: &vrc_get_vpm::repository::LocalCachedRepository,
-        _package: &PackageManifest,
-    ) -> impl futures::Future<Output = std::io::Result<Self::FileStream>> + Send {
-        std::future::ready(Err(std::io::Error::new(
-            std::io::ErrorKind::Unsupported,
-            "Remove Access",
-        )))
+        package: &PackageManifest,
+        _progress: &impl InstallProgress,
+    ) -> std::io::Result<Self::FileStream> {
+        let name = package.name();
+        let version = package.version();
+        let path = format!("Repos/{name}/vrc-get-{name}-{version}.zip");
+        self.vfs.open(path.as_ref()).await
+    }
+
+    fn use_package_store(&self) -> bool {
+        self.package_store
     }
 }
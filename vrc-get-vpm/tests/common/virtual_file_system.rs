@@ -9,7 +9,9 @@ use std::pin::Pin;
 use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
 use std::{error, io};
-use vrc_get_vpm::io::{EnvironmentIo, ExitStatus, FileType, IoTrait, Metadata, ProjectIo};
+use vrc_get_vpm::io::{
+    EnvironmentIo, ExitStatus, FileSystemProjectIo, FileType, IoTrait, Metadata, ProjectIo,
+};
 
 pub(crate) use file_stream::*;
 
@@ -28,15 +30,42 @@ where
 /// This struct implements All EnvironmentIo and ProjectIo methods.
 pub struct VirtualFileSystem {
     root: DirectoryEntry,
+    link_source: Option<DirectoryEntry>,
+    environment: bool,
 }
 
 impl VirtualFileSystem {
     pub fn new() -> Self {
         Self {
             root: DirectoryEntry::new(),
+            link_source: None,
+            environment: false,
         }
     }
 
+    /// Creates the file system for the environment.
+    ///
+    /// Like `DefaultEnvironmentIo`, absolute paths like the paths of `file:` URLs are resolved
+    /// from the root. This cannot be used for projects since `ProjectIo` doesn't allow absolute paths.
+    pub fn new_environment() -> Self {
+        Self {
+            environment: true,
+            ..Self::new()
+        }
+    }
+
+    pub fn is_environment(&self) -> bool {
+        self.environment
+    }
+
+    /// Allows hard linking the files in `source`, like the environment, into this file system.
+    ///
+    /// The linked files share the content with the files in `source`.
+    pub fn with_hard_links_from(mut self, source: &VirtualFileSystem) -> Self {
+        self.link_source = Some(source.root.clone());
+        self
+    }
+
     pub async fn add_file(&self, path: &Path, content: &[u8]) -> io::Result<()> {
         let Some((dir_path, last)) = self.resolve2(path)? else {
             return err(IS_DIRECTORY, "is directory");
@@ -73,7 +102,10 @@ impl VirtualFileSystem {
         for x in path.components() {
             match x {
                 Component::Prefix(_) | Component::RootDir => {
-                    panic!("absolute path")
+                    if !self.environment {
+                        panic!("absolute path")
+                    }
+                    result.clear();
                 }
                 Component::CurDir => continue,
                 Component::ParentDir => {
@@ -287,7 +319,47 @@ impl EnvironmentIo for VirtualFileSystem {
     }
 }
 
-impl ProjectIo for VirtualFileSystem {}
+/// The project is at the root of the virtual file system.
+impl FileSystemProjectIo for VirtualFileSystem {
+    fn location(&self) -> &Path {
+        if cfg!(windows) {
+            r"C:\".as_ref()
+        } else {
+            "/".as_ref()
+        }
+    }
+}
+
+impl ProjectIo for VirtualFileSystem {
+    async fn hard_link(&self, original: &Path, link: &Path) -> io::Result<()> {
+        let Some(source) = &self.link_source else {
+            return err(ErrorKind::Unsupported, "hard link");
+        };
+        let Some((original_dir, original_last)) = self.resolve2(original)? else {
+            return err(IS_DIRECTORY, "is directory");
+        };
+        let Some((link_dir, link_last)) = self.resolve2(link)? else {
+            return err(ErrorKind::AlreadyExists, "file exists");
+        };
+
+        let file = source
+            .get_folder(&original_dir)
+            .await?
+            .get(original_last)
+            .await?
+            .into_file()?;
+
+        let link_dir = self.root.get_folder(&link_dir).await?;
+        let mut link_dir = link_dir.backed.lock().unwrap();
+        match link_dir.entry(link_last.to_os_string()) {
+            Entry::Occupied(_) => err(ErrorKind::AlreadyExists, "file exists"),
+            Entry::Vacant(e) => {
+                e.insert(FileSystemEntry::File(file));
+                Ok(())
+            }
+        }
+    }
+}
 
 #[derive(Clone)]
 enum FileSystemEntry {
@@ -298,7 +370,9 @@ enum FileSystemEntry {
 impl FileSystemEntry {
     fn metadata(&self) -> Metadata {
         match self {
-            FileSystemEntry::File(_) => Metadata::file(),
+            FileSystemEntry::File(file) => {
+                Metadata::file().with_len(file.content.lock().unwrap().content.len() as u64)
+            }
             FileSystemEntry::Directory(_) => Metadata::dir(),
         }
     }
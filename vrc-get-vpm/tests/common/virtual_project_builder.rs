@@ -9,6 +9,7 @@ use vrc_get_vpm::{PackageManifest, UnityProject};
 pub struct VirtualProjectBuilder {
     dependencies: IndexMap<String, String>,
     locked: IndexMap<String, (Version, IndexMap<String, VersionRange>)>,
+    linked: IndexMap<String, String>,
     files: IndexMap<String, String>,
     directories: Vec<String>,
 }
@@ -18,6 +19,7 @@ impl VirtualProjectBuilder {
         Self {
             dependencies: IndexMap::new(),
             locked: IndexMap::new(),
+            linked: IndexMap::new(),
             files: IndexMap::new(),
             directories: vec![],
         }
@@ -51,6 +53,11 @@ impl VirtualProjectBuilder {
         self
     }
 
+    pub fn add_linked(&mut self, name: &str, path: &str) -> &mut VirtualProjectBuilder {
+        self.linked.insert(name.into(), path.into());
+        self
+    }
+
     pub fn add_file(
         &mut self,
         path: impl Into<String>,
@@ -79,6 +86,18 @@ impl VirtualProjectBuilder {
     }
 
     pub async fn build(&self) -> std::io::Result<UnityProject<VirtualFileSystem>> {
+        self.build_in(VirtualFileSystem::new()).await
+    }
+
+    pub async fn build_in(
+        &self,
+        fs: VirtualFileSystem,
+    ) -> std::io::Result<UnityProject<VirtualFileSystem>> {
+        assert!(
+            !fs.is_environment(),
+            "the project io doesn't allow absolute paths"
+        );
+
         let vpm_manifest = {
             let mut dependencies = serde_json::Map::new();
             for (dependency, version) in &self.dependencies {
@@ -106,13 +125,24 @@ impl VirtualProjectBuilder {
             })
         };
 
-        let fs = VirtualFileSystem::new();
         fs.add_file(
             "Packages/vpm-manifest.json".as_ref(),
             vpm_manifest.to_string().as_bytes(),
         )
         .await?;
 
+        if !self.linked.is_empty() {
+            let mut linked = serde_json::Map::new();
+            for (name, path) in &self.linked {
+                linked.insert(name.to_string(), json!({ "path": path }));
+            }
+            fs.add_file(
+                "Packages/vrc-get-settings.json".as_ref(),
+                json!({ "linked": linked }).to_string().as_bytes(),
+            )
+            .await?;
+        }
+
         for (name, contents) in &self.files {
             fs.add_file(name.as_ref(), contents.as_bytes()).await?;
         }
@@ -0,0 +1,80 @@
+use indexmap::IndexMap;
+use vrc_get_vpm::repository::{resolve_headers, uses_credential_reference, CredentialError};
+
+fn headers(pairs: &[(&str, &str)]) -> IndexMap<Box<str>, Box<str>> {
+    (pairs.iter())
+        .map(|&(name, value)| (name.into(), value.into()))
+        .collect()
+}
+
+#[test]
+fn plain_headers() {
+    let plain = headers(&[("X-Plain", "value"), ("X-Other", "$HOME {env:HOME}")]);
+
+    assert_eq!(resolve_headers(&plain).unwrap(), plain);
+    assert!(!uses_credential_reference(&plain["X-Other"]));
+}
+
+#[test]
+fn environment_variable() {
+    std::env::set_var("VRC_GET_TEST_CREDENTIAL_TOKEN", "secret");
+    let configured = headers(&[
+        (
+            "Authorization",
+            "Bearer ${env:VRC_GET_TEST_CREDENTIAL_TOKEN}",
+        ),
+        ("X-Plain", "value"),
+    ]);
+
+    let resolved = resolve_headers(&configured).unwrap();
+
+    assert_eq!(resolved["Authorization"].as_ref(), "Bearer secret");
+    assert_eq!(resolved["X-Plain"].as_ref(), "value");
+    // the configured headers are not modified
+    assert_eq!(
+        configured["Authorization"].as_ref(),
+        "Bearer ${env:VRC_GET_TEST_CREDENTIAL_TOKEN}"
+    );
+    assert!(uses_credential_reference(&configured["Authorization"]));
+}
+
+#[test]
+fn missing_environment_variable() {
+    let configured = headers(&[("Authorization", "${env:VRC_GET_TEST_CREDENTIAL_MISSING}")]);
+
+    assert!(matches!(
+        resolve_headers(&configured),
+        Err(CredentialError::MissingEnvironmentVariable(name))
+            if name.as_ref() == "VRC_GET_TEST_CREDENTIAL_MISSING"
+    ));
+}
+
+#[test]
+fn unterminated_reference() {
+    let configured = headers(&[("Authorization", "${env:VRC_GET_TEST_CREDENTIAL_TOKEN")]);
+
+    assert!(matches!(
+        resolve_headers(&configured),
+        Err(CredentialError::Unterminated)
+    ));
+}
+
+#[test]
+fn credential_helper() {
+    let configured = headers(&[("Authorization", "Bearer ${command:echo secret}")]);
+
+    let resolved = resolve_headers(&configured).unwrap();
+
+    assert_eq!(resolved["Authorization"].as_ref(), "Bearer secret");
+    assert!(uses_credential_reference(&configured["Authorization"]));
+}
+
+#[test]
+fn failing_credential_helper() {
+    let configured = headers(&[("Authorization", "${command:exit 3}")]);
+
+    assert!(matches!(
+        resolve_headers(&configured),
+        Err(CredentialError::HelperFailed { .. })
+    ));
+}
@@ -0,0 +1,245 @@
+use crate::common::*;
+use futures::executor::block_on;
+use futures::prelude::*;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use vrc_get_vpm::environment::DownloadPackageErr;
+use vrc_get_vpm::io::IoTrait;
+use vrc_get_vpm::repository::LocalCachedRepository;
+use vrc_get_vpm::{Environment, EnvironmentIoHolder, PackageManifest, RemotePackageDownloader};
+
+mod common;
+
+const URL: &str = "https://example.com/com.anatawa12.tool-1.0.0.zip";
+const ZIP_PATH: &str = "Repos/com.anatawa12.tool/vrc-get-com.anatawa12.tool-1.0.0.zip";
+
+fn sha256(content: &[u8]) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+fn repository() -> LocalCachedRepository {
+    serde_json::from_value(json!({
+        "repo": { "name": "Example", "packages": {} },
+    }))
+    .unwrap()
+}
+
+fn package(zip_sha256: &str) -> PackageManifest {
+    serde_json::from_value(json!({
+        "name": "com.anatawa12.tool",
+        "version": "1.0.0",
+        "url": URL,
+        "zipSHA256": zip_sha256,
+    }))
+    .unwrap()
+}
+
+async fn read(io: &impl IoTrait, path: &str) -> Vec<u8> {
+    let mut content = vec![];
+    io.open(path.as_ref())
+        .await
+        .unwrap()
+        .read_to_end(&mut content)
+        .await
+        .unwrap();
+    content
+}
+
+#[test]
+fn download_matching_hash() {
+    block_on(async {
+        let http = MockHttpClient::new().add(URL, b"package zip", "\"v1\"");
+        let env = Environment::load(Some(http), VirtualFileSystem::new())
+            .await
+            .unwrap();
+
+        let mut zip = vec![];
+        env.get_package(&repository(), &package(&sha256(b"package zip")), &())
+            .await
+            .unwrap()
+            .read_to_end(&mut zip)
+            .await
+            .unwrap();
+
+        assert_eq!(zip, b"package zip");
+        assert_eq!(read(env.io(), ZIP_PATH).await, b"package zip");
+        let sha_file = read(env.io(), &format!("{ZIP_PATH}.sha256")).await;
+        assert!(sha_file.starts_with(sha256(b"package zip").as_bytes()));
+    })
+}
+
+#[test]
+fn mismatched_download_is_moved_aside() {
+    block_on(async {
+        let http = MockHttpClient::new().add(URL, b"tampered zip", "\"v1\"");
+        let env = Environment::load(Some(http), VirtualFileSystem::new())
+            .await
+            .unwrap();
+
+        let err = env
+            .get_package(&repository(), &package(&sha256(b"package zip")), &())
+            .await
+            .err()
+            .expect("download should fail");
+
+        let err = err
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<DownloadPackageErr>())
+            .expect("DownloadPackageErr");
+        let DownloadPackageErr::HashMismatch {
+            expected,
+            actual,
+            moved_to,
+            ..
+        } = err
+        else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(expected.as_ref(), sha256(b"package zip"));
+        assert_eq!(actual.as_ref(), sha256(b"tampered zip"));
+        let mismatched = format!("{ZIP_PATH}.mismatched");
+        assert_eq!(moved_to.as_deref(), Some(Path::new(&mismatched)));
+
+        assert_eq!(read(env.io(), &mismatched).await, b"tampered zip");
+        assert!(!env.io().is_file(ZIP_PATH.as_ref()).await);
+        assert!(
+            !env.io()
+                .is_file(format!("{ZIP_PATH}.sha256").as_ref())
+                .await
+        );
+    })
+}
+
+#[test]
+fn stale_cache_is_not_reused() {
+    block_on(async {
+        let vfs = VirtualFileSystem::new();
+        vfs.add_file(ZIP_PATH.as_ref(), b"old zip").await.unwrap();
+        vfs.add_file(
+            format!("{ZIP_PATH}.sha256").as_ref(),
+            format!(
+                "{} vrc-get-com.anatawa12.tool-1.0.0.zip\n",
+                sha256(b"old zip")
+            )
+            .as_bytes(),
+        )
+        .await
+        .unwrap();
+        let http = MockHttpClient::new().add(URL, b"new zip", "\"v2\"");
+        let env = Environment::load(Some(http), vfs).await.unwrap();
+
+        // the cache is used while zipSHA256 in the repository matches
+        let mut zip = vec![];
+        env.get_package(&repository(), &package(&sha256(b"old zip")), &())
+            .await
+            .unwrap()
+            .read_to_end(&mut zip)
+            .await
+            .unwrap();
+        assert_eq!(zip, b"old zip");
+
+        let mut zip = vec![];
+        env.get_package(&repository(), &package(&sha256(b"new zip")), &())
+            .await
+            .unwrap()
+            .read_to_end(&mut zip)
+            .await
+            .unwrap();
+        assert_eq!(zip, b"new zip");
+        assert_eq!(read(env.io(), ZIP_PATH).await, b"new zip");
+    })
+}
+
+const CONTENT: &[u8] = b"the content of the package zip";
+
+async fn partially_downloaded(part: &[u8], validator: &str) -> VirtualFileSystem {
+    let vfs = VirtualFileSystem::new();
+    vfs.add_file(format!("{ZIP_PATH}.part").as_ref(), part)
+        .await
+        .unwrap();
+    vfs.add_file(
+        format!("{ZIP_PATH}.part.validator").as_ref(),
+        validator.as_bytes(),
+    )
+    .await
+    .unwrap();
+    vfs
+}
+
+async fn download_content(
+    vfs: VirtualFileSystem,
+    etag: &str,
+) -> Environment<MockHttpClient, VirtualFileSystem> {
+    let http = MockHttpClient::new().add(URL, CONTENT, etag);
+    let env = Environment::load(Some(http), vfs).await.unwrap();
+
+    let mut zip = vec![];
+    env.get_package(&repository(), &package(&sha256(CONTENT)), &())
+        .await
+        .unwrap()
+        .read_to_end(&mut zip)
+        .await
+        .unwrap();
+    assert_eq!(zip, CONTENT);
+
+    assert_eq!(read(env.io(), ZIP_PATH).await, CONTENT);
+    assert!(!env.io().is_file(format!("{ZIP_PATH}.part").as_ref()).await);
+    assert!(
+        !env.io()
+            .is_file(format!("{ZIP_PATH}.part.validator").as_ref())
+            .await
+    );
+    env
+}
+
+#[test]
+fn resume_partial_download() {
+    block_on(async {
+        let vfs = partially_downloaded(&CONTENT[..10], "\"v1\"").await;
+        let env = download_content(vfs, "\"v1\"").await;
+
+        assert_eq!(env.http().unwrap().requests(), [(URL.into(), Some(10))]);
+    })
+}
+
+#[test]
+fn restart_download_of_changed_resource() {
+    block_on(async {
+        // the resource is changed after the partial download so If-Range returns the whole
+        let vfs = partially_downloaded(b"the old content", "\"v0\"").await;
+        let env = download_content(vfs, "\"v1\"").await;
+
+        assert_eq!(env.http().unwrap().requests(), [(URL.into(), Some(15))]);
+    })
+}
+
+#[test]
+fn restart_download_of_unsatisfiable_range() {
+    block_on(async {
+        // the partial data is larger than the resource
+        let part = [CONTENT, b" and more"].concat();
+        let vfs = partially_downloaded(&part, "\"v1\"").await;
+        let env = download_content(vfs, "\"v1\"").await;
+
+        assert_eq!(
+            env.http().unwrap().requests(),
+            [(URL.into(), Some(part.len() as u64))]
+        );
+    })
+}
+
+#[test]
+fn restart_download_of_corrupted_partial_data() {
+    block_on(async {
+        // the partial data doesn't match the resource with the same validator
+        let vfs = partially_downloaded(b"corrupted!", "\"v1\"").await;
+        let env = download_content(vfs, "\"v1\"").await;
+
+        // the hash mismatch of the resumed download is retried without resuming
+        assert_eq!(
+            env.http().unwrap().requests(),
+            [(URL.into(), Some(10)), (URL.into(), None)]
+        );
+    })
+}
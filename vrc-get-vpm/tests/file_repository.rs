@@ -0,0 +1,255 @@
+use crate::common::*;
+use futures::executor::block_on;
+use futures::prelude::*;
+use indexmap::IndexMap;
+use itertools::Itertools;
+use serde_json::json;
+use std::path::Path;
+use url::Url;
+use vrc_get_vpm::environment::UpdateRepositoryMode;
+use vrc_get_vpm::io::IoTrait;
+use vrc_get_vpm::repository::{RemoteRepository, SigningKey};
+use vrc_get_vpm::{Environment, EnvironmentIoHolder};
+
+mod common;
+
+fn repository_dir() -> &'static Path {
+    if cfg!(windows) {
+        r"C:\repos\vpm".as_ref()
+    } else {
+        "/repos/vpm".as_ref()
+    }
+}
+
+fn index_json(versions: &[&str]) -> Vec<u8> {
+    let versions = (versions.iter())
+        .map(|&version| {
+            let package = json!({ "name": "com.anatawa12.tool", "version": version });
+            (version.to_owned(), package)
+        })
+        .collect::<serde_json::Map<_, _>>();
+    json!({
+        "name": "File Repository",
+        "packages": { "com.anatawa12.tool": { "versions": versions } },
+    })
+    .to_string()
+    .into_bytes()
+}
+
+async fn write_index(vfs: &VirtualFileSystem, versions: &[&str]) {
+    vfs.create_dir_all(repository_dir()).await.unwrap();
+    vfs.write(&repository_dir().join("index.json"), &index_json(versions))
+        .await
+        .unwrap();
+}
+
+fn versions(repo: &RemoteRepository) -> Vec<String> {
+    (repo.get_versions_of("com.anatawa12.tool"))
+        .map(|x| x.version().to_string())
+        .sorted()
+        .collect()
+}
+
+#[test]
+fn load_directory_url() {
+    block_on(async {
+        let vfs = VirtualFileSystem::new_environment();
+        write_index(&vfs, &["1.0.0"]).await;
+        let url = Url::from_directory_path(repository_dir()).unwrap();
+
+        let repo = RemoteRepository::load_file(&vfs, &url).await.unwrap();
+
+        assert_eq!(repo.name(), Some("File Repository"));
+        assert_eq!(versions(&repo), ["1.0.0"]);
+        // the url of the directory is used as the url of the repository
+        assert_eq!(repo.url(), Some(&url));
+    })
+}
+
+#[test]
+fn unchanged_file_matches_etag() {
+    block_on(async {
+        let vfs = VirtualFileSystem::new_environment();
+        write_index(&vfs, &["1.0.0"]).await;
+        let url = Url::from_file_path(repository_dir().join("index.json")).unwrap();
+
+        let (_, etag) = RemoteRepository::load_file_with_etag(&vfs, &url, None, &[])
+            .await
+            .unwrap()
+            .unwrap();
+        let etag = etag.unwrap();
+        assert!(etag.starts_with("sha256:"));
+
+        let reloaded = RemoteRepository::load_file_with_etag(&vfs, &url, Some(&etag), &[])
+            .await
+            .unwrap();
+        assert!(reloaded.is_none());
+
+        write_index(&vfs, &["1.0.0", "1.1.0"]).await;
+        let (repo, new_etag) = RemoteRepository::load_file_with_etag(&vfs, &url, Some(&etag), &[])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(new_etag.as_deref(), Some(etag.as_ref()));
+        assert_eq!(versions(&repo), ["1.0.0", "1.1.0"]);
+    })
+}
+
+#[test]
+fn load_file_repository_offline() {
+    block_on(async {
+        let vfs = VirtualFileSystem::new_environment();
+        write_index(&vfs, &["1.0.0"]).await;
+        let url = Url::from_directory_path(repository_dir()).unwrap();
+
+        // the file repositories are loaded without http client
+        let mut env = Environment::<reqwest::Client, _>::load(None, vfs)
+            .await
+            .unwrap();
+        env.add_remote_repo(url.clone(), None, IndexMap::new(), vec![])
+            .await
+            .unwrap();
+        env.save().await.unwrap();
+
+        let versions_in_env = |env: &Environment<reqwest::Client, VirtualFileSystem>| {
+            let (_, repo) = (env.get_repos())
+                .find(|(_, repo)| repo.url() == Some(&url))
+                .expect("file repository");
+            versions(repo.repo())
+        };
+
+        env.load_package_infos(UpdateRepositoryMode::Force)
+            .await
+            .unwrap();
+        assert_eq!(versions_in_env(&env), ["1.0.0"]);
+
+        write_index(env.io(), &["1.0.0", "1.1.0"]).await;
+
+        // the cache is used without updating
+        env.load_package_infos(UpdateRepositoryMode::None)
+            .await
+            .unwrap();
+        assert_eq!(versions_in_env(&env), ["1.0.0"]);
+
+        env.load_package_infos(UpdateRepositoryMode::IfOutdated)
+            .await
+            .unwrap();
+        assert_eq!(versions_in_env(&env), ["1.0.0", "1.1.0"]);
+    })
+}
+
+async fn read(io: &impl IoTrait, path: &Path) -> Vec<u8> {
+    let mut content = vec![];
+    io.open(path)
+        .await
+        .unwrap()
+        .read_to_end(&mut content)
+        .await
+        .unwrap();
+    content
+}
+
+async fn write_signature(vfs: &VirtualFileSystem, key: &SigningKey) {
+    let index = read(vfs, &repository_dir().join("index.json")).await;
+    vfs.write(
+        &repository_dir().join("index.json.sig"),
+        key.sign(&index).as_bytes(),
+    )
+    .await
+    .unwrap();
+}
+
+async fn signed_environment(
+    key: &SigningKey,
+) -> (Environment<reqwest::Client, VirtualFileSystem>, Url) {
+    let vfs = VirtualFileSystem::new_environment();
+    write_index(&vfs, &["1.0.0"]).await;
+    write_signature(&vfs, key).await;
+    let url = Url::from_directory_path(repository_dir()).unwrap();
+
+    let mut env = Environment::<reqwest::Client, _>::load(None, vfs)
+        .await
+        .unwrap();
+    env.add_remote_repo(
+        url.clone(),
+        None,
+        IndexMap::new(),
+        vec![key.public_key().into()],
+    )
+    .await
+    .unwrap();
+    env.save().await.unwrap();
+
+    (env, url)
+}
+
+fn cache_path(env: &Environment<reqwest::Client, VirtualFileSystem>) -> Box<Path> {
+    env.get_user_repos()[0].local_path().into()
+}
+
+fn loaded_versions(
+    env: &Environment<reqwest::Client, VirtualFileSystem>,
+    url: &Url,
+) -> Option<Vec<String>> {
+    (env.get_repos())
+        .find(|(_, repo)| repo.url() == Some(url))
+        .map(|(_, repo)| versions(repo.repo()))
+}
+
+#[test]
+fn signed_cache_with_bad_signature_is_removed() {
+    block_on(async {
+        let key = SigningKey::generate().unwrap();
+        let (mut env, url) = signed_environment(&key).await;
+
+        env.load_package_infos(UpdateRepositoryMode::None)
+            .await
+            .unwrap();
+        assert_eq!(loaded_versions(&env, &url).unwrap(), ["1.0.0"]);
+
+        // the index is changed without signing
+        write_index(env.io(), &["1.0.0", "1.1.0"]).await;
+
+        env.load_package_infos(UpdateRepositoryMode::Force)
+            .await
+            .unwrap();
+        assert_eq!(loaded_versions(&env, &url), None);
+        assert!(!env.io().is_file(&cache_path(&env)).await);
+    })
+}
+
+#[test]
+fn cache_verified_with_other_keys_is_verified_again() {
+    block_on(async {
+        let key = SigningKey::generate().unwrap();
+        let (mut env, url) = signed_environment(&key).await;
+
+        // the cache verified with another key
+        let path = cache_path(&env);
+        let mut cache: serde_json::Value =
+            serde_json::from_slice(&read(env.io(), &path).await).unwrap();
+        cache["vrc-get"]["verifiedKeys"] = json!("sha256:other");
+        (env.io()
+            .write(&path, &serde_json::to_vec(&cache).unwrap())
+            .await)
+            .unwrap();
+
+        // the cache is verified again even without updating
+        env.load_package_infos(UpdateRepositoryMode::None)
+            .await
+            .unwrap();
+        assert_eq!(loaded_versions(&env, &url).unwrap(), ["1.0.0"]);
+
+        (env.io()
+            .write(&path, &serde_json::to_vec(&cache).unwrap())
+            .await)
+            .unwrap();
+        write_index(env.io(), &["1.0.0", "1.1.0"]).await;
+
+        // the cache is not used if it cannot be verified
+        env.load_package_infos(UpdateRepositoryMode::None)
+            .await
+            .unwrap();
+        assert_eq!(loaded_versions(&env, &url), None);
+    })
+}
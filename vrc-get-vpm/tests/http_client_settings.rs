@@ -0,0 +1,146 @@
+use crate::common::*;
+use futures::executor::block_on;
+use std::path::PathBuf;
+use std::time::Duration;
+use vrc_get_vpm::environment::{HttpClientSettings, HttpClientSettingsErr};
+
+mod common;
+
+const CERTIFICATE_A: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIBiDCCAS+gAwIBAgIUXr+DrpPxY2NtiRRGfpfZFXsRjt8wCgYIKoZIzj0EAwIw
+GTEXMBUGA1UEAwwOdnJjLWdldCB0ZXN0IGEwIBcNMjYxMDE4MTEyMTM4WhgPMjEy
+NjA5MjQxMTIxMzhaMBkxFzAVBgNVBAMMDnZyYy1nZXQgdGVzdCBhMFkwEwYHKoZI
+zj0CAQYIKoZIzj0DAQcDQgAEzZ3kxwrdhFiehqAFhbHkyM1FmCXVfTmeCNfNJeXZ
+d4ZmFEVYgrBAqr82hFsOIMzrE9VSPp/WaEVOmIyxuIZMRKNTMFEwHQYDVR0OBBYE
+FIydCN4gJGe7lzDt+70JJBSEnahsMB8GA1UdIwQYMBaAFIydCN4gJGe7lzDt+70J
+JBSEnahsMA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwIDRwAwRAIgYU/cWsUS
+5dTUaf3cSoRhg2FEoTl7PlNrenhRyw3QWbICIEh92V3o0VrX2qPDEVqTB85uqhrR
+DjbuK3GVgFZ3742P
+-----END CERTIFICATE-----
+";
+
+const CERTIFICATE_B: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIBijCCAS+gAwIBAgIUZRhzXdAKL4AAUSPHADlSWun/RycwCgYIKoZIzj0EAwIw
+GTEXMBUGA1UEAwwOdnJjLWdldCB0ZXN0IGIwIBcNMjYxMDE4MTEyMTM4WhgPMjEy
+NjA5MjQxMTIxMzhaMBkxFzAVBgNVBAMMDnZyYy1nZXQgdGVzdCBiMFkwEwYHKoZI
+zj0CAQYIKoZIzj0DAQcDQgAEQ4e3FouZL6y4SOpdjz1EAhCvRoEC69KgiWt5YC5g
+If95R8YqtQeTGc2+Smpxa0fM7FHDeoWDgbU9mZCY/RDzUKNTMFEwHQYDVR0OBBYE
+FA8wc5fmUF3EkJi4dR69cfZMeGPVMB8GA1UdIwQYMBaAFA8wc5fmUF3EkJi4dR69
+cfZMeGPVMA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwIDSQAwRgIhAPJEumsA
+pnnOsA11y2u4GRLE+Zg88cbAR7OmlCiiGofOAiEA6lbyMN/WEW8wkuLVdvZlXkpr
+XH5vWv6twzA4mvvOONM=
+-----END CERTIFICATE-----
+";
+
+/// The certificate with the valid base64 but not the valid DER
+const BROKEN_CERTIFICATE: &str = "\
+-----BEGIN CERTIFICATE-----
+AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA
+-----END CERTIFICATE-----
+";
+
+fn write_ca_bundle(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("vrc-get-test-{}-{name}.pem", std::process::id()));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn load_settings(json: &str) -> HttpClientSettings {
+    block_on(async {
+        let vfs = VirtualFileSystem::new();
+        vfs.add_file("vrc-get/settings.json".as_ref(), json.as_bytes())
+            .await
+            .unwrap();
+        HttpClientSettings::load(&vfs).await.unwrap()
+    })
+}
+
+fn settings_with_ca_bundle(name: &str, content: &str) -> HttpClientSettings {
+    let path = write_ca_bundle(name, content);
+    load_settings(&serde_json::json!({ "caBundle": path }).to_string())
+}
+
+#[test]
+fn load_http_client_settings() {
+    let settings = load_settings(
+        r#"{
+            "proxy": "http://proxy.example.com:8080",
+            "noProxy": "localhost,example.com",
+            "caBundle": "/etc/ssl/company.pem",
+            "requestTimeout": 30
+        }"#,
+    );
+
+    assert_eq!(settings.proxy(), Some("http://proxy.example.com:8080"));
+    assert_eq!(settings.no_proxy(), Some("localhost,example.com"));
+    assert_eq!(settings.ca_bundle(), Some("/etc/ssl/company.pem".as_ref()));
+    assert_eq!(settings.request_timeout(), Some(Duration::from_secs(30)));
+}
+
+#[test]
+fn load_http_client_settings_without_settings_file() {
+    let settings = block_on(HttpClientSettings::load(&VirtualFileSystem::new())).unwrap();
+
+    assert_eq!(settings.proxy(), None);
+    assert_eq!(settings.no_proxy(), None);
+    assert_eq!(settings.ca_bundle(), None);
+    assert_eq!(settings.request_timeout(), None);
+}
+
+#[test]
+fn invalid_proxy() {
+    let settings = load_settings(r#"{ "proxy": "http://[invalid" }"#);
+
+    let err = settings
+        .build_client(reqwest::Client::builder())
+        .unwrap_err();
+    assert!(
+        matches!(err, HttpClientSettingsErr::InvalidProxy { .. }),
+        "{err}"
+    );
+}
+
+#[test]
+fn ca_bundle_with_multiple_certificates() {
+    let bundle = format!("{CERTIFICATE_A}\n# comment between the certificates\n{CERTIFICATE_B}");
+    let settings = settings_with_ca_bundle("multiple", &bundle);
+
+    settings.build_client(reqwest::Client::builder()).unwrap();
+}
+
+#[test]
+fn ca_bundle_with_broken_second_certificate() {
+    // the certificates after the first one must be read
+    let bundle = format!("{CERTIFICATE_A}{BROKEN_CERTIFICATE}");
+    let settings = settings_with_ca_bundle("broken-second", &bundle);
+
+    assert!(settings.build_client(reqwest::Client::builder()).is_err());
+}
+
+#[test]
+fn empty_ca_bundle() {
+    let settings = settings_with_ca_bundle("empty", "");
+
+    let err = settings
+        .build_client(reqwest::Client::builder())
+        .unwrap_err();
+    assert!(
+        matches!(err, HttpClientSettingsErr::ReadCaBundle { .. }),
+        "{err}"
+    );
+}
+
+#[test]
+fn missing_ca_bundle() {
+    let settings = load_settings(r#"{ "caBundle": "/nonexistent/vrc-get-ca-bundle.pem" }"#);
+
+    let err = settings
+        .build_client(reqwest::Client::builder())
+        .unwrap_err();
+    assert!(
+        matches!(err, HttpClientSettingsErr::ReadCaBundle { .. }),
+        "{err}"
+    );
+}
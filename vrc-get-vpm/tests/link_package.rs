@@ -0,0 +1,190 @@
+use crate::common::*;
+use futures::executor::block_on;
+use futures::AsyncReadExt;
+use vrc_get_vpm::io::IoTrait;
+use vrc_get_vpm::unity_project::pending_project_changes::RemoveReason;
+use vrc_get_vpm::unity_project::LinkPackageErr;
+use vrc_get_vpm::version::Version;
+use vrc_get_vpm::{PackageManifest, UnityProject};
+
+mod common;
+
+async fn read_json(project: &UnityProject<VirtualFileSystem>, path: &str) -> serde_json::Value {
+    let mut read = String::new();
+    project
+        .io()
+        .open(path.as_ref())
+        .await
+        .unwrap()
+        .read_to_string(&mut read)
+        .await
+        .unwrap();
+    serde_json::from_str(&read).unwrap()
+}
+
+fn user_package_path() -> &'static str {
+    if cfg!(windows) {
+        r"C:\user-packages\com.anatawa12.tool"
+    } else {
+        "/user-packages/com.anatawa12.tool"
+    }
+}
+
+#[test]
+fn link_and_resolve_dependencies() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new().build().await.unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add_at(
+                PackageManifest::new("com.anatawa12.tool", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.library", "^1.0.0"),
+                user_package_path(),
+            )
+            .add(PackageManifest::new(
+                "com.anatawa12.library",
+                Version::new(1, 0, 0),
+            ))
+            .build();
+
+        let tool = collection.get_package("com.anatawa12.tool", Version::new(1, 0, 0));
+        let library = collection.get_package("com.anatawa12.library", Version::new(1, 0, 0));
+
+        project.link_package(tool).unwrap();
+        assert!(project.is_linked("com.anatawa12.tool"));
+        assert!(project.should_resolve());
+
+        project.save().await.unwrap();
+
+        // the reference is relative to the Packages folder
+        let upm_manifest = read_json(&project, "Packages/manifest.json").await;
+        assert_eq!(
+            upm_manifest["dependencies"]["com.anatawa12.tool"],
+            "file:../user-packages/com.anatawa12.tool"
+        );
+        let settings = read_json(&project, "Packages/vrc-get-settings.json").await;
+        assert_eq!(
+            settings["linked"]["com.anatawa12.tool"]["path"],
+            "../user-packages/com.anatawa12.tool"
+        );
+        let vpm_manifest = read_json(&project, "Packages/vpm-manifest.json").await;
+        assert!(vpm_manifest["locked"].get("com.anatawa12.tool").is_none());
+
+        let result = project.resolve_request(&collection).await.unwrap();
+
+        assert_eq!(result.package_changes().len(), 1);
+        assert_installing_to_locked_only(&result, &library);
+    })
+}
+
+#[test]
+fn dependencies_are_read_from_linked_folder() {
+    block_on(async {
+        let vfs = VirtualFileSystem::new();
+        // the linked package is updated to require the new version of the library
+        let package_json = r#"{
+            "name": "com.anatawa12.tool",
+            "version": "1.1.0",
+            "vpmDependencies": { "com.anatawa12.library": "^1.1.0" }
+        }"#;
+        vfs.add_file(
+            "user-packages/com.anatawa12.tool/package.json".as_ref(),
+            package_json.as_bytes(),
+        )
+        .await
+        .unwrap();
+        let project = VirtualProjectBuilder::new()
+            .add_linked("com.anatawa12.tool", "../user-packages/com.anatawa12.tool")
+            .build_in(vfs)
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(PackageManifest::new(
+                "com.anatawa12.library",
+                Version::new(1, 0, 0),
+            ))
+            .add(PackageManifest::new(
+                "com.anatawa12.library",
+                Version::new(1, 1, 0),
+            ))
+            .build();
+        let library = collection.get_package("com.anatawa12.library", Version::new(1, 1, 0));
+
+        assert!(project.should_resolve());
+        let result = project.resolve_request(&collection).await.unwrap();
+
+        assert_eq!(result.package_changes().len(), 1);
+        assert_installing_to_locked_only(&result, &library);
+    })
+}
+
+#[test]
+fn unlink_with_remove() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new()
+            .add_locked("com.anatawa12.library", Version::new(1, 0, 0), &[])
+            .add_package_json(
+                "com.anatawa12.library",
+                r#"{"name":"com.anatawa12.library","version":"1.0.0"}"#,
+            )
+            .build()
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add_at(
+                PackageManifest::new("com.anatawa12.tool", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.library", "^1.0.0"),
+                user_package_path(),
+            )
+            .build();
+        let tool = collection.get_package("com.anatawa12.tool", Version::new(1, 0, 0));
+        project.link_package(tool).unwrap();
+
+        // the linked package depends on the library
+        let result = project
+            .remove_request(&["com.anatawa12.library"])
+            .await
+            .unwrap();
+        assert_eq!(result.conflicts().len(), 1);
+
+        let result = project
+            .remove_request(&["com.anatawa12.tool"])
+            .await
+            .unwrap();
+        assert_removed(&result, "com.anatawa12.tool", RemoveReason::Requested);
+
+        let env = VirtualEnvironment::new(VirtualFileSystem::new());
+        project.apply_pending_changes(&env, result).await.unwrap();
+        project.save().await.unwrap();
+
+        assert!(!project.is_linked("com.anatawa12.tool"));
+        let upm_manifest = read_json(&project, "Packages/manifest.json").await;
+        assert!(upm_manifest["dependencies"]
+            .get("com.anatawa12.tool")
+            .is_none());
+    })
+}
+
+#[test]
+fn deny_linking_installed_package() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new()
+            .add_locked("com.anatawa12.tool", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(PackageManifest::new(
+                "com.anatawa12.tool",
+                Version::new(1, 0, 0),
+            ))
+            .build();
+        let tool = collection.get_package("com.anatawa12.tool", Version::new(1, 0, 0));
+
+        let err = project.link_package(tool).unwrap_err();
+        assert!(matches!(err, LinkPackageErr::AlreadyInstalled { .. }));
+    })
+}
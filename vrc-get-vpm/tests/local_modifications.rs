@@ -0,0 +1,177 @@
+use crate::common::*;
+use futures::executor::block_on;
+use std::path::Path;
+use vrc_get_vpm::unity_project::LocalModificationsErr;
+use vrc_get_vpm::version::Version;
+
+mod common;
+
+const PACKAGE_JSON: &str = r#"{"name":"com.anatawa12.tool","version":"1.0.0"}"#;
+
+async fn cached_environment() -> VirtualEnvironment {
+    let vfs = VirtualFileSystem::new();
+    let zip = build_zip(&[
+        ("package.json", PACKAGE_JSON),
+        ("Runtime/", ""),
+        ("Runtime/Tool.cs", "original"),
+    ])
+    .await;
+    vfs.add_file(
+        "Repos/com.anatawa12.tool/vrc-get-com.anatawa12.tool-1.0.0.zip".as_ref(),
+        &zip,
+    )
+    .await
+    .unwrap();
+    VirtualEnvironment::new(vfs)
+}
+
+#[test]
+fn unmodified_package_can_be_removed() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new()
+            .add_locked("com.anatawa12.tool", Version::new(1, 0, 0), &[])
+            .add_package_json("com.anatawa12.tool", PACKAGE_JSON)
+            .add_file("Packages/com.anatawa12.tool/Runtime/Tool.cs", "original")
+            .build()
+            .await
+            .unwrap();
+        let env = cached_environment().await;
+
+        let result = project
+            .remove_request(&["com.anatawa12.tool"])
+            .await
+            .unwrap();
+
+        let modified = project.find_local_modifications(&env, &result).await;
+        assert!(modified.unwrap().is_empty());
+
+        project.apply_pending_changes(&env, result).await.unwrap();
+    })
+}
+
+#[test]
+fn modified_package_is_not_overwritten() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new()
+            .add_locked("com.anatawa12.tool", Version::new(1, 0, 0), &[])
+            .add_package_json("com.anatawa12.tool", PACKAGE_JSON)
+            .add_file("Packages/com.anatawa12.tool/Runtime/Tool.cs", "hotfix")
+            .add_file("Packages/com.anatawa12.tool/Runtime/Added.cs", "added")
+            .build()
+            .await
+            .unwrap();
+        let env = cached_environment().await;
+
+        let result = project
+            .remove_request(&["com.anatawa12.tool"])
+            .await
+            .unwrap();
+
+        let modified = project
+            .find_local_modifications(&env, &result)
+            .await
+            .unwrap();
+        assert_eq!(modified.len(), 1);
+        assert_eq!(modified[0].name(), "com.anatawa12.tool");
+        assert_eq!(
+            modified[0].modified_files(),
+            &[
+                Path::new("Packages/com.anatawa12.tool/Runtime/Added.cs").into(),
+                Path::new("Packages/com.anatawa12.tool/Runtime/Tool.cs").into(),
+            ]
+        );
+
+        let err = project
+            .apply_pending_changes(&env, result)
+            .await
+            .unwrap_err();
+        let err = err
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<LocalModificationsErr>())
+            .expect("LocalModificationsErr");
+        assert_eq!(err.packages().len(), 1);
+        assert!(project.is_locked("com.anatawa12.tool"));
+
+        // the packages are overwritten if requested
+        let mut result = project
+            .remove_request(&["com.anatawa12.tool"])
+            .await
+            .unwrap();
+        result.set_overwrite_local_modifications(true);
+        project.apply_pending_changes(&env, result).await.unwrap();
+        assert!(!project.is_locked("com.anatawa12.tool"));
+    })
+}
+
+#[test]
+fn package_without_cache_is_not_checked() {
+    block_on(async {
+        let mut project = VirtualProjectBuilder::new()
+            .add_locked("com.anatawa12.tool", Version::new(1, 0, 0), &[])
+            .add_package_json("com.anatawa12.tool", PACKAGE_JSON)
+            .add_file("Packages/com.anatawa12.tool/Runtime/Tool.cs", "hotfix")
+            .build()
+            .await
+            .unwrap();
+        let env = VirtualEnvironment::new(VirtualFileSystem::new());
+
+        let result = project
+            .remove_request(&["com.anatawa12.tool"])
+            .await
+            .unwrap();
+
+        project.apply_pending_changes(&env, result).await.unwrap();
+    })
+}
+
+#[test]
+fn large_file_modified_at_the_end() {
+    block_on(async {
+        let original = "a".repeat(20 * 1024);
+        let modified = format!("{}b", &original[1..]);
+
+        let vfs = VirtualFileSystem::new();
+        let zip = build_zip(&[
+            ("package.json", PACKAGE_JSON),
+            ("Runtime/Large.bytes", &original),
+            ("Runtime/Longer.bytes", &original),
+        ])
+        .await;
+        vfs.add_file(
+            "Repos/com.anatawa12.tool/vrc-get-com.anatawa12.tool-1.0.0.zip".as_ref(),
+            &zip,
+        )
+        .await
+        .unwrap();
+        let env = VirtualEnvironment::new(vfs);
+
+        let project = VirtualProjectBuilder::new()
+            .add_locked("com.anatawa12.tool", Version::new(1, 0, 0), &[])
+            .add_package_json("com.anatawa12.tool", PACKAGE_JSON)
+            .add_file("Packages/com.anatawa12.tool/Runtime/Large.bytes", modified)
+            .add_file(
+                "Packages/com.anatawa12.tool/Runtime/Longer.bytes",
+                format!("{original}a"),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        let result = project
+            .remove_request(&["com.anatawa12.tool"])
+            .await
+            .unwrap();
+
+        let modified = project
+            .find_local_modifications(&env, &result)
+            .await
+            .unwrap();
+        assert_eq!(
+            modified[0].modified_files(),
+            &[
+                Path::new("Packages/com.anatawa12.tool/Runtime/Large.bytes").into(),
+                Path::new("Packages/com.anatawa12.tool/Runtime/Longer.bytes").into(),
+            ]
+        );
+    })
+}
@@ -0,0 +1,93 @@
+use crate::common::*;
+use futures::executor::block_on;
+use futures::prelude::*;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use url::Url;
+use vrc_get_vpm::io::IoTrait;
+use vrc_get_vpm::repository::LocalCachedRepository;
+use vrc_get_vpm::{Environment, PackageInfo, PackageManifest};
+
+mod common;
+
+const ZIP_PATH: &str = "Repos/com.anatawa12.tool/vrc-get-com.anatawa12.tool-1.0.0.zip";
+
+fn package_json() -> Value {
+    json!({
+        "name": "com.anatawa12.tool",
+        "version": "1.0.0",
+        "url": "https://example.com/com.anatawa12.tool-1.0.0.zip",
+        "headers": { "Authorization": "Bearer secret" },
+    })
+}
+
+#[test]
+fn mirror_packages() {
+    block_on(async {
+        let zip = build_zip(&[("package.json", &package_json().to_string())]).await;
+        let sha256 = hex::encode(Sha256::digest(&zip));
+
+        let vfs = VirtualFileSystem::new();
+        vfs.add_file(ZIP_PATH.as_ref(), &zip).await.unwrap();
+        vfs.add_file(
+            format!("{ZIP_PATH}.sha256").as_ref(),
+            format!("{sha256} vrc-get-com.anatawa12.tool-1.0.0.zip\n").as_bytes(),
+        )
+        .await
+        .unwrap();
+        let env = Environment::<reqwest::Client, _>::load(None, vfs)
+            .await
+            .unwrap();
+
+        let repository: LocalCachedRepository = serde_json::from_value(json!({
+            "repo": {
+                "name": "Example",
+                "url": "https://example.com/vpm.json",
+                "packages": {
+                    "com.anatawa12.tool": { "versions": { "1.0.0": package_json() } },
+                },
+            },
+        }))
+        .unwrap();
+        let package: PackageManifest = serde_json::from_value(package_json()).unwrap();
+        let package = PackageInfo::remote(&package, &repository);
+
+        let dest = VirtualFileSystem::new();
+        let base_url = Url::parse("https://mirror.example.com/vpm").unwrap();
+        let mirrored = env
+            .mirror_packages(&[package, package], "Mirror", &dest, &base_url, &())
+            .await
+            .unwrap();
+
+        // the duplicated versions are mirrored once
+        assert_eq!(mirrored, 1);
+
+        let mut copied = vec![];
+        dest.open("packages/com.anatawa12.tool/com.anatawa12.tool-1.0.0.zip".as_ref())
+            .await
+            .unwrap()
+            .read_to_end(&mut copied)
+            .await
+            .unwrap();
+        assert_eq!(copied, zip);
+
+        let mut index = vec![];
+        dest.open("index.json".as_ref())
+            .await
+            .unwrap()
+            .read_to_end(&mut index)
+            .await
+            .unwrap();
+        let index: Value = serde_json::from_slice(&index).unwrap();
+        assert_eq!(index["name"], "Mirror");
+        assert_eq!(index["url"], "https://mirror.example.com/vpm/index.json");
+        let version = &index["packages"]["com.anatawa12.tool"]["versions"]["1.0.0"];
+        assert_eq!(
+            version["url"],
+            "https://mirror.example.com/vpm/packages/com.anatawa12.tool/com.anatawa12.tool-1.0.0.zip"
+        );
+        assert_eq!(version["zipSHA256"], sha256.as_str());
+        // the mirror does not require authentication
+        assert!(version.get("headers").is_none());
+    })
+}
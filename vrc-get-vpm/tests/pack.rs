@@ -0,0 +1,115 @@
+use crate::common::*;
+use futures::executor::block_on;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use url::Url;
+use vrc_get_vpm::version::Version;
+use vrc_get_vpm::PackedPackage;
+
+mod common;
+
+const PACKAGE_JSON: &str =
+    r#"{"name":"com.anatawa12.tool","version":"1.0.0","displayName":"Tool"}"#;
+
+#[test]
+fn pack_package() {
+    block_on(async {
+        let vfs = VirtualFileSystem::new();
+        vfs.add_file("package.json".as_ref(), PACKAGE_JSON.as_bytes())
+            .await
+            .unwrap();
+        vfs.add_file("Runtime/Tool.cs".as_ref(), b"tool")
+            .await
+            .unwrap();
+        vfs.add_file("Runtime/Tool.cs.meta".as_ref(), b"meta")
+            .await
+            .unwrap();
+        vfs.add_file("Samples~/Sample.cs".as_ref(), b"sample")
+            .await
+            .unwrap();
+        vfs.add_file(".git/HEAD".as_ref(), b"ref: refs/heads/master")
+            .await
+            .unwrap();
+        vfs.add_file("Runtime/.DS_Store".as_ref(), b"")
+            .await
+            .unwrap();
+        vfs.add_file("Build/tool-1.0.0.zip".as_ref(), b"")
+            .await
+            .unwrap();
+
+        let packed = PackedPackage::pack(&vfs, &[Path::new("Build")])
+            .await
+            .unwrap();
+
+        assert_eq!(packed.manifest().name(), "com.anatawa12.tool");
+        assert_eq!(packed.manifest().version(), &Version::new(1, 0, 0));
+        let files = (packed.files().iter())
+            .map(|x| x.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files,
+            [
+                "Runtime/Tool.cs",
+                "Runtime/Tool.cs.meta",
+                "Samples~/Sample.cs",
+                "package.json",
+            ]
+        );
+        assert_eq!(
+            packed.zip_sha256(),
+            hex::encode(Sha256::digest(packed.zip()))
+        );
+
+        let url = Url::parse("https://example.com/tool-1.0.0.zip").unwrap();
+        let entry = packed.repository_entry(&url);
+        assert_eq!(entry["displayName"], "Tool");
+        assert_eq!(entry["url"], url.as_str());
+        assert_eq!(entry["zipSHA256"], packed.zip_sha256());
+    })
+}
+
+#[test]
+fn pack_is_reproducible() {
+    block_on(async {
+        let vfs1 = VirtualFileSystem::new();
+        vfs1.add_file("package.json".as_ref(), PACKAGE_JSON.as_bytes())
+            .await
+            .unwrap();
+        vfs1.add_file("Editor/Editor.cs".as_ref(), b"editor")
+            .await
+            .unwrap();
+        vfs1.add_file("Runtime/Tool.cs".as_ref(), b"tool")
+            .await
+            .unwrap();
+
+        // the same files created in another order
+        let vfs2 = VirtualFileSystem::new();
+        vfs2.add_file("Runtime/Tool.cs".as_ref(), b"tool")
+            .await
+            .unwrap();
+        vfs2.add_file("Editor/Editor.cs".as_ref(), b"editor")
+            .await
+            .unwrap();
+        vfs2.add_file("package.json".as_ref(), PACKAGE_JSON.as_bytes())
+            .await
+            .unwrap();
+
+        let packed1 = PackedPackage::pack(&vfs1, &[]).await.unwrap();
+        let packed2 = PackedPackage::pack(&vfs2, &[]).await.unwrap();
+
+        assert_eq!(packed1.zip_sha256(), packed2.zip_sha256());
+        assert_eq!(packed1.zip(), packed2.zip());
+    })
+}
+
+#[test]
+fn pack_invalid_package_json() {
+    block_on(async {
+        let vfs = VirtualFileSystem::new();
+        vfs.add_file("package.json".as_ref(), br#"{"name":"com.anatawa12.tool"}"#)
+            .await
+            .unwrap();
+
+        assert!(PackedPackage::pack(&vfs, &[]).await.is_err());
+    })
+}
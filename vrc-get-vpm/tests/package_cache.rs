@@ -0,0 +1,190 @@
+use crate::common::VirtualFileSystem;
+use futures::executor::block_on;
+use std::path::Path;
+use vrc_get_vpm::environment::CacheCleanOptions;
+use vrc_get_vpm::version::Version;
+use vrc_get_vpm::Environment;
+
+mod common;
+
+async fn environment(files: &[(&str, usize)]) -> Environment<reqwest::Client, VirtualFileSystem> {
+    let vfs = VirtualFileSystem::new();
+    for &(path, size) in files {
+        vfs.add_file(Path::new(path), &vec![0; size]).await.unwrap();
+    }
+    Environment::load(None, vfs).await.unwrap()
+}
+
+fn versions(env_cached: &[vrc_get_vpm::environment::CachedPackage]) -> Vec<String> {
+    (env_cached.iter())
+        .map(|x| format!("{}@{}", x.name(), x.version()))
+        .collect()
+}
+
+#[test]
+fn list_cached_packages() {
+    block_on(async {
+        let env = environment(&[
+            (
+                "Repos/com.anatawa12.package/vrc-get-com.anatawa12.package-1.0.0.zip",
+                100,
+            ),
+            (
+                "Repos/com.anatawa12.package/vrc-get-com.anatawa12.package-1.0.0.zip.sha256",
+                64,
+            ),
+            (
+                "Repos/com.anatawa12.package/vrc-get-com.anatawa12.package-1.1.0-beta.1.zip",
+                200,
+            ),
+            (
+                "Repos/com.anatawa12.package/vrc-get-com.anatawa12.package-2.0.0.zip.part",
+                50,
+            ),
+            ("Repos/com.anatawa12.package/unrelated.txt", 10),
+            (
+                "Repos/com.anatawa12.other/vrc-get-com.anatawa12.other-0.1.0.zip",
+                300,
+            ),
+            ("Repos/vrc-curated.json", 1000),
+        ])
+        .await;
+
+        let cached = env.get_cached_packages().await.unwrap();
+
+        assert_eq!(
+            versions(&cached),
+            [
+                "com.anatawa12.other@0.1.0",
+                "com.anatawa12.package@1.0.0",
+                "com.anatawa12.package@1.1.0-beta.1",
+                "com.anatawa12.package@2.0.0",
+            ]
+        );
+        assert_eq!(cached[1].size(), 164);
+        assert_eq!(cached[1].files().len(), 2);
+        assert!(cached[1].is_downloaded());
+        assert!(!cached[3].is_downloaded());
+    })
+}
+
+#[test]
+fn no_cache() {
+    block_on(async {
+        let env = environment(&[]).await;
+
+        assert!(env.get_cached_packages().await.unwrap().is_empty());
+    })
+}
+
+#[test]
+fn keep_versions_and_latest() {
+    block_on(async {
+        let env = environment(&[
+            (
+                "Repos/com.anatawa12.package/vrc-get-com.anatawa12.package-1.0.0.zip",
+                100,
+            ),
+            (
+                "Repos/com.anatawa12.package/vrc-get-com.anatawa12.package-1.1.0.zip",
+                100,
+            ),
+            (
+                "Repos/com.anatawa12.package/vrc-get-com.anatawa12.package-1.2.0.zip",
+                100,
+            ),
+            (
+                "Repos/com.anatawa12.package/vrc-get-com.anatawa12.package-2.0.0.zip.part",
+                100,
+            ),
+        ])
+        .await;
+        let cached = env.get_cached_packages().await.unwrap();
+        let locked = Version::new(1, 0, 0);
+
+        let options = CacheCleanOptions::new()
+            .keep_versions([("com.anatawa12.package", &locked)])
+            .keep_latest(1);
+        let selected = options
+            .select(&cached)
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // the leftovers of the interrupted download are not kept as the latest
+        assert_eq!(
+            versions(&selected),
+            ["com.anatawa12.package@1.1.0", "com.anatawa12.package@2.0.0"]
+        );
+    })
+}
+
+#[test]
+fn max_size() {
+    block_on(async {
+        let env = environment(&[
+            (
+                "Repos/com.anatawa12.package/vrc-get-com.anatawa12.package-1.0.0.zip",
+                100,
+            ),
+            (
+                "Repos/com.anatawa12.package/vrc-get-com.anatawa12.package-1.1.0.zip",
+                100,
+            ),
+            (
+                "Repos/com.anatawa12.package/vrc-get-com.anatawa12.package-2.0.0.zip.part",
+                50,
+            ),
+        ])
+        .await;
+        let cached = env.get_cached_packages().await.unwrap();
+
+        let selected = CacheCleanOptions::new()
+            .max_size(150)
+            .select(&cached)
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // the leftovers are removed first
+        assert_eq!(
+            versions(&selected),
+            ["com.anatawa12.package@1.0.0", "com.anatawa12.package@2.0.0"]
+        );
+    })
+}
+
+#[test]
+fn clean_package_cache() {
+    block_on(async {
+        let env = environment(&[
+            (
+                "Repos/com.anatawa12.package/vrc-get-com.anatawa12.package-1.0.0.zip",
+                100,
+            ),
+            (
+                "Repos/com.anatawa12.package/vrc-get-com.anatawa12.package-1.0.0.zip.sha256",
+                64,
+            ),
+            (
+                "Repos/com.anatawa12.other/vrc-get-com.anatawa12.other-0.1.0.zip",
+                300,
+            ),
+        ])
+        .await;
+
+        let options = CacheCleanOptions::new().keep_latest(1);
+        assert!(env.clean_package_cache(&options).await.unwrap().is_empty());
+
+        let removed = env
+            .clean_package_cache(&CacheCleanOptions::new())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            versions(&removed),
+            ["com.anatawa12.other@0.1.0", "com.anatawa12.package@1.0.0"]
+        );
+        assert!(env.get_cached_packages().await.unwrap().is_empty());
+    })
+}
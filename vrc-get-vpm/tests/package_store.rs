@@ -0,0 +1,233 @@
+use crate::common::*;
+use futures::executor::block_on;
+use futures::prelude::*;
+use std::path::Path;
+use vrc_get_vpm::io::{DirEntry, IoTrait};
+use vrc_get_vpm::repository::LocalCachedRepository;
+use vrc_get_vpm::unity_project::AddPackageOperation;
+use vrc_get_vpm::version::Version;
+use vrc_get_vpm::{Environment, EnvironmentIoHolder, PackageInfo, PackageManifest, UnityProject};
+
+mod common;
+
+const PACKAGE_JSON: &str = r#"{"name":"com.anatawa12.tool","version":"1.0.0"}"#;
+const STORED_VERSION: &str = "vrc-get/package-store/com.anatawa12.tool/1.0.0";
+
+async fn cached_environment() -> VirtualEnvironment {
+    let vfs = VirtualFileSystem::new();
+    let zip = build_zip(&[
+        ("package.json", PACKAGE_JSON),
+        ("Runtime/", ""),
+        ("Runtime/Tool.cs", "original"),
+    ])
+    .await;
+    vfs.add_file(
+        "Repos/com.anatawa12.tool/vrc-get-com.anatawa12.tool-1.0.0.zip".as_ref(),
+        &zip,
+    )
+    .await
+    .unwrap();
+    VirtualEnvironment::new(vfs).with_package_store()
+}
+
+async fn install(project: &mut UnityProject<VirtualFileSystem>, env: &VirtualEnvironment) {
+    let repository: LocalCachedRepository =
+        serde_json::from_str(r#"{"repo":{"packages":{}}}"#).unwrap();
+    let package: PackageManifest = serde_json::from_str(PACKAGE_JSON).unwrap();
+
+    let result = project
+        .add_package_request(
+            env,
+            &[PackageInfo::remote(&package, &repository)],
+            AddPackageOperation::InstallToDependencies,
+            false,
+        )
+        .await
+        .unwrap();
+    project.apply_pending_changes(env, result).await.unwrap();
+    project.save().await.unwrap();
+}
+
+async fn read_to_string(io: &impl IoTrait, path: &str) -> String {
+    let mut read = String::new();
+    io.open(path.as_ref())
+        .await
+        .unwrap()
+        .read_to_string(&mut read)
+        .await
+        .unwrap();
+    read
+}
+
+#[test]
+fn install_through_package_store() {
+    block_on(async {
+        let env = cached_environment().await;
+        let mut first = VirtualProjectBuilder::new().build().await.unwrap();
+        let mut second = VirtualProjectBuilder::new().build().await.unwrap();
+
+        install(&mut first, &env).await;
+        install(&mut second, &env).await;
+
+        // the virtual file system doesn't support hard links so the files are copied
+        for project in [&first, &second] {
+            assert_eq!(
+                read_to_string(project.io(), "Packages/com.anatawa12.tool/Runtime/Tool.cs").await,
+                "original"
+            );
+        }
+
+        // the package is extracted once into the folder named with the sha256 of the zip file
+        let mut extracted = env.io().read_dir(STORED_VERSION.as_ref()).await.unwrap();
+        let sha256 = extracted.try_next().await.unwrap().unwrap().file_name();
+        assert_eq!(sha256.len(), 64);
+        assert!(extracted.try_next().await.unwrap().is_none());
+        let stored = Path::new(STORED_VERSION)
+            .join(sha256)
+            .join("Runtime/Tool.cs");
+        assert_eq!(
+            read_to_string(env.io(), stored.to_str().unwrap()).await,
+            "original"
+        );
+    })
+}
+
+#[test]
+fn install_with_hard_links() {
+    block_on(async {
+        let env = cached_environment().await;
+        let mut first = VirtualProjectBuilder::new()
+            .build_in(VirtualFileSystem::new().with_hard_links_from(env.io()))
+            .await
+            .unwrap();
+        let mut second = VirtualProjectBuilder::new()
+            .build_in(VirtualFileSystem::new().with_hard_links_from(env.io()))
+            .await
+            .unwrap();
+
+        install(&mut first, &env).await;
+
+        // modifying the linked file in place modifies the stored file
+        first
+            .io()
+            .write(
+                "Packages/com.anatawa12.tool/Runtime/Tool.cs".as_ref(),
+                b"modified",
+            )
+            .await
+            .unwrap();
+        let mut extracted = env.io().read_dir(STORED_VERSION.as_ref()).await.unwrap();
+        let sha256 = extracted.try_next().await.unwrap().unwrap().file_name();
+        let stored = Path::new(STORED_VERSION)
+            .join(sha256)
+            .join("Runtime/Tool.cs");
+        assert_eq!(
+            read_to_string(env.io(), stored.to_str().unwrap()).await,
+            "modified"
+        );
+
+        // the modified package in the store is not reused
+        install(&mut second, &env).await;
+
+        assert_eq!(
+            read_to_string(second.io(), "Packages/com.anatawa12.tool/Runtime/Tool.cs").await,
+            "original"
+        );
+        assert_eq!(
+            read_to_string(env.io(), stored.to_str().unwrap()).await,
+            "original"
+        );
+        assert_eq!(
+            read_to_string(first.io(), "Packages/com.anatawa12.tool/Runtime/Tool.cs").await,
+            "modified"
+        );
+    })
+}
+
+#[test]
+fn remove_package_installed_through_package_store() {
+    block_on(async {
+        let env = cached_environment().await;
+        let mut project = VirtualProjectBuilder::new().build().await.unwrap();
+        install(&mut project, &env).await;
+
+        let result = project
+            .remove_request(&["com.anatawa12.tool"])
+            .await
+            .unwrap();
+        project.apply_pending_changes(&env, result).await.unwrap();
+
+        assert!(
+            !project
+                .io()
+                .is_dir("Packages/com.anatawa12.tool".as_ref())
+                .await
+        );
+        assert!(env.io().is_dir(STORED_VERSION.as_ref()).await);
+    })
+}
+
+#[test]
+fn prune_package_store() {
+    block_on(async {
+        let vfs = VirtualFileSystem::new();
+        for version in ["1.0.0", "1.1.0"] {
+            let path = format!(
+                "vrc-get/package-store/com.anatawa12.tool/{version}/{}/package.json",
+                "0".repeat(64)
+            );
+            vfs.add_file(path.as_ref(), b"{}").await.unwrap();
+        }
+        let env = Environment::<reqwest::Client, _>::load(None, vfs)
+            .await
+            .unwrap();
+        let kept = Version::new(1, 1, 0);
+
+        let removed = env
+            .prune_package_store([("com.anatawa12.tool", &kept)])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            removed,
+            [("com.anatawa12.tool".into(), Version::new(1, 0, 0))]
+        );
+        assert_eq!(
+            env.get_stored_packages().await.unwrap(),
+            [("com.anatawa12.tool".into(), kept)]
+        );
+    })
+}
+
+#[test]
+fn deny_storing_package_with_invalid_name() {
+    block_on(async {
+        let package_json = r#"{"name":"../escape","version":"1.0.0"}"#;
+        let vfs = VirtualFileSystem::new();
+        let zip = build_zip(&[("package.json", package_json)]).await;
+        vfs.add_file("Repos/../escape/vrc-get-../escape-1.0.0.zip".as_ref(), &zip)
+            .await
+            .unwrap();
+        let env = VirtualEnvironment::new(vfs).with_package_store();
+        let mut project = VirtualProjectBuilder::new().build().await.unwrap();
+
+        let repository: LocalCachedRepository =
+            serde_json::from_str(r#"{"repo":{"packages":{}}}"#).unwrap();
+        let package: PackageManifest = serde_json::from_str(package_json).unwrap();
+        let result = project
+            .add_package_request(
+                &env,
+                &[PackageInfo::remote(&package, &repository)],
+                AddPackageOperation::InstallToDependencies,
+                false,
+            )
+            .await
+            .unwrap();
+        project
+            .apply_pending_changes(&env, result)
+            .await
+            .expect_err("the package name escapes the store");
+
+        assert!(!env.io().is_dir("vrc-get/escape".as_ref()).await);
+    })
+}
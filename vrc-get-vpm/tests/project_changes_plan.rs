@@ -0,0 +1,154 @@
+use crate::common::*;
+use futures::executor::block_on;
+use vrc_get_vpm::unity_project::{AddPackageOperation, LoadPlanErr, ProjectChangesPlan};
+use vrc_get_vpm::version::Version;
+use vrc_get_vpm::PackageManifest;
+
+mod common;
+
+fn manifest_with_hash(name: &str, version: &str, hash: &str) -> PackageManifest {
+    serde_json::from_value(serde_json::json!({
+        "name": name,
+        "version": version,
+        "zipSHA256": hash,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn plan_round_trip() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new().build().await.unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(
+                PackageManifest::new("com.anatawa12.tool", Version::new(1, 0, 0))
+                    .add_vpm_dependency("com.anatawa12.library", "^1.0.0"),
+            )
+            .add(manifest_with_hash("com.anatawa12.library", "1.0.0", "aaaa"))
+            .build();
+
+        let tool = collection.get_package("com.anatawa12.tool", Version::new(1, 0, 0));
+        let library = collection.get_package("com.anatawa12.library", Version::new(1, 0, 0));
+
+        let changes = project
+            .add_package_request(
+                &collection,
+                &[tool],
+                AddPackageOperation::InstallToDependencies,
+                false,
+            )
+            .await
+            .unwrap();
+
+        let json = serde_json::to_string(&changes.to_plan(&project)).unwrap();
+        let plan: ProjectChangesPlan = serde_json::from_str(&json).unwrap();
+
+        let restored = plan.into_changes(&collection, &project).unwrap();
+
+        assert_eq!(restored.package_changes().len(), 2);
+        assert_eq!(restored.remove_legacy_folders().len(), 0);
+        assert_eq!(restored.remove_legacy_files().len(), 0);
+        assert_eq!(restored.conflicts().len(), 0);
+
+        assert_installing_to_both(&restored, &tool);
+        assert_installing_to_locked_only(&restored, &library);
+    })
+}
+
+#[test]
+fn plan_with_changed_hash() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new().build().await.unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(manifest_with_hash("com.anatawa12.library", "1.0.0", "aaaa"))
+            .build();
+
+        let library = collection.get_package("com.anatawa12.library", Version::new(1, 0, 0));
+
+        let plan = project
+            .add_package_request(
+                &collection,
+                &[library],
+                AddPackageOperation::InstallToDependencies,
+                false,
+            )
+            .await
+            .unwrap()
+            .to_plan(&project);
+
+        let changed = PackageCollectionBuilder::new()
+            .add(manifest_with_hash("com.anatawa12.library", "1.0.0", "bbbb"))
+            .build();
+
+        let err = plan
+            .clone()
+            .into_changes(&changed, &project)
+            .expect_err("should fail");
+        match err {
+            LoadPlanErr::HashMismatch {
+                package_name,
+                planned,
+                actual,
+                ..
+            } => {
+                assert_eq!(package_name.as_ref(), "com.anatawa12.library");
+                assert_eq!(planned.as_deref(), Some("aaaa"));
+                assert_eq!(actual.as_deref(), Some("bbbb"));
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+
+        let removed = PackageCollectionBuilder::new().build();
+
+        let err = plan
+            .into_changes(&removed, &project)
+            .expect_err("should fail");
+        assert!(matches!(err, LoadPlanErr::PackageNotFound { .. }));
+    })
+}
+
+#[test]
+fn plan_with_changed_project() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_locked("com.anatawa12.library", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        let collection = PackageCollectionBuilder::new()
+            .add(manifest_with_hash("com.anatawa12.library", "1.1.0", "aaaa"))
+            .build();
+
+        let library = collection.get_package("com.anatawa12.library", Version::new(1, 1, 0));
+
+        let plan = project
+            .add_package_request(
+                &collection,
+                &[library],
+                AddPackageOperation::InstallToDependencies,
+                false,
+            )
+            .await
+            .unwrap()
+            .to_plan(&project);
+
+        let changed = VirtualProjectBuilder::new()
+            .add_locked("com.anatawa12.library", Version::new(1, 0, 1), &[])
+            .build()
+            .await
+            .unwrap();
+
+        let err = plan
+            .into_changes(&collection, &changed)
+            .expect_err("should fail");
+        match err {
+            LoadPlanErr::LockedPackageChanged { package_name } => {
+                assert_eq!(package_name.as_ref(), "com.anatawa12.library");
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+    })
+}
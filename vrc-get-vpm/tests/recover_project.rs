@@ -0,0 +1,196 @@
+use crate::common::*;
+use futures::executor::block_on;
+use futures::AsyncReadExt;
+use vrc_get_vpm::io::IoTrait;
+use vrc_get_vpm::unity_project::RecoverChangesResult;
+use vrc_get_vpm::version::Version;
+use vrc_get_vpm::UnityProject;
+
+mod common;
+
+const JOURNAL_PATH: &str = "Library/vrc-get/changes-journal.json";
+
+async fn read_file(project: &UnityProject<VirtualFileSystem>, path: &str) -> String {
+    let mut read = String::new();
+    project
+        .io()
+        .open(path.as_ref())
+        .await
+        .unwrap()
+        .read_to_string(&mut read)
+        .await
+        .unwrap();
+    read
+}
+
+#[test]
+fn rollback_interrupted_install() {
+    block_on(async {
+        let temp_dir = "Temp/vrc-get/00000000-0000-0000-0000-000000000000";
+        let mut project = VirtualProjectBuilder::new()
+            .add_dependency_range("com.vrchat.avatars", "~3.5.x")
+            .add_locked("com.vrchat.avatars", Version::new(3, 4, 2), &[])
+            // the new version is partially extracted
+            .add_file(
+                "Packages/com.vrchat.avatars/package.json",
+                r#"{"name":"com.vrchat.avatars","version":"3.5.0"}"#,
+            )
+            .add_file(
+                format!("{temp_dir}/com.vrchat.avatars/.__removed_package.json"),
+                r#"{"name":"com.vrchat.avatars","version":"3.4.2"}"#,
+            )
+            .add_file(
+                format!("{temp_dir}/com.vrchat.avatars/Editor/.__removed_content.txt"),
+                "text",
+            )
+            .add_file(
+                JOURNAL_PATH,
+                format!(
+                    r#"{{
+                        "state": "installing",
+                        "tempDir": "{temp_dir}",
+                        "installing": ["com.vrchat.avatars"],
+                        "addLocked": [{{ "name": "com.vrchat.avatars", "version": "3.5.0" }}]
+                    }}"#
+                ),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        assert!(project.has_interrupted_changes());
+
+        let env = VirtualEnvironment::new(VirtualFileSystem::new());
+        let resolve = project.remove_request(&[]).await.unwrap();
+        project
+            .apply_pending_changes(&env, resolve)
+            .await
+            .expect_err("applying changes should fail before recovering");
+
+        let result = project.recover_interrupted_changes().await.unwrap();
+        assert_eq!(result, RecoverChangesResult::RolledBack);
+        assert!(!project.has_interrupted_changes());
+
+        assert_eq!(
+            read_file(&project, "Packages/com.vrchat.avatars/package.json").await,
+            r#"{"name":"com.vrchat.avatars","version":"3.4.2"}"#
+        );
+        assert_eq!(
+            read_file(&project, "Packages/com.vrchat.avatars/Editor/content.txt").await,
+            "text"
+        );
+        assert_eq!(
+            project.get_locked("com.vrchat.avatars").unwrap().version(),
+            &Version::new(3, 4, 2)
+        );
+
+        let io = project.io();
+        io.metadata("Temp".as_ref()).await.expect_err("Temp exists");
+        (io.metadata(JOURNAL_PATH.as_ref()).await).expect_err("journal exists");
+
+        let result = project.recover_interrupted_changes().await.unwrap();
+        assert_eq!(result, RecoverChangesResult::NothingToRecover);
+    })
+}
+
+#[test]
+fn roll_forward_installed_changes() {
+    block_on(async {
+        let temp_dir = "Temp/vrc-get/00000000-0000-0000-0000-000000000000";
+        let mut project = VirtualProjectBuilder::new()
+            .add_dependency_range("com.vrchat.base", "3.4.2")
+            .add_locked("com.vrchat.base", Version::new(3, 4, 2), &[])
+            .add_package_json(
+                "com.vrchat.avatars",
+                r#"{"name":"com.vrchat.avatars","version":"3.4.2"}"#,
+            )
+            .add_file(
+                format!("{temp_dir}/com.vrchat.base/.__removed_package.json"),
+                r#"{"name":"com.vrchat.base","version":"3.4.2"}"#,
+            )
+            .add_file("Assets/LegacyFolder/file.txt", "text")
+            .add_file(
+                JOURNAL_PATH,
+                format!(
+                    r#"{{
+                        "state": "installed",
+                        "tempDir": "{temp_dir}",
+                        "installing": ["com.vrchat.avatars"],
+                        "removing": ["com.vrchat.base"],
+                        "addLocked": [{{ "name": "com.vrchat.avatars", "version": "3.4.2" }}],
+                        "addDependencies": [{{ "name": "com.vrchat.avatars", "version": "3.4.2" }}],
+                        "removeLegacyFolders": ["Assets/LegacyFolder"]
+                    }}"#
+                ),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        assert!(project.has_interrupted_changes());
+
+        let result = project.recover_interrupted_changes().await.unwrap();
+        assert_eq!(result, RecoverChangesResult::RolledForward);
+        assert!(!project.has_interrupted_changes());
+
+        assert_eq!(
+            project.get_locked("com.vrchat.avatars").unwrap().version(),
+            &Version::new(3, 4, 2)
+        );
+        assert!(project.get_dependency("com.vrchat.avatars").is_some());
+        assert!(project.get_locked("com.vrchat.base").is_none());
+        assert!(project.get_dependency("com.vrchat.base").is_none());
+        assert!(project
+            .installed_packages()
+            .any(|(name, _)| name == "com.vrchat.avatars"));
+
+        let manifest = read_file(&project, "Packages/vpm-manifest.json").await;
+        assert!(manifest.contains("com.vrchat.avatars"));
+        assert!(!manifest.contains("com.vrchat.base"));
+
+        let io = project.io();
+        io.metadata("Temp".as_ref()).await.expect_err("Temp exists");
+        (io.metadata("Assets/LegacyFolder".as_ref()).await).expect_err("legacy folder exists");
+        (io.metadata(JOURNAL_PATH.as_ref()).await).expect_err("journal exists");
+    })
+}
+
+#[test]
+fn recover_with_journal_being_replaced() {
+    block_on(async {
+        let temp_dir = "Temp/vrc-get/00000000-0000-0000-0000-000000000000";
+        // interrupted after removing the journal and before renaming the new one
+        let journal_temp_path = format!("{JOURNAL_PATH}.tmp");
+        let mut project = VirtualProjectBuilder::new()
+            .add_package_json(
+                "com.vrchat.avatars",
+                r#"{"name":"com.vrchat.avatars","version":"3.4.2"}"#,
+            )
+            .add_file(
+                journal_temp_path.as_str(),
+                format!(
+                    r#"{{
+                        "state": "installed",
+                        "tempDir": "{temp_dir}",
+                        "installing": ["com.vrchat.avatars"],
+                        "addLocked": [{{ "name": "com.vrchat.avatars", "version": "3.4.2" }}]
+                    }}"#
+                ),
+            )
+            .build()
+            .await
+            .unwrap();
+
+        assert!(project.has_interrupted_changes());
+
+        let result = project.recover_interrupted_changes().await.unwrap();
+        assert_eq!(result, RecoverChangesResult::RolledForward);
+        assert_eq!(
+            project.get_locked("com.vrchat.avatars").unwrap().version(),
+            &Version::new(3, 4, 2)
+        );
+
+        let io = project.io();
+        (io.metadata(journal_temp_path.as_ref()).await).expect_err("journal exists");
+    })
+}
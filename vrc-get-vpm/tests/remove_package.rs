@@ -138,3 +138,73 @@ fn remove_referenced_legacy_package() {
         );
     })
 }
+
+#[test]
+fn cascade_remove_dependents() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.anatawa12.tool", Version::new(1, 0, 0))
+            .add_dependency("com.anatawa12.gists", Version::new(1, 0, 0))
+            .add_locked(
+                "com.anatawa12.tool",
+                Version::new(1, 0, 0),
+                &[("com.anatawa12.library", "^1.0.0")],
+            )
+            .add_locked(
+                "com.anatawa12.library",
+                Version::new(1, 0, 0),
+                &[("com.vrchat.base", "^1.0.0")],
+            )
+            .add_locked("com.vrchat.base", Version::new(1, 0, 0), &[])
+            .add_locked("com.anatawa12.gists", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        let result = project.remove_request(&["com.vrchat.base"]).await.unwrap();
+        assert_eq!(result.conflicts().len(), 1);
+
+        let result = project
+            .remove_cascade_request(&["com.vrchat.base"])
+            .await
+            .unwrap();
+
+        assert_eq!(result.package_changes().len(), 3);
+        assert_eq!(result.conflicts().len(), 0);
+
+        assert_removed(&result, "com.vrchat.base", RemoveReason::Requested);
+        assert_removed(&result, "com.anatawa12.library", RemoveReason::Dependent);
+        assert_removed(&result, "com.anatawa12.tool", RemoveReason::Dependent);
+    })
+}
+
+#[test]
+fn autoremove_unreachable() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.vrchat.avatars", Version::new(1, 0, 0))
+            .add_locked(
+                "com.vrchat.avatars",
+                Version::new(1, 0, 0),
+                &[("com.vrchat.base", "1.0.0")],
+            )
+            .add_locked("com.vrchat.base", Version::new(1, 0, 0), &[])
+            .add_locked(
+                "com.anatawa12.tool",
+                Version::new(1, 0, 0),
+                &[("com.anatawa12.library", "^1.0.0")],
+            )
+            .add_locked("com.anatawa12.library", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        let result = project.autoremove_request().await;
+
+        assert_eq!(result.package_changes().len(), 2);
+        assert_eq!(result.conflicts().len(), 0);
+
+        assert_removed(&result, "com.anatawa12.tool", RemoveReason::Unused);
+        assert_removed(&result, "com.anatawa12.library", RemoveReason::Unused);
+    })
+}
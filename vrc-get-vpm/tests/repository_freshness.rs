@@ -0,0 +1,30 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use vrc_get_vpm::repository::LocalCachedRepository;
+
+fn cached_repository(fetched_at: Option<SystemTime>) -> LocalCachedRepository {
+    let vrc_get = match fetched_at {
+        Some(time) => {
+            let seconds = time.duration_since(UNIX_EPOCH).unwrap().as_secs();
+            format!(r#","vrc-get":{{"fetchedAt":{seconds}}}"#)
+        }
+        None => String::new(),
+    };
+    serde_json::from_str(&format!(r#"{{"repo":{{"packages":{{}}}}{vrc_get}}}"#)).unwrap()
+}
+
+#[test]
+fn fetched_within_ttl() {
+    let repo = cached_repository(Some(SystemTime::now() - Duration::from_secs(60)));
+
+    assert!(repo.is_fetched_within(Duration::from_secs(5 * 60)));
+    assert!(!repo.is_fetched_within(Duration::from_secs(30)));
+    assert!(!repo.is_fetched_within(Duration::ZERO));
+}
+
+#[test]
+fn never_fetched() {
+    let repo = cached_repository(None);
+
+    assert_eq!(repo.fetched_at(), None);
+    assert!(!repo.is_fetched_within(Duration::MAX));
+}
@@ -0,0 +1,111 @@
+use crate::common::*;
+use futures::executor::block_on;
+use vrc_get_vpm::unity_project::{DependencyPath, DependencyPathReason};
+use vrc_get_vpm::version::Version;
+
+mod common;
+
+fn path_names(path: &DependencyPath) -> Vec<&str> {
+    path.steps().iter().map(|x| x.name()).collect()
+}
+
+#[test]
+fn all_paths_to_package() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.anatawa12.tool", Version::new(1, 0, 0))
+            .add_dependency("com.anatawa12.another-tool", Version::new(1, 0, 0))
+            .add_locked(
+                "com.anatawa12.tool",
+                Version::new(1, 0, 0),
+                &[
+                    ("com.anatawa12.library", "^1.0.0"),
+                    ("com.anatawa12.core", "^1.0.0"),
+                ],
+            )
+            .add_locked(
+                "com.anatawa12.another-tool",
+                Version::new(1, 0, 0),
+                &[("com.anatawa12.library", "^1.0.0")],
+            )
+            .add_locked(
+                "com.anatawa12.library",
+                Version::new(1, 0, 0),
+                &[("com.anatawa12.core", "^1.1.0")],
+            )
+            .add_locked("com.anatawa12.core", Version::new(1, 1, 0), &[])
+            .build()
+            .await
+            .unwrap();
+
+        let mut paths = project
+            .dependency_paths_to("com.anatawa12.core")
+            .iter()
+            .map(path_names)
+            .map(|x| x.join(" "))
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        assert_eq!(
+            paths,
+            vec![
+                "com.anatawa12.another-tool com.anatawa12.library com.anatawa12.core",
+                "com.anatawa12.tool com.anatawa12.core",
+                "com.anatawa12.tool com.anatawa12.library com.anatawa12.core",
+            ]
+        );
+
+        let direct = project
+            .dependency_paths_to("com.anatawa12.core")
+            .into_iter()
+            .find(|x| x.steps().len() == 2)
+            .unwrap();
+        assert_eq!(direct.root(), "com.anatawa12.tool");
+        match direct.steps()[0].reason() {
+            DependencyPathReason::Root(range) => assert_eq!(range.to_string(), "1.0.0"),
+            reason => panic!("unexpected reason: {reason:?}"),
+        }
+        match direct.steps()[1].reason() {
+            DependencyPathReason::Dependency(range) => assert_eq!(range.to_string(), "^1.0.0"),
+            reason => panic!("unexpected reason: {reason:?}"),
+        }
+
+        assert!(project
+            .dependency_paths_to("com.anatawa12.not-installed")
+            .is_empty());
+    })
+}
+
+#[test]
+fn path_through_legacy_package() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_dependency("com.anatawa12.package", Version::new(1, 1, 0))
+            .add_locked("com.anatawa12.package", Version::new(1, 1, 0), &[])
+            .add_package_json(
+                "com.anatawa12.package",
+                r#"
+            {
+                "name": "com.anatawa12.package",
+                "version": "1.1.0",
+                "legacyPackages": ["com.anatawa12.legacy-package"]
+            }
+            "#,
+            )
+            .build()
+            .await
+            .unwrap();
+
+        let paths = project.dependency_paths_to("com.anatawa12.legacy-package");
+
+        assert_eq!(paths.len(), 1);
+        assert_eq!(
+            path_names(&paths[0]),
+            vec!["com.anatawa12.package", "com.anatawa12.legacy-package"]
+        );
+        assert!(matches!(
+            paths[0].steps()[1].reason(),
+            DependencyPathReason::Legacy
+        ));
+    })
+}
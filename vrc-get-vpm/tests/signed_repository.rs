@@ -0,0 +1,173 @@
+use futures::executor::block_on;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::io;
+use url::Url;
+use vrc_get_vpm::repository::{RemoteRepository, SignatureError, SigningKey};
+use vrc_get_vpm::HttpClient;
+
+const INDEX_JSON: &str = r#"{"name":"Signed","id":"com.example.signed","packages":{}}"#;
+
+/// The http client serving the files in memory
+struct StaticHttpClient {
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl StaticHttpClient {
+    fn new(files: &[(&str, &[u8])]) -> Self {
+        Self {
+            files: (files.iter())
+                .map(|(url, body)| (url.to_string(), body.to_vec()))
+                .collect(),
+        }
+    }
+
+    fn find(&self, url: &Url) -> io::Result<futures::io::Cursor<Vec<u8>>> {
+        match self.files.get(url.as_str()) {
+            Some(body) => Ok(futures::io::Cursor::new(body.clone())),
+            None => Err(io::Error::new(io::ErrorKind::NotFound, url.as_str())),
+        }
+    }
+}
+
+impl HttpClient for StaticHttpClient {
+    async fn get(
+        &self,
+        url: &Url,
+        _: &IndexMap<&str, &str>,
+    ) -> io::Result<impl futures::AsyncRead + Send> {
+        self.find(url)
+    }
+
+    async fn get_with_etag(
+        &self,
+        url: &Url,
+        _: &IndexMap<Box<str>, Box<str>>,
+        _: Option<&str>,
+    ) -> io::Result<Option<(impl futures::AsyncRead + Send, Option<Box<str>>)>> {
+        Ok(Some((self.find(url)?, None)))
+    }
+}
+
+fn index_url() -> Url {
+    Url::parse("https://example.com/index.json").unwrap()
+}
+
+fn signature_error(error: io::Error) -> SignatureError {
+    *error
+        .into_inner()
+        .expect("no inner error")
+        .downcast::<SignatureError>()
+        .expect("not a signature error")
+}
+
+#[test]
+fn signed_repository() {
+    block_on(async {
+        let key = SigningKey::generate().unwrap();
+        let signature = key.sign(INDEX_JSON.as_bytes());
+        let client = StaticHttpClient::new(&[
+            ("https://example.com/index.json", INDEX_JSON.as_bytes()),
+            ("https://example.com/index.json.sig", signature.as_bytes()),
+        ]);
+
+        let (repo, _) = RemoteRepository::download_with_etag(
+            &client,
+            &index_url(),
+            &IndexMap::new(),
+            None,
+            &[key.public_key().into()],
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(repo.id(), Some("com.example.signed"));
+    })
+}
+
+#[test]
+fn signed_with_untrusted_key() {
+    block_on(async {
+        let key = SigningKey::generate().unwrap();
+        let trusted = SigningKey::generate().unwrap();
+        let signature = key.sign(INDEX_JSON.as_bytes());
+        let client = StaticHttpClient::new(&[
+            ("https://example.com/index.json", INDEX_JSON.as_bytes()),
+            ("https://example.com/index.json.sig", signature.as_bytes()),
+        ]);
+
+        let error = RemoteRepository::download_with_etag(
+            &client,
+            &index_url(),
+            &IndexMap::new(),
+            None,
+            &[trusted.public_key().into()],
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(signature_error(error), SignatureError::Untrusted));
+    })
+}
+
+#[test]
+fn modified_after_signed() {
+    block_on(async {
+        let key = SigningKey::generate().unwrap();
+        let signature = key.sign(INDEX_JSON.as_bytes());
+        let modified = INDEX_JSON.replace("Signed", "Modified");
+        let client = StaticHttpClient::new(&[
+            ("https://example.com/index.json", modified.as_bytes()),
+            ("https://example.com/index.json.sig", signature.as_bytes()),
+        ]);
+
+        let error = RemoteRepository::download_with_etag(
+            &client,
+            &index_url(),
+            &IndexMap::new(),
+            None,
+            &[key.public_key().into()],
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(signature_error(error), SignatureError::Untrusted));
+    })
+}
+
+#[test]
+fn missing_signature() {
+    block_on(async {
+        let key = SigningKey::generate().unwrap();
+        let client =
+            StaticHttpClient::new(&[("https://example.com/index.json", INDEX_JSON.as_bytes())]);
+
+        let error = RemoteRepository::download_with_etag(
+            &client,
+            &index_url(),
+            &IndexMap::new(),
+            None,
+            &[key.public_key().into()],
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(signature_error(error), SignatureError::Missing(_)));
+
+        // the signature is not required without trusted keys
+        RemoteRepository::download_with_etag(&client, &index_url(), &IndexMap::new(), None, &[])
+            .await
+            .unwrap()
+            .unwrap();
+    })
+}
+
+#[test]
+fn signing_key_round_trip() {
+    let key = SigningKey::generate().unwrap();
+    let loaded = SigningKey::from_base64(&key.to_base64()).unwrap();
+
+    assert_eq!(key.public_key(), loaded.public_key());
+    assert_eq!(key.sign(b"index"), loaded.sign(b"index"));
+}
@@ -0,0 +1,182 @@
+use crate::common::*;
+use futures::executor::block_on;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use vrc_get_vpm::unity_project::PackageIssue;
+use vrc_get_vpm::version::Version;
+use vrc_get_vpm::PackageManifest;
+
+mod common;
+
+const PACKAGE_JSON: &str = r#"{"name":"com.anatawa12.tool","version":"1.0.0"}"#;
+
+async fn cached_zip() -> Vec<u8> {
+    build_zip(&[
+        ("package.json", PACKAGE_JSON),
+        ("Runtime/", ""),
+        ("Runtime/Tool.cs", "original"),
+        ("Runtime/Other.cs", "other"),
+    ])
+    .await
+}
+
+async fn environment_with_hash(zip_sha256: &str) -> VirtualEnvironment {
+    let vfs = VirtualFileSystem::new();
+    vfs.add_file(
+        "Repos/com.anatawa12.tool/vrc-get-com.anatawa12.tool-1.0.0.zip".as_ref(),
+        &cached_zip().await,
+    )
+    .await
+    .unwrap();
+
+    let manifest: PackageManifest = serde_json::from_value(serde_json::json!({
+        "name": "com.anatawa12.tool",
+        "version": "1.0.0",
+        "zipSHA256": zip_sha256,
+    }))
+    .unwrap();
+    let collection = PackageCollectionBuilder::new().add(manifest).build();
+
+    VirtualEnvironment::with_collection(vfs, collection)
+}
+
+async fn environment() -> VirtualEnvironment {
+    let hash = hex::encode(Sha256::digest(cached_zip().await));
+    environment_with_hash(&hash).await
+}
+
+#[test]
+fn intact_package() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_locked("com.anatawa12.tool", Version::new(1, 0, 0), &[])
+            .add_package_json("com.anatawa12.tool", PACKAGE_JSON)
+            .add_file("Packages/com.anatawa12.tool/Runtime/Tool.cs", "original")
+            .add_file("Packages/com.anatawa12.tool/Runtime/Other.cs", "other")
+            .build()
+            .await
+            .unwrap();
+        let env = environment().await;
+
+        let report = project.verify_packages(&env).await.unwrap();
+
+        assert!(report.is_ok());
+        assert_eq!(report.packages().len(), 1);
+        assert_eq!(report.packages()[0].name(), "com.anatawa12.tool");
+        assert!(report.packages()[0].files_checked());
+    })
+}
+
+#[test]
+fn edited_package() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_locked("com.anatawa12.tool", Version::new(1, 0, 0), &[])
+            .add_package_json(
+                "com.anatawa12.tool",
+                r#"{"name":"com.anatawa12.tool","version":"1.0.1"}"#,
+            )
+            .add_file("Packages/com.anatawa12.tool/Runtime/Tool.cs", "hotfix")
+            .add_file("Packages/com.anatawa12.tool/Runtime/Added.cs", "added")
+            .build()
+            .await
+            .unwrap();
+        let env = environment().await;
+
+        let report = project.verify_packages(&env).await.unwrap();
+
+        assert!(!report.is_ok());
+        let issues = report.packages()[0].issues();
+        assert_eq!(issues.len(), 5, "{issues:?}");
+        assert!(matches!(
+            &issues[0],
+            PackageIssue::VersionMismatch { installed } if installed == &Version::new(1, 0, 1)
+        ));
+        assert!(matches!(
+            &issues[1],
+            PackageIssue::ModifiedFile { path }
+                if path.as_ref() == Path::new("Packages/com.anatawa12.tool/Runtime/Tool.cs")
+        ));
+        assert!(matches!(
+            &issues[2],
+            PackageIssue::ModifiedFile { path }
+                if path.as_ref() == Path::new("Packages/com.anatawa12.tool/package.json")
+        ));
+        assert!(matches!(
+            &issues[3],
+            PackageIssue::ExtraFile { path }
+                if path.as_ref() == Path::new("Packages/com.anatawa12.tool/Runtime/Added.cs")
+        ));
+        assert!(matches!(
+            &issues[4],
+            PackageIssue::MissingFile { path }
+                if path.as_ref() == Path::new("Packages/com.anatawa12.tool/Runtime/Other.cs")
+        ));
+    })
+}
+
+#[test]
+fn missing_package() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_locked("com.anatawa12.tool", Version::new(1, 0, 0), &[])
+            .build()
+            .await
+            .unwrap();
+        let env = environment().await;
+
+        let report = project.verify_packages(&env).await.unwrap();
+
+        let issues = report.packages()[0].issues();
+        assert!(matches!(&issues[0], PackageIssue::MissingPackageJson));
+        assert_eq!(
+            issues
+                .iter()
+                .filter(|x| matches!(x, PackageIssue::MissingFile { .. }))
+                .count(),
+            3
+        );
+    })
+}
+
+#[test]
+fn cached_zip_not_matching_hash() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_locked("com.anatawa12.tool", Version::new(1, 0, 0), &[])
+            .add_package_json("com.anatawa12.tool", PACKAGE_JSON)
+            .add_file("Packages/com.anatawa12.tool/Runtime/Tool.cs", "original")
+            .add_file("Packages/com.anatawa12.tool/Runtime/Other.cs", "other")
+            .build()
+            .await
+            .unwrap();
+        let env = environment_with_hash(&"0".repeat(64)).await;
+
+        let report = project.verify_packages(&env).await.unwrap();
+
+        let issues = report.packages()[0].issues();
+        assert_eq!(issues.len(), 1);
+        assert!(matches!(&issues[0], PackageIssue::ZipHashMismatch { .. }));
+    })
+}
+
+#[test]
+fn package_without_cache() {
+    block_on(async {
+        let project = VirtualProjectBuilder::new()
+            .add_locked("com.anatawa12.tool", Version::new(1, 0, 0), &[])
+            .add_package_json("com.anatawa12.tool", PACKAGE_JSON)
+            .build()
+            .await
+            .unwrap();
+        let env = VirtualEnvironment::new(VirtualFileSystem::new());
+
+        let report = project.verify_packages(&env).await.unwrap();
+
+        assert!(!report.is_ok());
+        assert!(!report.packages()[0].files_checked());
+        let issues = report.packages()[0].issues();
+        assert_eq!(issues.len(), 1);
+        assert!(matches!(&issues[0], PackageIssue::FilesNotChecked));
+    })
+}
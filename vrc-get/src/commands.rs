@@ -3,25 +3,33 @@ use indexmap::IndexMap;
 use itertools::Itertools;
 
 use log::warn;
+use progress::InstallProgressBar;
 use reqwest::header::{HeaderName, HeaderValue, InvalidHeaderName, InvalidHeaderValue};
 use reqwest::{Client, Url};
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::error::Error as StdError;
 use std::ffi::OsStr;
 use std::fmt::{Debug, Display};
-use std::num::NonZeroU32;
+use std::num::{NonZeroU32, NonZeroUsize};
 use std::path::{Component, Path, PathBuf};
 use std::process::exit;
 use std::str::FromStr;
+use vrc_get_vpm::environment::{HttpClientSettings, UpdateRepositoryMode};
 use vrc_get_vpm::io::{DefaultEnvironmentIo, DefaultProjectIo};
-use vrc_get_vpm::repository::RemoteRepository;
-use vrc_get_vpm::unity_project::pending_project_changes::{PackageChange, RemoveReason};
-use vrc_get_vpm::unity_project::{AddPackageOperation, PendingProjectChanges};
-use vrc_get_vpm::version::Version;
+use vrc_get_vpm::repository::{is_valid_public_key, RemoteRepository, SigningKey};
+use vrc_get_vpm::unity_project::pending_project_changes::{
+    PackageChange, RejectReason, RemoveReason, ResolutionExplanation,
+};
+use vrc_get_vpm::unity_project::{
+    AddPackageErr, AddPackageOperation, DependencyPathReason, PackageIssue, PackageVerification,
+    PendingProjectChanges, ProjectChangesPlan, RecoverChangesResult,
+};
+use vrc_get_vpm::version::{Version, VersionRange};
 use vrc_get_vpm::{
-    PackageCollection, PackageInfo, PackageManifest, UserRepoSetting, VersionSelector,
+    PackageCollection, PackageInfo, PackageManifest, PackedPackage, UserRepoSetting,
+    VersionSelector,
 };
 
 type Environment = vrc_get_vpm::Environment<Client, DefaultEnvironmentIo>;
@@ -80,14 +88,36 @@ struct EnvArgs {
     no_update: bool,
 }
 
+async fn create_client(io: &DefaultEnvironmentIo, offline: bool) -> Option<Client> {
+    if offline {
+        return None;
+    }
+
+    let settings = HttpClientSettings::load(io)
+        .await
+        .exit_context("loading http client settings");
+    let builder = Client::builder().user_agent(concat!("vrc-get/", env!("CARGO_PKG_VERSION")));
+    let client = (settings.build_client(builder)).exit_context("configuring http client");
+    Some(client)
+}
+
 async fn load_env(args: &EnvArgs) -> Environment {
-    let client = crate::create_client(args.offline);
+    let update = if args.no_update {
+        UpdateRepositoryMode::None
+    } else {
+        UpdateRepositoryMode::IfOutdated
+    };
+    load_env_with_update(args, update).await
+}
+
+async fn load_env_with_update(args: &EnvArgs, update: UpdateRepositoryMode) -> Environment {
     let io = DefaultEnvironmentIo::new_default();
+    let client = create_client(&io, args.offline).await;
     let mut env = Environment::load(client, io)
         .await
         .exit_context("loading global config");
 
-    env.load_package_infos(!args.no_update)
+    env.load_package_infos(update)
         .await
         .exit_context("loading repositories");
     env.save().await.exit_context("saving repositories updates");
@@ -104,9 +134,15 @@ async fn load_unity(path: Option<Box<Path>>) -> UnityProject {
         Some(path) => DefaultProjectIo::new(path),
     };
 
-    UnityProject::load(io)
+    let unity = UnityProject::load(io)
         .await
-        .exit_context("loading unity project")
+        .exit_context("loading unity project");
+
+    if unity.has_interrupted_changes() {
+        warn!("the previous changes to the project were interrupted. run `vrc-get recover` to recover the project");
+    }
+
+    unity
 }
 
 #[cfg(feature = "experimental-vcc")]
@@ -232,6 +268,7 @@ fn print_prompt_install(changes: &PendingProjectChanges) {
                 RemoveReason::Requested => "requested",
                 RemoveReason::Legacy => "legacy",
                 RemoveReason::Unused => "unused",
+                RemoveReason::Dependent => "depending on removed packages",
             };
             println!("- {} (removed since {})", name, reason_name);
         }
@@ -251,6 +288,9 @@ fn print_prompt_install(changes: &PendingProjectChanges) {
                 for conflict in conflicts.conflicting_packages() {
                     println!("- {conflict}");
                 }
+                if let Some(explanation) = conflicts.explanation() {
+                    print_explanation(explanation);
+                }
             }
         }
     }
@@ -269,6 +309,66 @@ fn print_prompt_install(changes: &PendingProjectChanges) {
             }
         }
     }
+
+    // process hold conflicts
+    {
+        let mut hold_conflicts = (changes.conflicts().iter())
+            .filter(|(_, conflicts)| conflicts.conflicts_with_hold())
+            .map(|(package, _)| package)
+            .peekable();
+
+        if hold_conflicts.peek().is_some() {
+            println!("**Those packages are held so kept at the locked version**");
+            for package in hold_conflicts {
+                println!("- {}", package);
+            }
+        }
+    }
+}
+
+fn print_explanation(explanation: &ResolutionExplanation) {
+    if let Some(version) = explanation.version() {
+        eprintln!("  selected version: {version}");
+    }
+
+    if explanation.requirement_chain().len() > 1 {
+        eprintln!(
+            "  required through: {}",
+            explanation.requirement_chain().iter().join(" -> ")
+        );
+    }
+
+    if !explanation.requirements().is_empty() {
+        eprintln!("  requirements:");
+        for requirement in explanation.requirements() {
+            let dependent = requirement.dependent().unwrap_or("(project)");
+            let satisfied = if requirement.is_satisfied() {
+                "satisfied"
+            } else {
+                "not satisfied"
+            };
+            eprintln!(
+                "  - {dependent} requires {} ({satisfied})",
+                requirement.range()
+            );
+        }
+    }
+
+    if !explanation.rejected_versions().is_empty() {
+        eprintln!("  rejected versions:");
+        for rejected in explanation.rejected_versions() {
+            let reasons = (rejected.reasons().iter())
+                .map(|reason| match reason {
+                    RejectReason::OutOfRange => "out of range",
+                    RejectReason::Yanked => "yanked",
+                    RejectReason::IncompatibleUnity => "incompatible with unity",
+                    RejectReason::Prerelease => "prerelease",
+                    _ => "unknown",
+                })
+                .join(", ");
+            eprintln!("  - {} ({reasons})", rejected.version());
+        }
+    }
 }
 
 fn prompt_install(yes: bool) {
@@ -335,8 +435,31 @@ impl<T, E> ResultExt<T, E> for Result<T, E> {
     }
 }
 
+trait AddPackageResultExt<T> {
+    fn exit_resolve_context(self, context: &str) -> T;
+}
+
+impl<T> AddPackageResultExt<T> for Result<T, AddPackageErr> {
+    fn exit_resolve_context(self, context: &str) -> T {
+        match self {
+            Ok(value) => value,
+            Err(err) => {
+                eprintln!("error {context}: {err}");
+                if let AddPackageErr::DependencyNotFound { explanation, .. } = &err {
+                    print_explanation(explanation);
+                }
+                exit(1)
+            }
+        }
+    }
+}
+
+mod cache;
 mod info;
 mod migrate;
+mod overrides;
+mod progress;
+mod store;
 #[cfg(feature = "experimental-vcc")]
 mod vcc;
 
@@ -347,16 +470,32 @@ pub enum Command {
     #[command(alias = "i")]
     Install(Install),
     Resolve(Resolve),
+    Apply(Apply),
+    Recover(Recover),
     #[command(alias = "rm")]
     Remove(Remove),
+    Autoremove(Autoremove),
+    Link(Link),
     Update(Update),
     Outdated(Outdated),
+    Why(Why),
+    Tree(Tree),
+    Verify(Verify),
     Upgrade(Upgrade),
     Downgrade(Downgrade),
+    Hold(Hold),
+    Unhold(Unhold),
+    #[command(subcommand)]
+    Override(overrides::Override),
     Search(Search),
+    Pack(Pack),
     #[command(subcommand)]
     Repo(Repo),
     #[command(subcommand)]
+    Cache(cache::Cache),
+    #[command(subcommand)]
+    Store(store::Store),
+    #[command(subcommand)]
     Info(info::Info),
     #[command(subcommand)]
     Migrate(migrate::Migrate),
@@ -373,13 +512,26 @@ pub enum Command {
 multi_command!(Command is
     Install,
     Resolve,
+    Apply,
+    Recover,
     Remove,
+    Autoremove,
+    Link,
     Update,
     Outdated,
+    Why,
+    Tree,
+    Verify,
     Upgrade,
     Downgrade,
+    Hold,
+    Unhold,
+    Override,
     Search,
+    Pack,
     Repo,
+    Cache,
+    Store,
     Info,
     Migrate,
     Vcc,
@@ -418,6 +570,14 @@ pub struct Install {
     /// skip confirm
     #[arg(short, long)]
     yes: bool,
+
+    /// Overwrite packages even if they are modified locally
+    #[arg(long)]
+    force: bool,
+
+    /// Write the changes to the file instead of applying them. Use `vrc-get apply` to apply them later.
+    #[arg(long = "plan-out", value_name = "FILE")]
+    plan_out: Option<PathBuf>,
 }
 
 impl Install {
@@ -427,6 +587,8 @@ impl Install {
             return Resolve {
                 project: self.project,
                 env_args: self.env_args,
+                force: self.force,
+                plan_out: self.plan_out,
             }
             .run()
             .await;
@@ -466,7 +628,7 @@ impl Install {
             vec![get_package(&env, &name, version_selector)]
         };
 
-        let changes = unity
+        let mut changes = unity
             .add_package_request(
                 &env,
                 &packages,
@@ -474,16 +636,23 @@ impl Install {
                 self.prerelease,
             )
             .await
-            .exit_context("collecting packages to be installed");
+            .exit_resolve_context("collecting packages to be installed");
 
         print_prompt_install(&changes);
 
+        if let Some(plan_out) = self.plan_out {
+            write_plan(&unity, &changes, &plan_out).await;
+            return;
+        }
+
         if require_prompt_for_install(&changes, name.as_str(), None) {
             prompt_install(self.yes);
         }
 
+        changes.set_overwrite_local_modifications(self.force);
+
         unity
-            .apply_pending_changes(&env, changes)
+            .apply_pending_changes_with_progress(&env, changes, &InstallProgressBar::new())
             .await
             .exit_context("adding package");
 
@@ -492,6 +661,109 @@ impl Install {
     }
 }
 
+async fn write_plan(unity: &UnityProject, changes: &PendingProjectChanges<'_>, path: &Path) {
+    let json = serde_json::to_string_pretty(&changes.to_plan(unity)).unwrap();
+    tokio::fs::write(path, json)
+        .await
+        .exit_context("writing plan file");
+    println!("The plan is written to {}", path.display());
+}
+
+/// Applies changes saved with `--plan-out` to unity project
+///
+/// This fails if the packages in the plan are changed in the repositories after the plan is created.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Apply {
+    /// Path to the plan file
+    #[arg()]
+    plan: PathBuf,
+
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+    #[command(flatten)]
+    env_args: EnvArgs,
+
+    /// skip confirm
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Overwrite packages even if they are modified locally
+    #[arg(long)]
+    force: bool,
+}
+
+impl Apply {
+    pub async fn run(self) {
+        let plan = tokio::fs::read(&self.plan)
+            .await
+            .exit_context("reading plan file");
+        let plan: ProjectChangesPlan =
+            serde_json::from_slice(&plan).exit_context("parsing plan file");
+
+        let env = load_env(&self.env_args).await;
+        let mut unity = load_unity(self.project).await;
+
+        let mut changes = plan
+            .into_changes(&env, &unity)
+            .exit_context("loading plan file");
+
+        print_prompt_install(&changes);
+
+        prompt_install(self.yes);
+
+        changes.set_overwrite_local_modifications(self.force);
+
+        unity
+            .apply_pending_changes_with_progress(&env, changes, &InstallProgressBar::new())
+            .await
+            .exit_context("applying changes");
+
+        unity.save().await.exit_context("saving manifest file");
+        update_project_last_modified(env, unity.project_dir()).await;
+    }
+}
+
+/// Recovers unity project from interrupted installation or removal
+///
+/// If the packages were not fully installed, the previous packages will be restored.
+/// Otherwise, the remaining changes like updating vpm-manifest.json will be applied.
+/// The interrupted changes are found with the journal in `Library/vrc-get` of the project.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Recover {
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+}
+
+impl Recover {
+    pub async fn run(self) {
+        let mut unity = load_unity(self.project).await;
+
+        let result = unity
+            .recover_interrupted_changes()
+            .await
+            .exit_context("recovering project");
+
+        match result {
+            RecoverChangesResult::NothingToRecover => {
+                println!("No interrupted changes found");
+            }
+            RecoverChangesResult::RolledBack => {
+                println!("Restored the packages before the interrupted changes");
+            }
+            RecoverChangesResult::RolledForward => {
+                println!("Applied the remaining changes of the interrupted changes");
+            }
+            _ => {
+                println!("Recovered the project");
+            }
+        }
+    }
+}
+
 /// (re)installs all locked packages
 ///
 /// If some install packages that is not locked depends on non installed packages,
@@ -504,6 +776,14 @@ pub struct Resolve {
     project: Option<Box<Path>>,
     #[command(flatten)]
     env_args: EnvArgs,
+
+    /// Overwrite packages even if they are modified locally
+    #[arg(long)]
+    force: bool,
+
+    /// Write the changes to the file instead of applying them. Use `vrc-get apply` to apply them later.
+    #[arg(long = "plan-out", value_name = "FILE")]
+    plan_out: Option<PathBuf>,
 }
 
 impl Resolve {
@@ -511,15 +791,22 @@ impl Resolve {
         let env = load_env(&self.env_args).await;
         let mut unity = load_unity(self.project).await;
 
-        let changes = unity
+        let mut changes = unity
             .resolve_request(&env)
             .await
-            .exit_context("collecting packages to be installed");
+            .exit_resolve_context("collecting packages to be installed");
 
         print_prompt_install(&changes);
 
+        if let Some(plan_out) = self.plan_out {
+            write_plan(&unity, &changes, &plan_out).await;
+            return;
+        }
+
+        changes.set_overwrite_local_modifications(self.force);
+
         unity
-            .apply_pending_changes(&env, changes)
+            .apply_pending_changes_with_progress(&env, changes, &InstallProgressBar::new())
             .await
             .exit_context("installing packages");
 
@@ -544,6 +831,14 @@ pub struct Remove {
     /// skip confirm
     #[arg(short, long)]
     yes: bool,
+
+    /// Overwrite packages even if they are modified locally
+    #[arg(long)]
+    force: bool,
+
+    /// Also remove packages depending on the packages to be removed
+    #[arg(long)]
+    cascade: bool,
 }
 
 impl Remove {
@@ -551,10 +846,13 @@ impl Remove {
         let env = load_env(&self.env_args).await;
         let mut unity = load_unity(self.project).await;
 
-        let changes = unity
-            .remove_request(&self.names.iter().map(String::as_ref).collect::<Vec<_>>())
-            .await
-            .exit_context("collecting packages to be removed");
+        let names = self.names.iter().map(String::as_ref).collect::<Vec<_>>();
+        let mut changes = if self.cascade {
+            unity.remove_cascade_request(&names).await
+        } else {
+            unity.remove_request(&names).await
+        }
+        .exit_context("collecting packages to be removed");
 
         print_prompt_install(&changes);
 
@@ -565,8 +863,126 @@ impl Remove {
             prompt_install(self.yes);
         }
 
+        changes.set_overwrite_local_modifications(self.force);
+
+        unity
+            .apply_pending_changes_with_progress(&env, changes, &InstallProgressBar::new())
+            .await
+            .exit_context("removing packages");
+
+        save_unity(&mut unity).await;
+        update_project_last_modified(env, unity.project_dir()).await;
+    }
+}
+
+/// Link a local user package to the project instead of copying it
+///
+/// The package is referenced with `file:` in Packages/manifest.json so edits to the package
+/// are reflected without reinstalling. Use `vrc-get remove` to unlink the package.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Link {
+    /// Name of the user package
+    #[arg()]
+    name: String,
+    /// Version of package. if not specified, latest version will be used
+    #[arg(id = "VERSION")]
+    version: Option<Version>,
+
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+    #[command(flatten)]
+    env_args: EnvArgs,
+
+    /// skip confirm
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Overwrite packages even if they are modified locally
+    #[arg(long)]
+    force: bool,
+}
+
+impl Link {
+    pub async fn run(self) {
+        let env = load_env(&self.env_args).await;
+        let mut unity = load_unity(self.project).await;
+
+        let package = env
+            .find_packages(&self.name)
+            .filter(|x| x.is_local())
+            .filter(|x| self.version.iter().all(|v| x.version() == v))
+            .max_by_key(|x| x.version())
+            .unwrap_or_else(|| exit_with!("no matching user package found"));
+
+        unity.link_package(package).exit_context("linking package");
+
+        let mut changes = unity
+            .resolve_request(&env)
+            .await
+            .exit_resolve_context("collecting dependencies to be installed");
+
+        if !changes.package_changes().is_empty() {
+            print_prompt_install(&changes);
+            prompt_install(self.yes);
+        }
+
+        changes.set_overwrite_local_modifications(self.force);
+
+        unity
+            .apply_pending_changes_with_progress(&env, changes, &InstallProgressBar::new())
+            .await
+            .exit_context("installing dependencies");
+
+        println!("linked {} {}", package.name(), package.version());
+
+        save_unity(&mut unity).await;
+        update_project_last_modified(env, unity.project_dir()).await;
+    }
+}
+
+/// Remove packages no longer required by the project
+///
+/// Locked packages not reachable from dependencies of the project will be removed.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Autoremove {
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+    #[command(flatten)]
+    env_args: EnvArgs,
+
+    /// skip confirm
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Overwrite packages even if they are modified locally
+    #[arg(long)]
+    force: bool,
+}
+
+impl Autoremove {
+    pub async fn run(self) {
+        let env = load_env(&self.env_args).await;
+        let mut unity = load_unity(self.project).await;
+
+        let mut changes = unity.autoremove_request().await;
+
+        if changes.package_changes().is_empty() {
+            println!("No packages to remove");
+            return;
+        }
+
+        print_prompt_install(&changes);
+
+        prompt_install(self.yes);
+
+        changes.set_overwrite_local_modifications(self.force);
+
         unity
-            .apply_pending_changes(&env, changes)
+            .apply_pending_changes_with_progress(&env, changes, &InstallProgressBar::new())
             .await
             .exit_context("removing packages");
 
@@ -575,14 +991,14 @@ impl Remove {
     }
 }
 
-/// Update local repository cache
+/// Update local repository cache, including repositories fetched recently
 #[derive(Parser)]
 #[command(author, version)]
 pub struct Update {}
 
 impl Update {
     pub async fn run(self) {
-        let _ = load_env(&EnvArgs::default()).await;
+        let _ = load_env_with_update(&EnvArgs::default(), UpdateRepositoryMode::Force).await;
     }
 }
 
@@ -615,6 +1031,9 @@ impl Outdated {
         let selector = VersionSelector::latest_for(unity.unity_version(), self.prerelease);
 
         for locked in unity.locked_packages() {
+            if unity.is_held(locked.name()) {
+                continue;
+            }
             match env.find_package_by_name(locked.name(), selector) {
                 None => log::error!("latest version for package {} not found.", locked.name()),
                 // if found version is newer: add to outdated
@@ -627,6 +1046,7 @@ impl Outdated {
 
         for locked in unity.all_packages() {
             for (name, range) in locked.dependencies() {
+                let range = unity.get_override(name).unwrap_or(range);
                 if let Some((outdated, _)) = outdated_packages.get(name.as_ref()) {
                     if !range.matches(outdated.version()) {
                         outdated_packages.remove(name.as_ref());
@@ -635,6 +1055,17 @@ impl Outdated {
             }
         }
 
+        let active_overrides = unity.active_overrides();
+        for active in &active_overrides {
+            warn!(
+                "{dependency} required by {dependent} is overridden: {declared} -> {overridden}",
+                dependency = active.dependency(),
+                dependent = active.dependent(),
+                declared = active.declared(),
+                overridden = active.overridden(),
+            );
+        }
+
         match self.json_format.map(|x| x.get()).unwrap_or(0) {
             0 => {
                 for (name, (found, installed)) in &outdated_packages {
@@ -652,6 +1083,14 @@ impl Outdated {
                     package_name: &'a str,
                     installed_version: &'a Version,
                     newer_version: &'a Version,
+                    // since 1.9.0
+                    overrides: Vec<OverrideInfo<'a>>,
+                }
+                #[derive(Serialize)]
+                struct OverrideInfo<'a> {
+                    dependent: &'a str,
+                    declared: &'a VersionRange,
+                    overridden: &'a VersionRange,
                 }
                 let info = outdated_packages
                     .into_iter()
@@ -659,6 +1098,15 @@ impl Outdated {
                         package_name,
                         installed_version: installed,
                         newer_version: found.version(),
+                        overrides: active_overrides
+                            .iter()
+                            .filter(|active| active.dependency() == package_name)
+                            .map(|active| OverrideInfo {
+                                dependent: active.dependent(),
+                                declared: active.declared(),
+                                overridden: active.overridden(),
+                            })
+                            .collect(),
                     })
                     .collect::<Vec<_>>();
                 println!("{}", serde_json::to_string(&info).unwrap());
@@ -668,58 +1116,558 @@ impl Outdated {
     }
 }
 
-/// Upgrade specified package or all packages to latest or specified version.
-///
-/// With install command, you'll add to dependencies. With upgrade command,
-/// you'll upgrade dependencies or locked dependencies but not add to dependencies.
+/// Show why the package is installed to the project
 #[derive(Parser)]
 #[command(author, version)]
-pub struct Upgrade {
+pub struct Why {
     /// Name of Package
     #[arg()]
-    name: Option<String>,
-    /// Version of package. if not specified, latest version will be used
-    #[arg(id = "VERSION")]
-    version: Option<Version>,
-    /// Include prerelease
-    #[arg(long = "prerelease")]
-    prerelease: bool,
+    name: String,
 
     /// Path to project dir. by default CWD or parents of CWD will be used
     #[arg(short = 'p', long = "project")]
     project: Option<Box<Path>>,
-    #[command(flatten)]
-    env_args: EnvArgs,
 
-    /// skip confirm
-    #[arg(short, long)]
-    yes: bool,
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
 }
 
-impl Upgrade {
+impl Why {
     pub async fn run(self) {
-        let env = load_env(&self.env_args).await;
-        let mut unity = load_unity(self.project).await;
-
-        let updates = if let Some(name) = &self.name {
-            let version_selector = match self.version {
-                None => VersionSelector::latest_for(unity.unity_version(), self.prerelease),
-                Some(ref version) => VersionSelector::specific_version(version),
-            };
-            let package = get_package(&env, name, version_selector);
+        let unity = load_unity(self.project).await;
 
-            vec![package]
-        } else {
-            let version_selector =
-                VersionSelector::latest_for(unity.unity_version(), self.prerelease);
+        let paths = unity.dependency_paths_to(&self.name);
 
-            unity
-                .locked_packages()
+        match self.json_format.map(|x| x.get()).unwrap_or(0) {
+            0 => {
+                if paths.is_empty() {
+                    if unity.get_locked(&self.name).is_some() {
+                        println!("{} is locked but not required by any package", self.name);
+                    } else {
+                        println!("{} is not required by the project", self.name);
+                    }
+                    return;
+                }
+
+                for path in &paths {
+                    for (depth, step) in path.steps().iter().enumerate() {
+                        let indent = "  ".repeat(depth);
+                        let arrow = if depth == 0 { "" } else { "-> " };
+                        let name = step.name();
+                        match step.reason() {
+                            DependencyPathReason::Root(range) => {
+                                println!("{indent}{arrow}{name} {range} (dependency of project)")
+                            }
+                            DependencyPathReason::Unlocked => {
+                                println!("{indent}{arrow}{name} (unlocked package)")
+                            }
+                            DependencyPathReason::Dependency(range) => {
+                                println!("{indent}{arrow}{name} {range}")
+                            }
+                            DependencyPathReason::Legacy => {
+                                println!("{indent}{arrow}{name} (as legacy package)")
+                            }
+                            _ => println!("{indent}{arrow}{name}"),
+                        }
+                    }
+                }
+            }
+            1 => {
+                #[derive(Serialize)]
+                struct PathStep<'a> {
+                    name: &'a str,
+                    reason: &'static str,
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    range: Option<String>,
+                }
+
+                let paths = paths
+                    .iter()
+                    .map(|path| {
+                        path.steps()
+                            .iter()
+                            .map(|step| {
+                                let (reason, range) = match step.reason() {
+                                    DependencyPathReason::Root(range) => {
+                                        ("root", Some(range.to_string()))
+                                    }
+                                    DependencyPathReason::Unlocked => ("unlocked", None),
+                                    DependencyPathReason::Dependency(range) => {
+                                        ("dependency", Some(range.to_string()))
+                                    }
+                                    DependencyPathReason::Legacy => ("legacy", None),
+                                    _ => ("unknown", None),
+                                };
+                                PathStep {
+                                    name: step.name(),
+                                    reason,
+                                    range,
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string(&paths).unwrap());
+            }
+            v => exit_with!("unsupported json version: {v}"),
+        }
+    }
+}
+
+/// Show dependency graph of the project
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Tree {
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format", conflicts_with = "dot")]
+    json_format: Option<NonZeroU32>,
+
+    /// With this option, output is printed in Graphviz DOT format
+    #[arg(long = "dot")]
+    dot: bool,
+}
+
+/// The edge of the dependency graph. `from` is `None` for dependencies of the project.
+struct TreeEdge<'a> {
+    from: Option<&'a str>,
+    to: &'a str,
+    range: String,
+    satisfied: bool,
+}
+
+impl Tree {
+    pub async fn run(self) {
+        let unity = load_unity(self.project).await;
+
+        // name -> (version, locked)
+        let nodes: IndexMap<&str, (&Version, bool)> = unity
+            .locked_packages()
+            .map(|x| (x.name(), (x.version(), true)))
+            .chain(
+                (unity.unlocked_packages().iter())
+                    .filter_map(|(_, pkg)| pkg.as_ref())
+                    .map(|x| (x.name(), (x.version(), false))),
+            )
+            .collect();
+
+        let mut edges = vec![];
+
+        for name in unity.dependencies() {
+            let range = unity.get_dependency(name).unwrap();
+            edges.push(TreeEdge {
+                from: None,
+                to: name,
+                range: range.to_string(),
+                satisfied: nodes
+                    .get(name)
+                    .map(|(version, _)| range.matches(version))
+                    .unwrap_or(false),
+            });
+        }
+
+        for package in unity.all_packages() {
+            for (dependency, range) in package.dependencies() {
+                edges.push(TreeEdge {
+                    from: Some(package.name()),
+                    to: dependency,
+                    range: range.to_string(),
+                    satisfied: nodes
+                        .get(dependency.as_ref())
+                        .map(|(version, _)| range.match_pre(version, true))
+                        .unwrap_or(false),
+                });
+            }
+        }
+
+        if self.dot {
+            Self::print_dot(&nodes, &edges);
+            return;
+        }
+
+        match self.json_format.map(|x| x.get()).unwrap_or(0) {
+            0 => Self::print_tree(&nodes, &edges),
+            1 => {
+                #[derive(Serialize)]
+                struct Graph<'a> {
+                    nodes: Vec<Node<'a>>,
+                    edges: Vec<Edge<'a>>,
+                }
+
+                #[derive(Serialize)]
+                struct Node<'a> {
+                    name: &'a str,
+                    version: &'a Version,
+                    locked: bool,
+                }
+
+                #[derive(Serialize)]
+                struct Edge<'a> {
+                    from: Option<&'a str>,
+                    to: &'a str,
+                    range: &'a str,
+                    satisfied: bool,
+                }
+
+                let graph = Graph {
+                    nodes: nodes
+                        .iter()
+                        .map(|(&name, &(version, locked))| Node {
+                            name,
+                            version,
+                            locked,
+                        })
+                        .collect(),
+                    edges: edges
+                        .iter()
+                        .map(|x| Edge {
+                            from: x.from,
+                            to: x.to,
+                            range: &x.range,
+                            satisfied: x.satisfied,
+                        })
+                        .collect(),
+                };
+
+                println!("{}", serde_json::to_string(&graph).unwrap());
+            }
+            v => exit_with!("unsupported json version: {v}"),
+        }
+    }
+
+    fn print_tree(nodes: &IndexMap<&str, (&Version, bool)>, edges: &[TreeEdge]) {
+        fn print_node(
+            nodes: &IndexMap<&str, (&Version, bool)>,
+            edges: &[TreeEdge],
+            edge: &TreeEdge,
+            prefix: &str,
+            last: bool,
+            printed: &mut HashSet<String>,
+        ) {
+            let (branch, child_prefix) = if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+
+            let node = match nodes.get(edge.to) {
+                Some((version, true)) => format!("{} {version}", edge.to),
+                Some((version, false)) => format!("{} {version} (unlocked)", edge.to),
+                None => format!("{} (missing)", edge.to),
+            };
+            let satisfied = if edge.satisfied {
+                ""
+            } else {
+                " [not satisfied]"
+            };
+            let expanded = !printed.insert(edge.to.to_string());
+            let repeated = if expanded { " (*)" } else { "" };
+
+            println!(
+                "{prefix}{branch}{node} (requires {range}){satisfied}{repeated}",
+                range = edge.range
+            );
+
+            if !expanded {
+                let prefix = format!("{prefix}{child_prefix}");
+                print_children(nodes, edges, Some(edge.to), &prefix, printed);
+            }
+        }
+
+        fn print_children(
+            nodes: &IndexMap<&str, (&Version, bool)>,
+            edges: &[TreeEdge],
+            from: Option<&str>,
+            prefix: &str,
+            printed: &mut HashSet<String>,
+        ) {
+            let children = edges.iter().filter(|x| x.from == from).collect::<Vec<_>>();
+            for (i, edge) in children.iter().enumerate() {
+                let last = i == children.len() - 1;
+                print_node(nodes, edges, edge, prefix, last, printed);
+            }
+        }
+
+        let mut printed = HashSet::new();
+
+        println!("(project)");
+        print_children(nodes, edges, None, "", &mut printed);
+
+        for (&name, &(version, locked)) in nodes {
+            if !locked && !printed.contains(name) {
+                printed.insert(name.to_string());
+                println!("{name} {version} (unlocked)");
+                print_children(nodes, edges, Some(name), "", &mut printed);
+            }
+        }
+    }
+
+    fn print_dot(nodes: &IndexMap<&str, (&Version, bool)>, edges: &[TreeEdge]) {
+        fn quote(s: &str) -> String {
+            format!("\"{}\"", s.replace('"', "\\\""))
+        }
+
+        println!("digraph dependencies {{");
+        println!("    {} [shape=box];", quote("(project)"));
+        for (&name, &(version, locked)) in nodes {
+            let style = if locked { "" } else { ", style=dashed" };
+            println!(
+                "    {} [label={}{style}];",
+                quote(name),
+                quote(&format!("{name}\\n{version}"))
+            );
+        }
+        for edge in edges {
+            if !nodes.contains_key(edge.to) {
+                println!(
+                    "    {} [label={}, color=red];",
+                    quote(edge.to),
+                    quote(&format!("{}\\n(missing)", edge.to))
+                );
+            }
+            let color = if edge.satisfied { "" } else { ", color=red" };
+            println!(
+                "    {} -> {} [label={}{color}];",
+                quote(edge.from.unwrap_or("(project)")),
+                quote(edge.to),
+                quote(&edge.range)
+            );
+        }
+        println!("}}");
+    }
+}
+
+/// Verify the installed packages match the locked versions
+///
+/// Each locked package is checked with its package.json and the cached zip file of the package.
+/// This exits with non-zero status if some packages are modified, partially installed, or missing,
+/// or the files of some packages cannot be checked since the zip files are not cached.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Verify {
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+
+    /// Do not fail for packages whose files cannot be checked since the zip files are not cached
+    #[arg(long)]
+    allow_unchecked: bool,
+
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
+
+    #[command(flatten)]
+    env_args: EnvArgs,
+}
+
+impl Verify {
+    pub async fn run(self) {
+        let env = load_env(&self.env_args).await;
+        let unity = load_unity(self.project).await;
+
+        let report = unity
+            .verify_packages(&env)
+            .await
+            .exit_context("verifying packages");
+
+        let is_ok = |package: &PackageVerification| {
+            (package.issues().iter())
+                .all(|issue| self.allow_unchecked && matches!(issue, PackageIssue::FilesNotChecked))
+        };
+        let all_ok = report.packages().iter().all(is_ok);
+
+        match self.json_format.map(|x| x.get()).unwrap_or(0) {
+            0 => {
+                for package in report.packages() {
+                    let name = package.name();
+                    let version = package.version();
+                    if package.is_ok() {
+                        println!("{name}@{version}: ok");
+                    } else if is_ok(package) {
+                        println!("{name}@{version}: ok (files not checked: zip file not cached)");
+                    } else {
+                        println!("{name}@{version}: {} issue(s)", package.issues().len());
+                        for issue in package.issues() {
+                            println!("  {}", describe_issue(issue));
+                        }
+                    }
+                }
+            }
+            1 => {
+                #[derive(Serialize)]
+                struct Report<'a> {
+                    ok: bool,
+                    packages: Vec<Package<'a>>,
+                }
+
+                #[derive(Serialize)]
+                struct Package<'a> {
+                    name: &'a str,
+                    version: &'a Version,
+                    ok: bool,
+                    files_checked: bool,
+                    issues: Vec<Issue<'a>>,
+                }
+
+                #[derive(Serialize)]
+                struct Issue<'a> {
+                    kind: &'static str,
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    path: Option<&'a Path>,
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    installed: Option<&'a Version>,
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    expected: Option<&'a str>,
+                    #[serde(skip_serializing_if = "Option::is_none")]
+                    actual: Option<&'a str>,
+                }
+
+                fn issue(issue: &PackageIssue) -> Issue<'_> {
+                    let mut result = Issue {
+                        kind: "unknown",
+                        path: None,
+                        installed: None,
+                        expected: None,
+                        actual: None,
+                    };
+                    match issue {
+                        PackageIssue::MissingPackageJson => {
+                            result.kind = "missing_package_json";
+                        }
+                        PackageIssue::VersionMismatch { installed } => {
+                            result.kind = "version_mismatch";
+                            result.installed = Some(installed);
+                        }
+                        PackageIssue::ZipHashMismatch { expected, actual } => {
+                            result.kind = "zip_hash_mismatch";
+                            result.expected = Some(expected);
+                            result.actual = Some(actual);
+                        }
+                        PackageIssue::ModifiedFile { path } => {
+                            result.kind = "modified_file";
+                            result.path = Some(path);
+                        }
+                        PackageIssue::ExtraFile { path } => {
+                            result.kind = "extra_file";
+                            result.path = Some(path);
+                        }
+                        PackageIssue::MissingFile { path } => {
+                            result.kind = "missing_file";
+                            result.path = Some(path);
+                        }
+                        PackageIssue::FilesNotChecked => {
+                            result.kind = "files_not_checked";
+                        }
+                        _ => {}
+                    }
+                    result
+                }
+
+                let report = Report {
+                    ok: all_ok,
+                    packages: (report.packages().iter())
+                        .map(|package| Package {
+                            name: package.name(),
+                            version: package.version(),
+                            ok: is_ok(package),
+                            files_checked: package.files_checked(),
+                            issues: package.issues().iter().map(issue).collect(),
+                        })
+                        .collect(),
+                };
+                println!("{}", serde_json::to_string(&report).unwrap());
+            }
+            v => exit_with!("unsupported json version: {v}"),
+        }
+
+        if !all_ok {
+            exit(1);
+        }
+    }
+}
+
+fn describe_issue(issue: &PackageIssue) -> String {
+    match issue {
+        PackageIssue::MissingPackageJson => "package.json not found".to_string(),
+        PackageIssue::VersionMismatch { installed } => {
+            format!("installed version {installed} is not the locked version")
+        }
+        PackageIssue::ZipHashMismatch { expected, actual } => {
+            format!("cached zip file has sha256 {actual} but the repository says {expected}")
+        }
+        PackageIssue::ModifiedFile { path } => format!("modified: {}", path.display()),
+        PackageIssue::ExtraFile { path } => format!("extra: {}", path.display()),
+        PackageIssue::MissingFile { path } => format!("missing: {}", path.display()),
+        PackageIssue::FilesNotChecked => {
+            "files not checked: zip file not cached. use --allow-unchecked to ignore this"
+                .to_string()
+        }
+        _ => format!("{issue:?}"),
+    }
+}
+
+/// Upgrade specified package or all packages to latest or specified version.
+///
+/// With install command, you'll add to dependencies. With upgrade command,
+/// you'll upgrade dependencies or locked dependencies but not add to dependencies.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Upgrade {
+    /// Name of Package
+    #[arg()]
+    name: Option<String>,
+    /// Version of package. if not specified, latest version will be used
+    #[arg(id = "VERSION")]
+    version: Option<Version>,
+    /// Include prerelease
+    #[arg(long = "prerelease")]
+    prerelease: bool,
+
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+    #[command(flatten)]
+    env_args: EnvArgs,
+
+    /// skip confirm
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Overwrite packages even if they are modified locally
+    #[arg(long)]
+    force: bool,
+}
+
+impl Upgrade {
+    pub async fn run(self) {
+        let env = load_env(&self.env_args).await;
+        let mut unity = load_unity(self.project).await;
+
+        let updates = if let Some(name) = &self.name {
+            let version_selector = match self.version {
+                None => VersionSelector::latest_for(unity.unity_version(), self.prerelease),
+                Some(ref version) => VersionSelector::specific_version(version),
+            };
+            let package = get_package(&env, name, version_selector);
+
+            vec![package]
+        } else {
+            let version_selector =
+                VersionSelector::latest_for(unity.unity_version(), self.prerelease);
+
+            unity
+                .locked_packages()
+                .filter(|locked| !unity.is_held(locked.name()))
                 .map(|locked| get_package(&env, locked.name(), version_selector))
                 .collect()
         };
 
-        let changes = unity
+        let mut changes = unity
             .add_package_request(
                 &env,
                 &updates,
@@ -727,7 +1675,7 @@ impl Upgrade {
                 self.prerelease,
             )
             .await
-            .exit_context("collecting packages to be upgraded");
+            .exit_resolve_context("collecting packages to be upgraded");
 
         print_prompt_install(&changes);
 
@@ -747,8 +1695,10 @@ impl Upgrade {
             .map(|x| (x.name().to_owned(), x.version().clone()))
             .collect::<Vec<_>>();
 
+        changes.set_overwrite_local_modifications(self.force);
+
         unity
-            .apply_pending_changes(&env, changes)
+            .apply_pending_changes_with_progress(&env, changes, &InstallProgressBar::new())
             .await
             .exit_context("upgrading packages");
 
@@ -787,6 +1737,10 @@ pub struct Downgrade {
     /// skip confirm
     #[arg(short, long)]
     yes: bool,
+
+    /// Overwrite packages even if they are modified locally
+    #[arg(long)]
+    force: bool,
 }
 
 impl Downgrade {
@@ -800,7 +1754,7 @@ impl Downgrade {
             VersionSelector::specific_version(&self.version),
         )];
 
-        let changes = unity
+        let mut changes = unity
             .add_package_request(
                 &env,
                 &updates,
@@ -808,7 +1762,7 @@ impl Downgrade {
                 self.prerelease,
             )
             .await
-            .exit_context("collecting packages to be upgraded");
+            .exit_resolve_context("collecting packages to be upgraded");
 
         print_prompt_install(&changes);
 
@@ -822,8 +1776,10 @@ impl Downgrade {
             .map(|x| (x.name().to_owned(), x.version().clone()))
             .collect::<Vec<_>>();
 
+        changes.set_overwrite_local_modifications(self.force);
+
         unity
-            .apply_pending_changes(&env, changes)
+            .apply_pending_changes_with_progress(&env, changes, &InstallProgressBar::new())
             .await
             .exit_context("upgrading packages");
 
@@ -836,6 +1792,71 @@ impl Downgrade {
     }
 }
 
+/// Hold packages at the currently locked version
+///
+/// Held packages are skipped by `vrc-get upgrade` and `vrc-get outdated`.
+/// Without package names, this lists the held packages.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Hold {
+    /// Names of Packages to hold
+    #[arg()]
+    names: Vec<String>,
+
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+}
+
+impl Hold {
+    pub async fn run(self) {
+        let mut unity = load_unity(self.project).await;
+
+        if self.names.is_empty() {
+            for name in unity.held_packages() {
+                let version = unity.get_locked(name).map(|x| x.version().to_string());
+                println!("{name}: {}", version.as_deref().unwrap_or("not locked"));
+            }
+            return;
+        }
+
+        for name in &self.names {
+            if !unity.hold_package(name) {
+                exit_with!("package {name} is not installed");
+            }
+        }
+
+        save_unity(&mut unity).await;
+    }
+}
+
+/// Release the hold of packages
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Unhold {
+    /// Names of Packages to release
+    #[arg(required = true)]
+    names: Vec<String>,
+
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+}
+
+impl Unhold {
+    pub async fn run(self) {
+        let mut unity = load_unity(self.project).await;
+
+        for name in &self.names {
+            if !unity.unhold_package(name) {
+                warn!("package {name} is not held");
+            }
+        }
+
+        save_unity(&mut unity).await;
+    }
+}
+
 /// Search package by the query
 ///
 /// Search for packages that includes query in either name, displayName, or description.
@@ -898,6 +1919,88 @@ impl Search {
     }
 }
 
+/// Build a package zip file from a package folder
+///
+/// The zip file is reproducible: the same folder always gives the same zipSHA256.
+/// Hidden files and folders like .git are not included.
+/// The entry for index.json of the repository will be printed to stdout.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Pack {
+    /// Path to the package folder with package.json
+    #[arg(default_value = ".")]
+    dir: PathBuf,
+
+    /// Path to the zip file to write. by default <name>-<version>.zip in the current directory
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// The URL the zip file will be served at. by default the file URL of the zip file
+    #[arg(long)]
+    url: Option<Url>,
+
+    /// Paths relative to the package folder to be excluded from the zip file
+    #[arg(long)]
+    exclude: Vec<PathBuf>,
+}
+
+impl Pack {
+    pub async fn run(self) {
+        let dir = tokio::fs::canonicalize(&self.dir)
+            .await
+            .exit_context("resolving package directory");
+        let io = DefaultEnvironmentIo::new(dir.clone().into_boxed_path());
+
+        let mut excludes = self
+            .exclude
+            .iter()
+            .map(PathBuf::as_path)
+            .collect::<Vec<_>>();
+        let mut packed = PackedPackage::pack(&io, &excludes)
+            .await
+            .exit_context("packing package");
+
+        let output = match self.output {
+            Some(output) => output,
+            None => PathBuf::from(format!(
+                "{}-{}.zip",
+                packed.manifest().name(),
+                packed.manifest().version()
+            )),
+        };
+        let output = env::current_dir()
+            .exit_context("getting current directory")
+            .join(output);
+
+        // the zip file written by previous run must not be included
+        if let Ok(relative) = output.strip_prefix(&dir) {
+            if packed.files().iter().any(|x| x.as_ref() == relative) {
+                excludes.push(relative);
+                packed = PackedPackage::pack(&io, &excludes)
+                    .await
+                    .exit_context("packing package");
+            }
+        }
+
+        tokio::fs::write(&output, packed.zip())
+            .await
+            .exit_context("writing zip file");
+        eprintln!(
+            "wrote {} with {} files",
+            output.display(),
+            packed.files().len()
+        );
+
+        let url = match self.url {
+            Some(url) => url,
+            None => Url::from_file_path(&output)
+                .unwrap_or_else(|()| exit_with!("cannot create file URL for {}", output.display())),
+        };
+        let entry = packed.repository_entry(&url);
+        println!("{}", serde_json::to_string_pretty(&entry).unwrap());
+    }
+}
+
 /// Commands around repositories
 #[derive(Subcommand)]
 #[command(author, version)]
@@ -907,9 +2010,12 @@ pub enum Repo {
     Remove(RepoRemove),
     Cleanup(RepoCleanup),
     Packages(RepoPackages),
+    Mirror(RepoMirror),
+    Build(RepoBuild),
+    Sign(RepoSign),
 }
 
-multi_command!(Repo is List, Add, Remove, Cleanup, Packages);
+multi_command!(Repo is List, Add, Remove, Cleanup, Packages, Mirror, Build, Sign);
 
 /// List all repositories
 #[derive(Parser)]
@@ -948,14 +2054,29 @@ pub struct RepoAdd {
     #[arg()]
     name: Option<String>,
 
-    /// Headers
+    /// Headers.
+    /// `${env:NAME}` and `${command:COMMAND}` in the value are replaced with the environment variable
+    /// or the output of the command on each request so the secret is not saved
     #[arg(short='H', long, value_parser = HeaderPair::from_str)]
     header: Vec<HeaderPair>,
 
+    /// Base64-encoded ed25519 public key the repository must be signed with.
+    /// The signature will be checked on every update of the repository
+    #[arg(long, value_parser = parse_public_key)]
+    public_key: Vec<String>,
+
     #[command(flatten)]
     env_args: EnvArgs,
 }
 
+fn parse_public_key(key: &str) -> Result<String, &'static str> {
+    if is_valid_public_key(key) {
+        Ok(key.to_owned())
+    } else {
+        Err("not a base64-encoded ed25519 public key")
+    }
+}
+
 #[derive(Clone)]
 struct HeaderPair(HeaderName, HeaderValue);
 
@@ -1010,13 +2131,14 @@ impl StdError for HeaderPairErr {
 impl RepoAdd {
     pub async fn run(self) {
         let mut env = load_env(&self.env_args).await;
+        let public_keys = self.public_key.into_iter().map(Into::into).collect();
 
         if let Ok(url) = Url::parse(&self.path_or_url) {
             let mut headers = IndexMap::<Box<str>, Box<str>>::new();
             for HeaderPair(name, value) in self.header {
                 headers.insert(name.as_str().into(), value.to_str().unwrap().into());
             }
-            env.add_remote_repo(url, self.name.as_deref(), headers)
+            env.add_remote_repo(url, self.name.as_deref(), headers, public_keys)
                 .await
                 .exit_context("adding repository")
         } else {
@@ -1026,8 +2148,21 @@ impl RepoAdd {
             if !normalized.exists() {
                 exit_with!("path not found: {}", normalized.display());
             }
-            env.add_local_repo(normalized.as_ref(), self.name.as_deref())
-                .exit_context("adding repository")
+            if normalized.is_dir() {
+                // the directory with index.json like created by `vrc-get repo mirror`
+                let url = Url::from_directory_path(&normalized).unwrap_or_else(|()| {
+                    exit_with!("cannot create file URL for {}", normalized.display())
+                });
+                env.add_remote_repo(url, self.name.as_deref(), IndexMap::new(), public_keys)
+                    .await
+                    .exit_context("adding repository")
+            } else {
+                if !public_keys.is_empty() {
+                    exit_with!("signatures are not supported for local user package folders");
+                }
+                env.add_local_repo(normalized.as_ref(), self.name.as_deref())
+                    .exit_context("adding repository")
+            }
         }
 
         save_env(&mut env).await;
@@ -1205,7 +2340,8 @@ impl RepoPackages {
             if self.env_args.offline {
                 exit_with!("remote repository specified but offline mode.");
             }
-            let client = crate::create_client(self.env_args.offline).unwrap();
+            let io = DefaultEnvironmentIo::new_default();
+            let client = create_client(&io, self.env_args.offline).await.unwrap();
             let (repo, _) = RemoteRepository::download(&client, &url, &IndexMap::new())
                 .await
                 .exit_context("downloading repository");
@@ -1231,6 +2367,212 @@ impl RepoPackages {
     }
 }
 
+/// Mirror packages in the repositories into a directory to use them without the internet
+///
+/// The directory will contain the zip files and index.json of the mirror.
+/// Add the index.json as a repository on the other machine to use the mirror.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct RepoMirror {
+    /// Path to the directory to write the mirror
+    dir: PathBuf,
+
+    /// Mirror only latest N versions of each package
+    #[arg(long, value_name = "N")]
+    latest: Option<NonZeroUsize>,
+
+    /// Mirror only packages locked in the projects
+    #[arg(long = "project", value_name = "PROJECT", conflicts_with = "latest")]
+    projects: Vec<PathBuf>,
+
+    /// The URL the directory will be served at, like `https://example.com/mirror/`.
+    /// Use the file URL of the directory on the other machine to use the mirror on the file system
+    #[arg(long)]
+    base_url: Url,
+
+    /// The name of the mirror repository
+    #[arg(long, default_value = "Mirror")]
+    name: String,
+
+    #[command(flatten)]
+    env_args: EnvArgs,
+}
+
+impl RepoMirror {
+    pub async fn run(self) {
+        let env = load_env(&self.env_args).await;
+
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .exit_context("creating mirror directory");
+        let dir = tokio::fs::canonicalize(&self.dir)
+            .await
+            .exit_context("resolving mirror directory");
+        let mut base_url = self.base_url;
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+
+        let mut packages = vec![];
+
+        if self.projects.is_empty() {
+            for (_, repo) in env.get_repos() {
+                for versions in repo.get_packages() {
+                    let mut versions = versions.all_versions().collect::<Vec<_>>();
+                    versions.sort_by(|a, b| b.version().cmp(a.version()));
+                    if let Some(latest) = self.latest {
+                        versions.truncate(latest.get());
+                    }
+                    packages.extend(versions.into_iter().map(|x| PackageInfo::remote(x, repo)));
+                }
+            }
+        } else {
+            for project in self.projects {
+                let unity = load_unity(Some(project.into_boxed_path())).await;
+                for locked in unity.locked_packages() {
+                    let selector = VersionSelector::specific_version(locked.version());
+                    match env.find_package_by_name(locked.name(), selector) {
+                        Some(package) if package.repo().is_some() => packages.push(package),
+                        Some(_) => {}
+                        None => warn!(
+                            "package {}@{} not found in the repositories",
+                            locked.name(),
+                            locked.version()
+                        ),
+                    }
+                }
+            }
+        }
+
+        let dest = DefaultEnvironmentIo::new(dir.into_boxed_path());
+        let mirrored = env
+            .mirror_packages(
+                &packages,
+                &self.name,
+                &dest,
+                &base_url,
+                &InstallProgressBar::new(),
+            )
+            .await
+            .exit_context("mirroring packages");
+
+        println!("mirrored {mirrored} packages to {}", self.dir.display());
+        println!("index: {}", base_url.join("index.json").unwrap());
+    }
+}
+
+/// Build index.json of a repository from package zip files in a directory
+///
+/// The zip files in the directory are searched recursively.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct RepoBuild {
+    /// Path to the directory with package zip files. index.json will be written to this directory
+    dir: PathBuf,
+
+    /// The URL the directory will be served at
+    #[arg(long)]
+    base_url: Url,
+
+    /// The name of the repository. by default the name of the directory will be used
+    #[arg(long)]
+    name: Option<String>,
+
+    /// The id of the repository. by default the URL of index.json will be used
+    #[arg(long)]
+    id: Option<String>,
+}
+
+impl RepoBuild {
+    pub async fn run(self) {
+        let dir = tokio::fs::canonicalize(&self.dir)
+            .await
+            .exit_context("resolving repository directory");
+        let name = match self.name {
+            Some(name) => name,
+            None => (dir.file_name())
+                .map(|x| x.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "Repository".to_owned()),
+        };
+
+        let io = DefaultEnvironmentIo::new(dir.clone().into_boxed_path());
+        let (repo, warnings) =
+            RemoteRepository::build_from_zips(&io, &self.base_url, &name, self.id.as_deref())
+                .await
+                .exit_context("building repository");
+
+        for warning in &warnings {
+            warn!("{warning}");
+        }
+
+        let json = serde_json::to_string_pretty(&repo).unwrap();
+        tokio::fs::write(dir.join("index.json"), json)
+            .await
+            .exit_context("writing index.json");
+
+        let versions = (repo.get_packages())
+            .map(|x| x.all_versions().count())
+            .sum::<usize>();
+        println!(
+            "wrote index.json with {versions} versions of {} packages",
+            repo.get_packages().count()
+        );
+    }
+}
+
+/// Sign index.json of a repository with an ed25519 key
+///
+/// The signature will be written to index.json.sig next to index.json, which should be served
+/// with index.json. The public key to be used with `repo add --public-key` is printed to stdout.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct RepoSign {
+    /// Path to index.json to sign
+    index: PathBuf,
+
+    /// Path to the file with the signing key. Keep this file secret
+    #[arg(long)]
+    key: PathBuf,
+
+    /// Generate a new signing key and save to the key file. The key file must not exist
+    #[arg(long)]
+    generate_key: bool,
+}
+
+impl RepoSign {
+    pub async fn run(self) {
+        let key = if self.generate_key {
+            let key = SigningKey::generate().exit_context("generating signing key");
+            let mut options = std::fs::OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            let mut file = options.open(&self.key).exit_context("creating key file");
+            std::io::Write::write_all(&mut file, key.to_base64().as_bytes())
+                .exit_context("writing key file");
+            key
+        } else {
+            let key = tokio::fs::read_to_string(&self.key)
+                .await
+                .exit_context("reading key file");
+            SigningKey::from_base64(&key).exit_context("parsing key file")
+        };
+
+        let index = tokio::fs::read(&self.index)
+            .await
+            .exit_context("reading index.json");
+        let mut signature_path = self.index.into_os_string();
+        signature_path.push(".sig");
+        let signature_path = PathBuf::from(signature_path);
+        tokio::fs::write(&signature_path, key.sign(&index))
+            .await
+            .exit_context("writing signature");
+
+        eprintln!("wrote {}", signature_path.display());
+        println!("{}", key.public_key());
+    }
+}
+
 #[derive(Parser)]
 pub struct Completion {
     shell: Option<clap_complete::Shell>,
@@ -0,0 +1,244 @@
+use crate::commands::progress::format_bytes;
+use crate::commands::{load_env, load_unity, EnvArgs, ResultExt};
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use vrc_get_vpm::environment::{CacheCleanOptions, CachedPackage};
+use vrc_get_vpm::version::Version;
+
+/// Commands around the cache of the downloaded package zip files
+#[derive(Subcommand)]
+#[command(author, version)]
+pub enum Cache {
+    List(List),
+    Size(Size),
+    Clean(Clean),
+}
+
+multi_command!(Cache is List, Size, Clean);
+
+/// List the package versions in the cache
+///
+/// Versions marked with (partial) only have the leftovers of interrupted or mismatched downloads.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct List {
+    /// With this option, output is printed in json format
+    #[arg(long = "json-format")]
+    json_format: Option<NonZeroU32>,
+
+    #[command(flatten)]
+    env_args: EnvArgs,
+}
+
+impl List {
+    pub async fn run(self) {
+        let env = load_env(&self.env_args).await;
+        let cached = env
+            .get_cached_packages()
+            .await
+            .exit_context("listing cached packages");
+
+        match self.json_format.map(|x| x.get()).unwrap_or(0) {
+            0 => {
+                for package in &cached {
+                    let partial = if package.is_downloaded() {
+                        ""
+                    } else {
+                        " (partial)"
+                    };
+                    println!(
+                        "{}@{}: {}{partial}",
+                        package.name(),
+                        package.version(),
+                        format_bytes(package.size()),
+                    );
+                }
+            }
+            1 => {
+                #[derive(Serialize)]
+                struct CachedPackageInfo<'a> {
+                    name: &'a str,
+                    version: &'a Version,
+                    size: u64,
+                    modified: Option<u64>,
+                    downloaded: bool,
+                }
+                let info = (cached.iter())
+                    .map(|package| CachedPackageInfo {
+                        name: package.name(),
+                        version: package.version(),
+                        size: package.size(),
+                        modified: package
+                            .modified()
+                            .and_then(|x| x.duration_since(UNIX_EPOCH).ok())
+                            .map(|x| x.as_secs()),
+                        downloaded: package.is_downloaded(),
+                    })
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string(&info).unwrap());
+            }
+            v => exit_with!("unsupported json version: {v}"),
+        }
+    }
+}
+
+/// Show the total size of the package cache
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Size {
+    /// Print the size in bytes instead of human readable units
+    #[arg(long)]
+    bytes: bool,
+
+    #[command(flatten)]
+    env_args: EnvArgs,
+}
+
+impl Size {
+    pub async fn run(self) {
+        let env = load_env(&self.env_args).await;
+        let cached = env
+            .get_cached_packages()
+            .await
+            .exit_context("listing cached packages");
+
+        let size = cached.iter().map(CachedPackage::size).sum::<u64>();
+        if self.bytes {
+            println!("{size}");
+        } else {
+            println!("{} in {} versions", format_bytes(size), cached.len());
+        }
+    }
+}
+
+/// Remove the package versions from the cache
+///
+/// Without any options, all cached packages are removed.
+/// The leftovers of interrupted or mismatched downloads are always removed unless
+/// --older-than or --max-size keeps them.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Clean {
+    /// Keep the versions locked in the project. Can be specified multiple times
+    #[arg(long = "keep-project", value_name = "PROJECT")]
+    keep_projects: Vec<Box<Path>>,
+
+    /// Keep the versions locked in the projects registered to vrc-get
+    #[cfg(feature = "experimental-vcc")]
+    #[arg(long)]
+    keep_registered: bool,
+
+    /// Keep the latest N versions of each package
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    keep_latest: usize,
+
+    /// Remove only the versions downloaded more than DAYS days ago.
+    /// Installing the versions from the cache does not count
+    #[arg(long, value_name = "DAYS")]
+    older_than: Option<u64>,
+
+    /// Remove the oldest downloaded versions until the cache becomes this size or less.
+    /// Suffixes like K, M or G (in 1024 bytes) are supported
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    max_size: Option<u64>,
+
+    /// Show the versions to be removed without removing them
+    #[arg(long)]
+    dry_run: bool,
+
+    #[command(flatten)]
+    env_args: EnvArgs,
+}
+
+impl Clean {
+    pub async fn run(self) {
+        let env = load_env(&self.env_args).await;
+
+        let mut locked = vec![];
+        for project in self.keep_projects {
+            let unity = load_unity(Some(project)).await;
+            locked.extend(
+                (unity.locked_packages())
+                    .map(|x| (Box::<str>::from(x.name()), x.version().clone())),
+            );
+        }
+        #[cfg(feature = "experimental-vcc")]
+        if self.keep_registered {
+            locked.extend(
+                env.get_locked_packages_of_projects()
+                    .await
+                    .exit_context("loading registered projects"),
+            );
+        }
+
+        let mut options = CacheCleanOptions::new()
+            .keep_versions(
+                locked
+                    .iter()
+                    .map(|(name, version)| (name.as_ref(), version)),
+            )
+            .keep_latest(self.keep_latest);
+        if let Some(days) = self.older_than {
+            options = options.older_than(Duration::from_secs(days * 24 * 60 * 60));
+        }
+        if let Some(max_size) = self.max_size {
+            options = options.max_size(max_size);
+        }
+
+        let cached = env
+            .get_cached_packages()
+            .await
+            .exit_context("listing cached packages");
+        let selected = options.select(&cached);
+
+        for package in &selected {
+            let age = package
+                .modified()
+                .and_then(|x| SystemTime::now().duration_since(x).ok())
+                .map(|x| format!(", {} days old", x.as_secs() / (24 * 60 * 60)))
+                .unwrap_or_default();
+            println!(
+                "removing {}@{} ({}{age})",
+                package.name(),
+                package.version(),
+                format_bytes(package.size()),
+            );
+        }
+
+        if self.dry_run {
+            let size = selected.iter().map(|x| x.size()).sum::<u64>();
+            println!("{} would be freed", format_bytes(size));
+            return;
+        }
+
+        let freed = env
+            .remove_cached_packages(&selected)
+            .await
+            .exit_context("removing cached packages");
+        println!("{} freed", format_bytes(freed));
+    }
+}
+
+fn parse_size(size: &str) -> Result<u64, String> {
+    let size = size.trim();
+    let (number, unit) = match size.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => size.split_at(index),
+        None => (size, ""),
+    };
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        _ => return Err(format!("unknown size unit: {unit}")),
+    };
+    let number = number
+        .parse::<u64>()
+        .map_err(|e| format!("invalid size: {e}"))?;
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| "size is too large".to_owned())
+}
@@ -90,6 +90,22 @@ impl Project {
                 );
             }
         }
+
+        let active_overrides = unity.active_overrides();
+        if !active_overrides.is_empty() {
+            eprintln!();
+            eprintln!("Overridden Dependencies:");
+
+            for active in active_overrides {
+                eprintln!(
+                    "{dependency} required by {dependent}: {declared} overridden with {overridden}",
+                    dependency = active.dependency(),
+                    dependent = active.dependent(),
+                    declared = active.declared(),
+                    overridden = active.overridden(),
+                );
+            }
+        }
     }
 
     pub async fn version1(unity: &UnityProject) {
@@ -97,6 +113,8 @@ impl Project {
         struct Project<'a> {
             unity_version: Option<UnityVersion>,
             packages: &'a [PackageInfo<'a>],
+            // since 1.9.0
+            overrides: &'a [OverrideInfo<'a>],
         }
 
         #[derive(Serialize)]
@@ -107,6 +125,14 @@ impl Project {
             requested: Vec<&'a VersionRange>,
         }
 
+        #[derive(Serialize)]
+        struct OverrideInfo<'a> {
+            dependent: &'a str,
+            dependency: &'a str,
+            declared: &'a VersionRange,
+            overridden: &'a VersionRange,
+        }
+
         let mut packages = vec![];
 
         for locked in unity.locked_packages() {
@@ -155,9 +181,21 @@ impl Project {
             });
         }
 
+        let overrides = unity
+            .active_overrides()
+            .into_iter()
+            .map(|active| OverrideInfo {
+                dependent: active.dependent(),
+                dependency: active.dependency(),
+                declared: active.declared(),
+                overridden: active.overridden(),
+            })
+            .collect::<Vec<_>>();
+
         let project = Project {
             unity_version: unity.unity_version(),
             packages: packages.as_slice(),
+            overrides: overrides.as_slice(),
         };
 
         println!("{}", serde_json::to_string(&project).unwrap());
@@ -0,0 +1,100 @@
+use crate::commands::{load_unity, save_unity};
+use clap::{Parser, Subcommand};
+use log::warn;
+use std::path::Path;
+use vrc_get_vpm::version::VersionRange;
+
+/// Commands around the project-level dependency overrides
+///
+/// The overridden version range is used in place of the range declared in `vpmDependencies`
+/// of every package depending on the package.
+/// Run `vrc-get resolve` or `vrc-get upgrade` after changing overrides to apply them.
+#[derive(Subcommand)]
+#[command(author, version)]
+pub enum Override {
+    Add(Add),
+    Remove(Remove),
+    List(List),
+}
+
+multi_command!(Override is Add, Remove, List);
+
+/// Override the version range of the dependency
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Add {
+    /// Name of the overridden package
+    #[arg()]
+    name: String,
+    /// The version range used in place of the declared ranges
+    #[arg()]
+    range: VersionRange,
+
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+}
+
+impl Add {
+    pub async fn run(self) {
+        let mut unity = load_unity(self.project).await;
+
+        unity.set_override(&self.name, self.range);
+
+        save_unity(&mut unity).await;
+    }
+}
+
+/// Remove the overrides of the dependencies
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Remove {
+    /// Names of the overridden packages
+    #[arg(required = true)]
+    names: Vec<String>,
+
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+}
+
+impl Remove {
+    pub async fn run(self) {
+        let mut unity = load_unity(self.project).await;
+
+        for name in &self.names {
+            if !unity.remove_override(name) {
+                warn!("package {name} is not overridden");
+            }
+        }
+
+        save_unity(&mut unity).await;
+    }
+}
+
+/// List the overrides and where they are active
+#[derive(Parser)]
+#[command(author, version)]
+pub struct List {
+    /// Path to project dir. by default CWD or parents of CWD will be used
+    #[arg(short = 'p', long = "project")]
+    project: Option<Box<Path>>,
+}
+
+impl List {
+    pub async fn run(self) {
+        let unity = load_unity(self.project).await;
+
+        let active_overrides = unity.active_overrides();
+        for (name, range) in unity.overrides() {
+            println!("{name}: {range}");
+            for active in active_overrides.iter().filter(|x| x.dependency() == name) {
+                println!(
+                    "  required by {dependent}: {declared}",
+                    dependent = active.dependent(),
+                    declared = active.declared(),
+                );
+            }
+        }
+    }
+}
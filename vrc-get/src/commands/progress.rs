@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use vrc_get_vpm::{InstallProgress, PackageManifest};
+
+const BAR_WIDTH: usize = 30;
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Shows the progress of downloading and extracting packages on the terminal.
+///
+/// Nothing will be shown if the stderr is not a terminal.
+pub(super) struct InstallProgressBar {
+    enabled: bool,
+    state: Mutex<ProgressState>,
+}
+
+#[derive(Default)]
+struct ProgressState {
+    last_drawn: Option<Instant>,
+    download_totals: HashMap<Box<str>, Option<u64>>,
+    extract_totals: HashMap<Box<str>, usize>,
+}
+
+impl InstallProgressBar {
+    pub fn new() -> Self {
+        Self {
+            enabled: std::io::stderr().is_terminal(),
+            state: Mutex::new(ProgressState::default()),
+        }
+    }
+
+    fn draw(&self, state: &mut ProgressState, force: bool, message: String, ratio: Option<f64>) {
+        let now = Instant::now();
+        if !force {
+            if let Some(last_drawn) = state.last_drawn {
+                if now - last_drawn < REDRAW_INTERVAL {
+                    return;
+                }
+            }
+        }
+        state.last_drawn = Some(now);
+
+        let mut line = String::new();
+        if let Some(ratio) = ratio {
+            let filled = ((ratio.clamp(0.0, 1.0) * BAR_WIDTH as f64) as usize).min(BAR_WIDTH);
+            line.push('[');
+            line.push_str(&"=".repeat(filled));
+            line.push_str(&" ".repeat(BAR_WIDTH - filled));
+            line.push_str("] ");
+        }
+        line.push_str(&message);
+
+        let mut stderr = std::io::stderr().lock();
+        // clear the line and draw
+        write!(stderr, "\r\x1b[2K{line}").ok();
+        stderr.flush().ok();
+    }
+
+    fn clear(&self, state: &mut ProgressState) {
+        if state.last_drawn.take().is_some() {
+            let mut stderr = std::io::stderr().lock();
+            write!(stderr, "\r\x1b[2K").ok();
+            stderr.flush().ok();
+        }
+    }
+}
+
+impl InstallProgress for InstallProgressBar {
+    fn download_started(&self, package: &PackageManifest, total: Option<u64>) {
+        if !self.enabled {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.download_totals.insert(package.name().into(), total);
+        let message = format!("Downloading {}@{}", package.name(), package.version());
+        self.draw(&mut state, true, message, total.map(|_| 0.0));
+    }
+
+    fn download_progress(&self, package: &PackageManifest, downloaded: u64) {
+        if !self.enabled {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        let total = state.download_totals.get(package.name()).copied().flatten();
+        let message = match total {
+            Some(total) => format!(
+                "Downloading {}@{} {} / {}",
+                package.name(),
+                package.version(),
+                format_bytes(downloaded),
+                format_bytes(total),
+            ),
+            None => format!(
+                "Downloading {}@{} {}",
+                package.name(),
+                package.version(),
+                format_bytes(downloaded),
+            ),
+        };
+        let ratio = total.map(|total| downloaded as f64 / total.max(1) as f64);
+        self.draw(&mut state, false, message, ratio);
+    }
+
+    fn extract_started(&self, package: &PackageManifest, total: usize) {
+        if !self.enabled {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.extract_totals.insert(package.name().into(), total);
+        let message = format!("Extracting {}@{}", package.name(), package.version());
+        self.draw(&mut state, true, message, Some(0.0));
+    }
+
+    fn extract_progress(&self, package: &PackageManifest, extracted: usize) {
+        if !self.enabled {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        let total = state
+            .extract_totals
+            .get(package.name())
+            .copied()
+            .unwrap_or(0);
+        let message = format!(
+            "Extracting {}@{} {extracted} / {total} files",
+            package.name(),
+            package.version(),
+        );
+        let ratio = extracted as f64 / total.max(1) as f64;
+        self.draw(&mut state, false, message, Some(ratio));
+    }
+
+    fn package_installed(&self, package: &PackageManifest) {
+        if !self.enabled {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.download_totals.remove(package.name());
+        state.extract_totals.remove(package.name());
+        // keep the bar while other packages are still downloaded or extracted
+        if state.download_totals.is_empty() && state.extract_totals.is_empty() {
+            self.clear(&mut state);
+        }
+    }
+}
+
+impl Drop for InstallProgressBar {
+    fn drop(&mut self) {
+        if let Ok(state) = self.state.get_mut() {
+            if state.last_drawn.take().is_some() {
+                eprint!("\r\x1b[2K");
+            }
+        }
+    }
+}
+
+pub(super) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} {}", UNITS[0])
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
@@ -0,0 +1,128 @@
+use crate::commands::{load_env, load_unity, EnvArgs, ResultExt};
+use clap::{Parser, Subcommand};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Commands around the package store
+///
+/// The package store is enabled with `"packageStore": true` in `vrc-get/settings.json`.
+/// Each version of the remote packages is extracted into the store once and
+/// hard linked into the projects.
+#[derive(Subcommand)]
+#[command(author, version)]
+pub enum Store {
+    List(List),
+    Prune(Prune),
+}
+
+multi_command!(Store is List, Prune);
+
+/// List the package versions in the package store
+#[derive(Parser)]
+#[command(author, version)]
+pub struct List {
+    #[command(flatten)]
+    env_args: EnvArgs,
+}
+
+impl List {
+    pub async fn run(self) {
+        let env = load_env(&self.env_args).await;
+        if !env.use_package_store() {
+            log::warn!("the package store is not enabled");
+        }
+
+        let stored = env
+            .get_stored_packages()
+            .await
+            .exit_context("listing stored packages");
+        for (name, version) in &stored {
+            println!("{name}@{version}");
+        }
+    }
+}
+
+/// Remove the package versions not used by the projects from the package store
+///
+/// Either the versions to keep or --all must be specified.
+/// The projects are not affected by removing versions from the store,
+/// but the removed versions will be extracted again when installed next time.
+#[derive(Parser)]
+#[command(author, version)]
+pub struct Prune {
+    /// Keep the versions locked in the project. Can be specified multiple times
+    #[arg(long = "keep-project", value_name = "PROJECT")]
+    keep_projects: Vec<Box<Path>>,
+
+    /// Keep the versions locked in the projects registered to vrc-get
+    #[cfg(feature = "experimental-vcc")]
+    #[arg(long, conflicts_with = "all")]
+    keep_registered: bool,
+
+    /// Remove all versions in the package store
+    #[arg(long, conflicts_with = "keep_projects")]
+    all: bool,
+
+    /// Show the versions to be removed without removing them
+    #[arg(long)]
+    dry_run: bool,
+
+    #[command(flatten)]
+    env_args: EnvArgs,
+}
+
+impl Prune {
+    pub async fn run(self) {
+        #[cfg(feature = "experimental-vcc")]
+        let keep_registered = self.keep_registered;
+        #[cfg(not(feature = "experimental-vcc"))]
+        let keep_registered = false;
+
+        if self.keep_projects.is_empty() && !keep_registered && !self.all {
+            exit_with!("specify the projects to keep the versions with --keep-project, or --all to remove all versions");
+        }
+
+        let env = load_env(&self.env_args).await;
+
+        let mut locked = vec![];
+        for project in self.keep_projects {
+            let unity = load_unity(Some(project)).await;
+            locked.extend(
+                (unity.locked_packages())
+                    .map(|x| (Box::<str>::from(x.name()), x.version().clone())),
+            );
+        }
+        #[cfg(feature = "experimental-vcc")]
+        if keep_registered {
+            locked.extend(
+                env.get_locked_packages_of_projects()
+                    .await
+                    .exit_context("loading registered projects"),
+            );
+        }
+
+        if self.dry_run {
+            let keep = locked.into_iter().collect::<HashSet<_>>();
+            let stored = env
+                .get_stored_packages()
+                .await
+                .exit_context("listing stored packages");
+            for (name, version) in stored.iter().filter(|x| !keep.contains(x)) {
+                println!("removing {name}@{version}");
+            }
+            return;
+        }
+
+        let removed = env
+            .prune_package_store(
+                locked
+                    .iter()
+                    .map(|(name, version)| (name.as_ref(), version)),
+            )
+            .await
+            .exit_context("pruning package store");
+        for (name, version) in &removed {
+            println!("removed {name}@{version}");
+        }
+    }
+}
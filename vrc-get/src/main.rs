@@ -3,7 +3,6 @@
 extern crate core;
 
 use clap::Parser;
-use reqwest::Client;
 
 mod commands;
 
@@ -42,16 +41,3 @@ fn init_log() {
     }
     builder.init();
 }
-
-pub(crate) fn create_client(offline: bool) -> Option<Client> {
-    if offline {
-        None
-    } else {
-        Some(
-            Client::builder()
-                .user_agent(concat!("vrc-get/", env!("CARGO_PKG_VERSION")))
-                .build()
-                .expect("building client"),
-        )
-    }
-}